@@ -1,16 +1,268 @@
-use uom::si::{f32::{Ratio}, ratio::percent};
 use std::time::Duration;
+use uom::si::{electric_charge::ampere_hour, electric_current::ampere, electric_potential::volt, f32::{ElectricCurrent, ElectricPotential, Frequency, Ratio, ThermodynamicTemperature}, frequency::hertz, ratio::{percent, ratio}, thermodynamic_temperature::degree_celsius};
+
+use crate::{ecam::{Alert, AlertLevel, AttentionGetters, FlightPhase, FlightWarningComputer}, electrical::{ApuGenerator, AuxiliaryPowerUnit, Battery, CircuitBreaker, Contactor, Current, ElectricalBus, EmergencyGenerator, EngineGenerator, ExternalPowerSource, Feeders, GeneratorControlUnit, Paralleling, PowerConductor, PowerSource, PowerConsumer, Powerable, TransformerRectifier, TransformerRectifierMonitor}, overhead::{NormalAltnPushButton, OnOffPushButton}, shared::{Aircraft, DelayedTrueLogicGate, Engine, Failures, UpdateContext}, simulator::{SimulatorElement, SimulatorElementVisitor, SimulatorReader, SimulatorWriter}, scenario::ScenarioAircraft, snapshot::{StateVisitor, Stateful, visit_element, visit_variant}, timeseries::{Sample, SampleValue}};
+
+/// The A320 with all its systems and panels. Hosts set the inputs, e.g. by reading them from the
+/// simulator, and then advance the aircraft by calling `tick`.
+pub struct A320 {
+    engine_1: Engine,
+    engine_2: Engine,
+    apu: AuxiliaryPowerUnit,
+    ext_pwr: ExternalPowerSource,
+    electrical_overhead: A320ElectricalOverheadPanel,
+    electrical: A320ElectricalCircuit,
+    hydraulic: A320HydraulicCircuit,
+    failures: Failures,
+    flight_phase: FlightPhase,
+    fwc: FlightWarningComputer,
+    attention_getters: AttentionGetters
+}
+
+impl A320 {
+    pub fn new() -> A320 {
+        A320::with_electrical_circuit(A320ElectricalCircuit::new())
+    }
+
+    /// Creates the aircraft with the given electrical circuit, for variants which share the
+    /// A320's systems but differ in their electrical loads.
+    pub fn with_electrical_circuit(electrical: A320ElectricalCircuit) -> A320 {
+        A320 {
+            engine_1: Engine::new(1),
+            engine_2: Engine::new(2),
+            apu: AuxiliaryPowerUnit::new(),
+            ext_pwr: ExternalPowerSource::new(),
+            electrical_overhead: A320ElectricalOverheadPanel::new(),
+            electrical,
+            hydraulic: A320HydraulicCircuit::new(),
+            failures: Failures::new(),
+            flight_phase: FlightPhase::ElectricalPower,
+            fwc: FlightWarningComputer::new(),
+            attention_getters: AttentionGetters::new()
+        }
+    }
+
+    /// Returns the engine with the given number, or `None` when the A320 has no such engine.
+    pub fn engine(&self, number: u8) -> Option<&Engine> {
+        match number {
+            1 => Some(&self.engine_1),
+            2 => Some(&self.engine_2),
+            _ => None
+        }
+    }
+
+    pub fn engine_mut(&mut self, number: u8) -> Option<&mut Engine> {
+        match number {
+            1 => Some(&mut self.engine_1),
+            2 => Some(&mut self.engine_2),
+            _ => None
+        }
+    }
+
+    pub fn apu_mut(&mut self) -> &mut AuxiliaryPowerUnit {
+        &mut self.apu
+    }
+
+    pub fn ext_pwr_mut(&mut self) -> &mut ExternalPowerSource {
+        &mut self.ext_pwr
+    }
+
+    pub fn electrical_overhead(&self) -> &A320ElectricalOverheadPanel {
+        &self.electrical_overhead
+    }
+
+    pub fn electrical_overhead_mut(&mut self) -> &mut A320ElectricalOverheadPanel {
+        &mut self.electrical_overhead
+    }
+
+    pub fn electrical(&self) -> &A320ElectricalCircuit {
+        &self.electrical
+    }
+
+    /// Exports the electrical circuit as a Graphviz DOT graph.
+    pub fn electrical_dot(&self) -> String {
+        self.electrical.to_dot()
+    }
+
+    pub fn electrical_mut(&mut self) -> &mut A320ElectricalCircuit {
+        &mut self.electrical
+    }
+
+    pub fn failures(&self) -> &Failures {
+        &self.failures
+    }
+
+    pub fn failures_mut(&mut self) -> &mut Failures {
+        &mut self.failures
+    }
+
+    pub fn set_flight_phase(&mut self, flight_phase: FlightPhase) {
+        self.flight_phase = flight_phase;
+    }
+
+    pub fn fwc(&self) -> &FlightWarningComputer {
+        &self.fwc
+    }
+
+    pub fn attention_getters(&self) -> &AttentionGetters {
+        &self.attention_getters
+    }
+
+    pub fn attention_getters_mut(&mut self) -> &mut AttentionGetters {
+        &mut self.attention_getters
+    }
+}
+
+impl Aircraft for A320 {
+    /// Advances all systems by the context's delta. The engines and APU are updated first, as they
+    /// drive the electrical circuit. The electrical circuit is then updated, followed by the hydraulic
+    /// circuit, which draws on the electrical circuit. External power is an input only, which is set
+    /// before the tick. Finally the alerts are updated from the resulting state of the systems.
+    fn tick(&mut self, context: &UpdateContext) {
+        self.failures.update(context);
+
+        self.engine_1.update(context);
+        self.engine_2.update(context);
+        self.apu.update(context);
+
+        self.electrical.update(context, &self.engine_1, &self.engine_2, &self.apu, &self.ext_pwr,
+            &self.hydraulic, &self.electrical_overhead, &self.failures);
+        self.electrical.update_fault_lights(&mut self.electrical_overhead);
+
+        self.hydraulic.update(context);
+
+        self.fwc.update(self.flight_phase, self.electrical.triggered_alerts(&self.electrical_overhead));
+        self.attention_getters.update(&self.fwc, self.electrical.attention_getters_are_powered());
+    }
+
+    fn failures_mut(&mut self) -> &mut Failures {
+        &mut self.failures
+    }
+}
+
+impl SimulatorElement for A320 {
+    fn accept<T: SimulatorElementVisitor>(&mut self, visitor: &mut T) {
+        self.engine_1.accept(visitor);
+        self.engine_2.accept(visitor);
+        self.apu.accept(visitor);
+        self.ext_pwr.accept(visitor);
+        self.electrical_overhead.accept(visitor);
+        self.electrical.accept(visitor);
+        visitor.visit(self);
+    }
+}
+
+impl ScenarioAircraft for A320 {
+    fn bus_output(&self, id: &str) -> Option<Current> {
+        self.electrical.bus_output(id)
+    }
+
+    fn alert_is_active(&self, title: &str) -> bool {
+        self.fwc.is_active(title)
+    }
+
+    fn set_flight_phase(&mut self, flight_phase: FlightPhase) {
+        self.flight_phase = flight_phase;
+    }
+
+    fn sample(&self) -> Sample {
+        self.electrical.sample()
+    }
+
+    fn paralleled_sources(&self) -> Vec<Paralleling> {
+        self.electrical.paralleled_sources()
+    }
+
+    fn failure_ids(&self) -> Vec<String> {
+        self.electrical.failure_ids()
+    }
+
+    fn push_button_input(&self, push_button: &str, position: &str) -> Result<(String, bool), String> {
+        A320ElectricalOverheadPanel::push_button_input(push_button, position)
+    }
+}
 
-use crate::{electrical::{ApuGenerator, AuxiliaryPowerUnit, Battery, Contactor, ElectricalBus, EmergencyGenerator, EngineGenerator, ExternalPowerSource, PowerConductor, Powerable, TransformerRectifier}, overhead::{self, NormalAltnPushButton, OnOffPushButton}, shared::{DelayedTrueLogicGate, Engine, UpdateContext}};
+impl Stateful for A320 {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_element(state, "ENGINE 1", &mut self.engine_1);
+        visit_element(state, "ENGINE 2", &mut self.engine_2);
+        visit_element(state, "APU", &mut self.apu);
+        visit_element(state, "EXT PWR", &mut self.ext_pwr);
+        visit_element(state, "ELECTRICAL OVERHEAD", &mut self.electrical_overhead);
+        visit_element(state, "ELECTRICAL", &mut self.electrical);
+        visit_element(state, "HYDRAULIC", &mut self.hydraulic);
+        visit_element(state, "FAILURES", &mut self.failures);
+        visit_variant(state, "FLIGHT PHASE", &mut self.flight_phase, &[FlightPhase::ElectricalPower, FlightPhase::FirstEngineStarted,
+            FlightPhase::FirstEngineTakeoffPower, FlightPhase::EightyKnots, FlightPhase::LiftOff, FlightPhase::AboveFifteenHundredFeet,
+            FlightPhase::BelowEightHundredFeet, FlightPhase::Touchdown, FlightPhase::BelowEightyKnots, FlightPhase::SecondEngineShutdown]);
+
+        state.enter("FWC");
+        self.fwc.visit_state(state, ELECTRICAL_ALERTS);
+        state.leave();
+
+        state.enter("ATTENTION GETTERS");
+        self.attention_getters.visit_state(state, ELECTRICAL_ALERTS);
+        state.leave();
+    }
+}
+
+/// Cautions are inhibited during takeoff and landing, so as not to distract the crew.
+const TAKEOFF_AND_LANDING: &[FlightPhase] = &[FlightPhase::FirstEngineTakeoffPower, FlightPhase::EightyKnots, FlightPhase::LiftOff,
+    FlightPhase::BelowEightHundredFeet, FlightPhase::Touchdown];
+/// With both engines stopped on the ground, unpowered AC buses are the normal cold and dark state.
+const ENGINES_STOPPED_ON_GROUND: &[FlightPhase] = &[FlightPhase::ElectricalPower, FlightPhase::SecondEngineShutdown];
+
+pub const ELEC_EMER_CONFIG: Alert = Alert::new("ELEC EMER CONFIG", AlertLevel::Level3, ENGINES_STOPPED_ON_GROUND,
+    &["MIN RAT SPEED.....140 KT", "GEN 1+2.....OFF THEN ON", "BUS TIE.....OFF", "EMER ELEC PWR.....MAN ON"]);
+pub const ELEC_GEN_1_FAULT: Alert = Alert::new("ELEC GEN 1 FAULT", AlertLevel::Level2, TAKEOFF_AND_LANDING,
+    &["GEN 1.....OFF THEN ON", "IF UNSUCCESSFUL, GEN 1.....OFF"]);
+pub const ELEC_GEN_2_FAULT: Alert = Alert::new("ELEC GEN 2 FAULT", AlertLevel::Level2, TAKEOFF_AND_LANDING,
+    &["GEN 2.....OFF THEN ON", "IF UNSUCCESSFUL, GEN 2.....OFF"]);
+pub const ELEC_APU_GEN_FAULT: Alert = Alert::new("ELEC APU GEN FAULT", AlertLevel::Level2, TAKEOFF_AND_LANDING,
+    &["APU GEN.....OFF THEN ON", "IF UNSUCCESSFUL, APU GEN.....OFF"]);
+pub const ELEC_AC_BUS_1_FAULT: Alert = Alert::new("ELEC AC BUS 1 FAULT", AlertLevel::Level2, TAKEOFF_AND_LANDING, &[]);
+pub const ELEC_AC_BUS_2_FAULT: Alert = Alert::new("ELEC AC BUS 2 FAULT", AlertLevel::Level2, TAKEOFF_AND_LANDING, &[]);
+pub const ELEC_AC_ESS_BUS_FAULT: Alert = Alert::new("ELEC AC ESS BUS FAULT", AlertLevel::Level2, TAKEOFF_AND_LANDING, &["AC ESS FEED.....ALTN"]);
+pub const ELEC_DC_BUS_1_FAULT: Alert = Alert::new("ELEC DC BUS 1 FAULT", AlertLevel::Level2, TAKEOFF_AND_LANDING, &[]);
+pub const ELEC_DC_BUS_2_FAULT: Alert = Alert::new("ELEC DC BUS 2 FAULT", AlertLevel::Level2, TAKEOFF_AND_LANDING, &[]);
+pub const ELEC_DC_BAT_BUS_FAULT: Alert = Alert::new("ELEC DC BAT BUS FAULT", AlertLevel::Level2, TAKEOFF_AND_LANDING, &[]);
+pub const ELEC_DC_ESS_BUS_FAULT: Alert = Alert::new("ELEC DC ESS BUS FAULT", AlertLevel::Level2, TAKEOFF_AND_LANDING, &[]);
+pub const ELEC_TR_1_FAULT: Alert = Alert::new("ELEC TR 1 FAULT", AlertLevel::Level1, TAKEOFF_AND_LANDING, &[]);
+pub const ELEC_TR_2_FAULT: Alert = Alert::new("ELEC TR 2 FAULT", AlertLevel::Level1, TAKEOFF_AND_LANDING, &[]);
+pub const ELEC_BAT_1_FAULT: Alert = Alert::new("ELEC BAT 1 FAULT", AlertLevel::Level2, TAKEOFF_AND_LANDING, &["BAT 1.....OFF"]);
+pub const ELEC_BAT_2_FAULT: Alert = Alert::new("ELEC BAT 2 FAULT", AlertLevel::Level2, TAKEOFF_AND_LANDING, &["BAT 2.....OFF"]);
+pub const ELEC_IDG_1_OIL_LO_PR: Alert = Alert::new("ELEC IDG 1 OIL LO PR", AlertLevel::Level2, TAKEOFF_AND_LANDING, &["IDG 1.....DISC"]);
+pub const ELEC_IDG_2_OIL_LO_PR: Alert = Alert::new("ELEC IDG 2 OIL LO PR", AlertLevel::Level2, TAKEOFF_AND_LANDING, &["IDG 2.....DISC"]);
+pub const ELEC_IDG_1_OIL_OVHT: Alert = Alert::new("ELEC IDG 1 OIL OVHT", AlertLevel::Level2, TAKEOFF_AND_LANDING, &["IDG 1.....DISC"]);
+pub const ELEC_IDG_2_OIL_OVHT: Alert = Alert::new("ELEC IDG 2 OIL OVHT", AlertLevel::Level2, TAKEOFF_AND_LANDING, &["IDG 2.....DISC"]);
+
+/// All alerts which the electrical circuit can trigger.
+pub const ELECTRICAL_ALERTS: &[Alert] = &[ELEC_EMER_CONFIG, ELEC_GEN_1_FAULT, ELEC_GEN_2_FAULT, ELEC_APU_GEN_FAULT, ELEC_AC_BUS_1_FAULT,
+    ELEC_AC_BUS_2_FAULT, ELEC_AC_ESS_BUS_FAULT, ELEC_DC_BUS_1_FAULT, ELEC_DC_BUS_2_FAULT, ELEC_DC_BAT_BUS_FAULT, ELEC_DC_ESS_BUS_FAULT,
+    ELEC_TR_1_FAULT, ELEC_TR_2_FAULT, ELEC_BAT_1_FAULT, ELEC_BAT_2_FAULT, ELEC_IDG_1_OIL_LO_PR, ELEC_IDG_2_OIL_LO_PR, ELEC_IDG_1_OIL_OVHT,
+    ELEC_IDG_2_OIL_OVHT];
+
+/// How a powerable conductor of the circuit is wired, as recorded while it was powered.
+struct Wiring<'a> {
+    id: &'a str,
+    feeders: &'a Feeders,
+    input: Current,
+    output: Current,
+    /// Whether the conductor is a closed contactor, or `None` when it isn't a contactor.
+    closed: Option<bool>
+}
 
 pub struct A320ElectricalCircuit {
     engine_1_gen: EngineGenerator,
+    engine_1_gcu: GeneratorControlUnit,
     engine_1_gen_contactor: Contactor,
     engine_2_gen: EngineGenerator,
+    engine_2_gcu: GeneratorControlUnit,
     engine_2_gen_contactor: Contactor,
     bus_tie_1_contactor: Contactor,
     bus_tie_2_contactor: Contactor,
     apu_gen: ApuGenerator,
+    apu_gcu: GeneratorControlUnit,
     apu_gen_contactor: Contactor,
     ext_pwr_contactor: Contactor,
     ac_bus_1: ElectricalBus,
@@ -19,12 +271,14 @@ pub struct A320ElectricalCircuit {
     ac_ess_feed_contactor_1: Contactor,
     ac_ess_feed_contactor_2: Contactor,
     ac_ess_feed_contactor_delay_logic_gate: DelayedTrueLogicGate,
-    // The electrical diagram lists separate contactors for each transformer rectifier.
-    // As there is no button affecting the contactor, nor any logic that we know of, for now
-    // the contactors are just assumed to be part of the transformer rectifiers.
     tr_1: TransformerRectifier,
+    tr_1_monitor: TransformerRectifierMonitor,
+    tr_1_contactor: Contactor,
     tr_2: TransformerRectifier,
+    tr_2_monitor: TransformerRectifierMonitor,
+    tr_2_contactor: Contactor,
     tr_ess: TransformerRectifier,
+    tr_ess_contactor: Contactor,
     ac_ess_to_tr_ess_contactor: Contactor,
     emergency_gen: EmergencyGenerator,
     emergency_gen_contactor: Contactor,
@@ -33,72 +287,111 @@ pub struct A320ElectricalCircuit {
     dc_bus_1_tie_contactor: Contactor,
     dc_bus_2_tie_contactor: Contactor,
     dc_bat_bus: ElectricalBus,
+    dc_ess_bus: ElectricalBus,
+    dc_bat_bus_to_dc_ess_bus_contactor: Contactor,
     battery_1: Battery,
     battery_1_contactor: Contactor,
     battery_2: Battery,
-    battery_2_contactor: Contactor
+    battery_2_contactor: Contactor,
+    protected_consumers: Vec<A320ProtectedConsumer>
 }
 
 impl A320ElectricalCircuit {
     const AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS: Duration = Duration::from_secs(3);
+    /// The master warning and master caution lights on the glareshield.
+    const ATTENTION_GETTERS: &'static str = "MASTER WARN/CAUT LTS";
+    /// The load drawn by the consumers of a powered DC bus which are not modelled. A TR supplying a
+    /// powered DC bus therefore never reaches its undercurrent threshold, even when all modelled
+    /// consumers on that bus are unpowered.
+    pub const UNMODELLED_DC_BUS_LOAD_IN_AMPERES: f32 = 2.;
 
     pub fn new() -> A320ElectricalCircuit {
+        A320ElectricalCircuit::with_consumers(A320ProtectedConsumer::a320_consumers())
+    }
+
+    /// Creates the circuit supplying the given consumers, such that aircraft variants sharing
+    /// the A320's electrical architecture can define their own loads.
+    pub fn with_consumers(protected_consumers: Vec<A320ProtectedConsumer>) -> A320ElectricalCircuit {
         A320ElectricalCircuit {
             engine_1_gen: EngineGenerator::new(1),
+            engine_1_gcu: GeneratorControlUnit::new(),
             engine_1_gen_contactor: Contactor::new(String::from("9XU1")),
             engine_2_gen: EngineGenerator::new(2),
+            engine_2_gcu: GeneratorControlUnit::new(),
             engine_2_gen_contactor: Contactor::new(String::from("9XU2")),
             bus_tie_1_contactor: Contactor::new(String::from("11XU1")),
             bus_tie_2_contactor: Contactor::new(String::from("11XU2")),
             apu_gen: ApuGenerator::new(),
+            apu_gcu: GeneratorControlUnit::new(),
             apu_gen_contactor: Contactor::new(String::from("3XS")),
             ext_pwr_contactor: Contactor::new(String::from("3XG")),
-            ac_bus_1: ElectricalBus::new(),
-            ac_bus_2: ElectricalBus::new(),
-            ac_ess_bus: ElectricalBus::new(),
+            ac_bus_1: ElectricalBus::new(String::from("AC BUS 1")),
+            ac_bus_2: ElectricalBus::new(String::from("AC BUS 2")),
+            ac_ess_bus: ElectricalBus::new(String::from("AC ESS BUS")),
             ac_ess_feed_contactor_1: Contactor::new(String::from("3XC1")),
             ac_ess_feed_contactor_2: Contactor::new(String::from("3XC2")),
             ac_ess_feed_contactor_delay_logic_gate: DelayedTrueLogicGate::new(A320ElectricalCircuit::AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS),
-            tr_1: TransformerRectifier::new(),
-            tr_2: TransformerRectifier::new(),
-            tr_ess: TransformerRectifier::new(),
+            tr_1: TransformerRectifier::new(String::from("TR 1")),
+            tr_1_monitor: TransformerRectifierMonitor::new(),
+            tr_1_contactor: Contactor::new(String::from("5PU1")),
+            tr_2: TransformerRectifier::new(String::from("TR 2")),
+            tr_2_monitor: TransformerRectifierMonitor::new(),
+            tr_2_contactor: Contactor::new(String::from("5PU2")),
+            tr_ess: TransformerRectifier::new(String::from("ESS TR")),
+            tr_ess_contactor: Contactor::new(String::from("3PE")),
             ac_ess_to_tr_ess_contactor: Contactor::new(String::from("15XE1")),
             emergency_gen: EmergencyGenerator::new(),
             emergency_gen_contactor: Contactor::new(String::from("2XE")),
-            dc_bus_1: ElectricalBus::new(),
+            dc_bus_1: ElectricalBus::new(String::from("DC BUS 1")),
             dc_bus_1_tie_contactor: Contactor::new(String::from("1PC1")),
-            dc_bus_2: ElectricalBus::new(),
+            dc_bus_2: ElectricalBus::new(String::from("DC BUS 2")),
             dc_bus_2_tie_contactor: Contactor::new(String::from("1PC2")),
-            dc_bat_bus: ElectricalBus::new(),
+            dc_bat_bus: ElectricalBus::new(String::from("DC BAT BUS")),
+            dc_ess_bus: ElectricalBus::new(String::from("DC ESS BUS")),
+            dc_bat_bus_to_dc_ess_bus_contactor: Contactor::new(String::from("4PC")),
             battery_1: Battery::full(1),
             battery_1_contactor: Contactor::new(String::from("6PB1")),
             battery_2: Battery::full(2),
-            battery_2_contactor: Contactor::new(String::from("6PB2"))
+            battery_2_contactor: Contactor::new(String::from("6PB2")),
+            protected_consumers
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update(&mut self, context: &UpdateContext, engine1: &Engine, engine2: &Engine, apu: &AuxiliaryPowerUnit,
-        ext_pwr: &ExternalPowerSource, hydraulic: &A320HydraulicCircuit, elec_overhead: &A320ElectricalOverheadPanel) {
-        self.engine_1_gen.update(engine1, &elec_overhead.idg_1);
-        self.engine_2_gen.update(engine2, &elec_overhead.idg_2);
-        self.apu_gen.update(apu);
+        ext_pwr: &ExternalPowerSource, hydraulic: &A320HydraulicCircuit, elec_overhead: &A320ElectricalOverheadPanel, failures: &Failures) {
+        self.update_failures(failures);
+
+        self.engine_1_gen.update(context, engine1, &elec_overhead.idg_1, failures);
+        self.engine_2_gen.update(context, engine2, &elec_overhead.idg_2, failures);
+        self.apu_gen.update(context, apu, failures);
+        self.engine_1_gen.update_idg_temperature(context);
+        self.engine_2_gen.update_idg_temperature(context);
         self.emergency_gen.update(hydraulic.is_blue_pressurised());
 
-        let gen_1_provides_power = elec_overhead.gen_1.is_on() && self.engine_1_gen.output().is_powered();
-        let gen_2_provides_power = elec_overhead.gen_2.is_on() && self.engine_2_gen.output().is_powered();
+        self.engine_1_gcu.update(context, &self.engine_1_gen, &elec_overhead.gen_1);
+        self.engine_2_gcu.update(context, &self.engine_2_gen, &elec_overhead.gen_2);
+        self.apu_gcu.update(context, &self.apu_gen, &elec_overhead.apu_gen);
+
+        let gen_1_provides_power = self.engine_1_gcu.closes_line_contactor();
+        let gen_2_provides_power = self.engine_2_gcu.closes_line_contactor();
         let no_engine_gen_provides_power = !gen_1_provides_power && !gen_2_provides_power;
         let only_one_engine_gen_is_powered = gen_1_provides_power ^ gen_2_provides_power;
         let ext_pwr_provides_power = elec_overhead.ext_pwr.is_on() && ext_pwr.output().is_powered() && (no_engine_gen_provides_power || only_one_engine_gen_is_powered);
-        let apu_gen_provides_power = elec_overhead.apu_gen.is_on() && self.apu_gen.output().is_powered() && !ext_pwr_provides_power && (no_engine_gen_provides_power || only_one_engine_gen_is_powered);
-
-        self.engine_1_gen_contactor.toggle(gen_1_provides_power);
-        self.engine_2_gen_contactor.toggle(gen_2_provides_power);        
-        self.apu_gen_contactor.toggle(apu_gen_provides_power);
-        self.ext_pwr_contactor.toggle(ext_pwr_provides_power);
+        let apu_gen_provides_power = self.apu_gcu.closes_line_contactor() && !ext_pwr_provides_power && (no_engine_gen_provides_power || only_one_engine_gen_is_powered);
 
         let apu_or_ext_pwr_provides_power = ext_pwr_provides_power || apu_gen_provides_power;
-        self.bus_tie_1_contactor.toggle((only_one_engine_gen_is_powered && !apu_or_ext_pwr_provides_power) || (apu_or_ext_pwr_provides_power && !gen_1_provides_power));
-        self.bus_tie_2_contactor.toggle((only_one_engine_gen_is_powered && !apu_or_ext_pwr_provides_power) || (apu_or_ext_pwr_provides_power && !gen_2_provides_power));
+        let bus_tie_1_should_be_closed = (only_one_engine_gen_is_powered && !apu_or_ext_pwr_provides_power) || (apu_or_ext_pwr_provides_power && !gen_1_provides_power);
+        let bus_tie_2_should_be_closed = (only_one_engine_gen_is_powered && !apu_or_ext_pwr_provides_power) || (apu_or_ext_pwr_provides_power && !gen_2_provides_power);
+
+        A320ElectricalCircuit::toggle_break_before_make(context, vec!(
+            (&mut self.engine_1_gen_contactor, gen_1_provides_power),
+            (&mut self.engine_2_gen_contactor, gen_2_provides_power),
+            (&mut self.apu_gen_contactor, apu_gen_provides_power),
+            (&mut self.ext_pwr_contactor, ext_pwr_provides_power),
+            (&mut self.bus_tie_1_contactor, bus_tie_1_should_be_closed),
+            (&mut self.bus_tie_2_contactor, bus_tie_2_should_be_closed)
+        ));
         
         self.apu_gen_contactor.powered_by(vec!(&self.apu_gen));
         self.ext_pwr_contactor.powered_by(vec!(ext_pwr));
@@ -118,36 +411,89 @@ impl A320ElectricalCircuit {
         self.tr_1.powered_by(vec!(&self.ac_bus_1));
         self.tr_2.powered_by(vec!(&self.ac_bus_2));
 
+        // The aircraft only knows about a TR's state through its observed output current and temperature.
+        self.tr_1_monitor.update(context, &self.tr_1);
+        self.tr_2_monitor.update(context, &self.tr_2);
+        let ess_tr_engaged = self.tr_1_monitor.has_fault() || self.tr_2_monitor.has_fault();
+
+        self.tr_1_contactor.toggle(context, !self.tr_1_monitor.has_overtemperature());
+        self.tr_2_contactor.toggle(context, !self.tr_2_monitor.has_overtemperature());
+
         self.ac_ess_feed_contactor_delay_logic_gate.update(context, self.ac_bus_1.output().is_unpowered());
 
-        self.ac_ess_feed_contactor_1.toggle(self.ac_bus_1.output().is_powered() && (!self.ac_ess_feed_contactor_delay_logic_gate.output() && elec_overhead.ac_ess_feed.is_normal()));
-        self.ac_ess_feed_contactor_2.toggle(self.ac_bus_2.output().is_powered() && (self.ac_ess_feed_contactor_delay_logic_gate.output() || elec_overhead.ac_ess_feed.is_altn()));
+        let ac_ess_feed_contactor_1_should_be_closed = self.ac_bus_1.output().is_powered() && (!self.ac_ess_feed_contactor_delay_logic_gate.output() && elec_overhead.ac_ess_feed.is_normal());
+        let ac_ess_feed_contactor_2_should_be_closed = self.ac_bus_2.output().is_powered() && (self.ac_ess_feed_contactor_delay_logic_gate.output() || elec_overhead.ac_ess_feed.is_altn());
+        A320ElectricalCircuit::toggle_break_before_make(context, vec!(
+            (&mut self.ac_ess_feed_contactor_1, ac_ess_feed_contactor_1_should_be_closed),
+            (&mut self.ac_ess_feed_contactor_2, ac_ess_feed_contactor_2_should_be_closed)
+        ));
 
         self.ac_ess_feed_contactor_1.powered_by(vec!(&self.ac_bus_1));
         self.ac_ess_feed_contactor_2.powered_by(vec!(&self.ac_bus_2));
 
         self.ac_ess_bus.powered_by(vec!(&self.ac_ess_feed_contactor_1, &self.ac_ess_feed_contactor_2));
 
-        self.emergency_gen_contactor.toggle(self.ac_bus_1.output().is_unpowered() && self.ac_bus_2.output().is_unpowered());
+        self.emergency_gen_contactor.toggle(context, self.ac_bus_1.output().is_unpowered() && self.ac_bus_2.output().is_unpowered());
         self.emergency_gen_contactor.powered_by(vec!(&self.emergency_gen));
         
         let ac_ess_to_tr_ess_contactor_power_sources: Vec<&dyn PowerConductor> = vec!(&self.ac_ess_bus, &self.emergency_gen_contactor);
         self.ac_ess_to_tr_ess_contactor.powered_by(ac_ess_to_tr_ess_contactor_power_sources);
-        self.ac_ess_to_tr_ess_contactor.toggle(A320ElectricalCircuit::has_failed_or_is_unpowered(&self.tr_1) || A320ElectricalCircuit::has_failed_or_is_unpowered(&self.tr_2));
+        self.ac_ess_to_tr_ess_contactor.toggle(context, ess_tr_engaged);
 
         self.ac_ess_bus.or_powered_by(vec!(&self.ac_ess_to_tr_ess_contactor));
 
         self.tr_ess.powered_by(vec!(&self.ac_ess_to_tr_ess_contactor, &self.emergency_gen_contactor));
 
-        self.dc_bus_1.powered_by(vec!(&self.tr_1));
-        self.dc_bus_2.powered_by(vec!(&self.tr_2));
+        let dc_bus_1_supplied_by_tr_1 = self.tr_1_contactor.is_closed() && self.tr_1.output().is_powered();
+        let dc_bus_2_supplied_by_tr_2 = self.tr_2_contactor.is_closed() && self.tr_2.output().is_powered();
+        self.dc_bus_1_tie_contactor.toggle(context, dc_bus_1_supplied_by_tr_1 || dc_bus_2_supplied_by_tr_2);
+        self.dc_bus_2_tie_contactor.toggle(context, !dc_bus_1_supplied_by_tr_1 || !dc_bus_2_supplied_by_tr_2);
+
+        A320ElectricalCircuit::toggle_break_before_make(context, vec!(
+            (&mut self.tr_ess_contactor, ess_tr_engaged),
+            (&mut self.dc_bat_bus_to_dc_ess_bus_contactor, !ess_tr_engaged)
+        ));
+
+        // A battery is charged by TR 1 or 2 through the DC BAT BUS. When no TR is available at all,
+        // such as on the ground before any AC source is available, the batteries supply the DC BAT BUS instead.
+        let dc_bat_bus_supplied_by_trs = dc_bus_1_supplied_by_tr_1 || dc_bus_2_supplied_by_tr_2;
+        let batteries_supply_dc_bat_bus = !dc_bat_bus_supplied_by_trs && self.tr_ess.output().is_unpowered();
+        self.battery_1_contactor.toggle(context, elec_overhead.bat_1.is_on() &&
+            ((dc_bat_bus_supplied_by_trs && !self.battery_1.is_full()) || batteries_supply_dc_bat_bus));
+        self.battery_2_contactor.toggle(context, elec_overhead.bat_2.is_on() &&
+            ((dc_bat_bus_supplied_by_trs && !self.battery_2.is_full()) || batteries_supply_dc_bat_bus));
+
+        self.power_dc_buses();
+
+        for protected in self.protected_consumers.iter_mut() {
+            let bus = match protected.bus {
+                A320Bus::AcBus1 => &self.ac_bus_1,
+                A320Bus::AcBus2 => &self.ac_bus_2,
+                A320Bus::AcEssBus => &self.ac_ess_bus,
+                A320Bus::DcBus1 => &self.dc_bus_1,
+                A320Bus::DcBus2 => &self.dc_bus_2,
+                A320Bus::DcBatBus => &self.dc_bat_bus,
+                A320Bus::DcEssBus => &self.dc_ess_bus
+            };
+
+            protected.update(context, bus, elec_overhead.galy_and_cab.is_off(), failures);
+        }
+
+        self.update_dc_source_loads(context);
+        // The TR and battery outputs depend on their loads, which are only known once the consumers are updated.
+        self.power_dc_buses();
+    }
+
+    fn power_dc_buses(&mut self) {
+        self.tr_1_contactor.powered_by(vec!(&self.tr_1));
+        self.tr_2_contactor.powered_by(vec!(&self.tr_2));
+
+        self.dc_bus_1.powered_by(vec!(&self.tr_1_contactor));
+        self.dc_bus_2.powered_by(vec!(&self.tr_2_contactor));
 
         self.dc_bus_1_tie_contactor.powered_by(vec!(&self.dc_bus_1));
         self.dc_bus_2_tie_contactor.powered_by(vec!(&self.dc_bus_2));
 
-        self.dc_bus_1_tie_contactor.toggle(self.dc_bus_1.output().is_powered() || self.dc_bus_2.output().is_powered());
-        self.dc_bus_2_tie_contactor.toggle(self.dc_bus_1.output().is_unpowered() || self.dc_bus_2.output().is_unpowered());
-
         self.dc_bat_bus.powered_by(vec!(&self.dc_bus_1_tie_contactor, &self.dc_bus_2_tie_contactor));
 
         self.dc_bus_1_tie_contactor.or_powered_by(vec!(&self.dc_bat_bus));
@@ -155,34 +501,723 @@ impl A320ElectricalCircuit {
         self.dc_bus_1.or_powered_by(vec!(&self.dc_bus_1_tie_contactor));
         self.dc_bus_2.or_powered_by(vec!(&self.dc_bus_2_tie_contactor));
 
+        // The batteries only charge from the TRs. They supply the DC BAT BUS, but not DC BUS 1 and 2.
         self.battery_1_contactor.powered_by(vec!(&self.dc_bat_bus));
         self.battery_2_contactor.powered_by(vec!(&self.dc_bat_bus));
 
-        self.battery_1_contactor.toggle(!self.battery_1.is_full());
-        self.battery_2_contactor.toggle(!self.battery_2.is_full());
-
         self.battery_1.powered_by(vec!(&self.battery_1_contactor));
         self.battery_2.powered_by(vec!(&self.battery_2_contactor));
+
+        self.battery_1_contactor.or_powered_by(vec!(&self.battery_1));
+        self.battery_2_contactor.or_powered_by(vec!(&self.battery_2));
+        self.dc_bat_bus.or_powered_by(vec!(&self.battery_1_contactor, &self.battery_2_contactor));
+
+        self.tr_ess_contactor.powered_by(vec!(&self.tr_ess));
+        self.dc_bat_bus_to_dc_ess_bus_contactor.powered_by(vec!(&self.dc_bat_bus));
+        self.dc_ess_bus.powered_by(vec!(&self.tr_ess_contactor, &self.dc_bat_bus_to_dc_ess_bus_contactor));
+    }
+
+    /// A DC bus is supplied by its own TR whenever that TR is connected. Otherwise it is supplied
+    /// through the DC BAT BUS by the other TR. The DC BAT BUS is supplied through DC BUS 1 when possible.
+    /// The DC ESS BUS is supplied by the ESS TR when connected, otherwise through the DC BAT BUS.
+    /// A battery supplying the DC BAT BUS discharges by the load of that bus.
+    fn update_dc_source_loads(&mut self, context: &UpdateContext) {
+        let no_load = ElectricCurrent::new::<ampere>(0.);
+        let dc_ess_bus_load = self.bus_load(A320Bus::DcEssBus);
+        let dc_ess_bus_supplied_by_ess_tr = self.tr_ess_contactor.is_closed() && self.tr_ess.output().is_powered();
+        let dc_ess_bus_supplied_through_dc_bat_bus = self.dc_bat_bus_to_dc_ess_bus_contactor.is_closed() && self.dc_bat_bus.output().is_powered();
+        let dc_bat_bus_load = self.bus_load(A320Bus::DcBatBus) + if dc_ess_bus_supplied_through_dc_bat_bus { dc_ess_bus_load } else { no_load };
+        let dc_bat_bus_supplied_through_dc_bus_1 = self.dc_bus_1_tie_contactor.is_closed() && self.dc_bus_1.output().is_powered();
+        let dc_bus_1_load = self.bus_load(A320Bus::DcBus1) + if dc_bat_bus_supplied_through_dc_bus_1 { dc_bat_bus_load } else { no_load };
+        let dc_bus_2_load = self.bus_load(A320Bus::DcBus2) + if dc_bat_bus_supplied_through_dc_bus_1 { no_load } else { dc_bat_bus_load };
+
+        let (tr_1_load, tr_2_load) = match (self.tr_1_contactor.output().is_powered(), self.tr_2_contactor.output().is_powered()) {
+            (true, true) => (dc_bus_1_load, dc_bus_2_load),
+            (true, false) => (dc_bus_1_load + dc_bus_2_load, no_load),
+            (false, true) => (no_load, dc_bus_1_load + dc_bus_2_load),
+            (false, false) => (no_load, no_load)
+        };
+
+        self.tr_1.update_load(context, tr_1_load);
+        self.tr_2.update_load(context, tr_2_load);
+        self.tr_ess.update_load(context, if dc_ess_bus_supplied_by_ess_tr { dc_ess_bus_load } else { no_load });
+
+        let dc_bat_bus_source = self.dc_bat_bus.output().source();
+        self.battery_1.update(context, if dc_bat_bus_source == PowerSource::Battery(1) { dc_bat_bus_load } else { no_load });
+        self.battery_2.update(context, if dc_bat_bus_source == PowerSource::Battery(2) { dc_bat_bus_load } else { no_load });
+    }
+
+    fn bus_load(&self, bus: A320Bus) -> ElectricCurrent {
+        let unmodelled_load = ElectricCurrent::new::<ampere>(
+            if self.bus(bus).output().is_powered() { A320ElectricalCircuit::UNMODELLED_DC_BUS_LOAD_IN_AMPERES } else { 0. });
+
+        self.protected_consumers.iter().filter(|protected| protected.bus == bus)
+            .fold(unmodelled_load, |load, protected| load + protected.consumer.load())
+    }
+
+    pub fn tr_1_output_current(&self) -> ElectricCurrent {
+        self.tr_1.output_current()
+    }
+
+    pub fn tr_2_output_current(&self) -> ElectricCurrent {
+        self.tr_2.output_current()
+    }
+
+    pub fn tr_ess_output_current(&self) -> ElectricCurrent {
+        self.tr_ess.output_current()
+    }
+
+    pub fn consumer_is_powered(&self, id: &str) -> bool {
+        self.protected_consumers.iter().any(|protected| protected.consumer.id() == id && protected.consumer.is_powered())
+    }
+
+    /// Whether the master warning and master caution lights are supplied with power.
+    pub fn attention_getters_are_powered(&self) -> bool {
+        self.consumer_is_powered(A320ElectricalCircuit::ATTENTION_GETTERS)
+    }
+
+    /// Returns a snapshot of the circuit holding what the ECAM ELEC page displays.
+    pub fn electrical_page(&self) -> A320ElectricalPage {
+        A320ElectricalPage {
+            gen_1: self.generator_indication(&self.engine_1_gen, PowerSource::EngineGenerator(1)),
+            gen_2: self.generator_indication(&self.engine_2_gen, PowerSource::EngineGenerator(2)),
+            apu_gen: self.generator_indication(&self.apu_gen, PowerSource::ApuGenerator),
+            idg_1_temperature: A320ElectricalPage::idg_temperature_indication(&self.engine_1_gen),
+            idg_2_temperature: A320ElectricalPage::idg_temperature_indication(&self.engine_2_gen),
+            battery_1: A320ElectricalPage::battery_indication(&self.battery_1),
+            battery_2: A320ElectricalPage::battery_indication(&self.battery_2),
+            tr_1: A320ElectricalPage::transformer_rectifier_indication(&self.tr_1),
+            tr_2: A320ElectricalPage::transformer_rectifier_indication(&self.tr_2),
+            tr_ess: A320ElectricalPage::transformer_rectifier_indication(&self.tr_ess),
+            buses: self.buses().iter()
+                .map(|bus| BusIndication {
+                    id: bus.id().to_string(),
+                    powered: Indication::green_when(bus.output().is_powered(), bus.output().is_powered())
+                }).collect(),
+            contactors: self.contactors().iter().map(|contactor| ContactorIndication {
+                id: contactor.id().to_string(),
+                is_closed: contactor.is_closed()
+            }).collect()
+        }
+    }
+
+    /// Returns the alerts whose conditions are currently met, for the flight warning computer to filter and order.
+    pub fn triggered_alerts(&self, elec_overhead: &A320ElectricalOverheadPanel) -> Vec<Alert> {
+        let emer_config = self.ac_bus_1.output().is_unpowered() && self.ac_bus_2.output().is_unpowered();
+        let page = self.electrical_page();
+
+        let conditions = [
+            (ELEC_EMER_CONFIG, emer_config),
+            (ELEC_GEN_1_FAULT, A320ElectricalCircuit::generator_has_fault(&self.engine_1_gen, &self.engine_1_gcu, &elec_overhead.gen_1)),
+            (ELEC_GEN_2_FAULT, A320ElectricalCircuit::generator_has_fault(&self.engine_2_gen, &self.engine_2_gcu, &elec_overhead.gen_2)),
+            (ELEC_APU_GEN_FAULT, A320ElectricalCircuit::generator_has_fault(&self.apu_gen, &self.apu_gcu, &elec_overhead.apu_gen)),
+            (ELEC_AC_BUS_1_FAULT, !emer_config && self.ac_bus_1.output().is_unpowered()),
+            (ELEC_AC_BUS_2_FAULT, !emer_config && self.ac_bus_2.output().is_unpowered()),
+            (ELEC_AC_ESS_BUS_FAULT, !emer_config && self.ac_ess_bus.output().is_unpowered()),
+            (ELEC_DC_BUS_1_FAULT, !emer_config && self.dc_bus_1.output().is_unpowered()),
+            (ELEC_DC_BUS_2_FAULT, !emer_config && self.dc_bus_2.output().is_unpowered()),
+            (ELEC_DC_BAT_BUS_FAULT, !emer_config && self.dc_bat_bus.output().is_unpowered()),
+            (ELEC_DC_ESS_BUS_FAULT, !emer_config && self.dc_ess_bus.output().is_unpowered()),
+            (ELEC_TR_1_FAULT, self.ac_bus_1.output().is_powered() && self.tr_1_monitor.has_fault()),
+            (ELEC_TR_2_FAULT, self.ac_bus_2.output().is_powered() && self.tr_2_monitor.has_fault()),
+            (ELEC_BAT_1_FAULT, page.battery_1.voltage.colour() == IndicationColour::Amber),
+            (ELEC_BAT_2_FAULT, page.battery_2.voltage.colour() == IndicationColour::Amber),
+            (ELEC_IDG_1_OIL_LO_PR, self.engine_1_gen.idg_oil_pressure_is_low()),
+            (ELEC_IDG_2_OIL_LO_PR, self.engine_2_gen.idg_oil_pressure_is_low()),
+            (ELEC_IDG_1_OIL_OVHT, page.idg_1_temperature.colour() == IndicationColour::Amber),
+            (ELEC_IDG_2_OIL_OVHT, page.idg_2_temperature.colour() == IndicationColour::Amber)
+        ];
+
+        conditions.iter().filter(|(_, triggered)| *triggered).map(|(alert, _)| *alert).collect()
+    }
+
+    /// Illuminates the FAULT lights of the GEN and APU GEN push buttons while their generator is at fault,
+    /// those of the IDG push buttons on low oil pressure or oil outlet overheat, and that of the
+    /// AC ESS FEED push button while the AC ESS BUS is unpowered.
+    pub fn update_fault_lights(&self, elec_overhead: &mut A320ElectricalOverheadPanel) {
+        let gen_1_fault = A320ElectricalCircuit::generator_has_fault(&self.engine_1_gen, &self.engine_1_gcu, &elec_overhead.gen_1);
+        let gen_2_fault = A320ElectricalCircuit::generator_has_fault(&self.engine_2_gen, &self.engine_2_gcu, &elec_overhead.gen_2);
+        let apu_gen_fault = A320ElectricalCircuit::generator_has_fault(&self.apu_gen, &self.apu_gcu, &elec_overhead.apu_gen);
+        elec_overhead.gen_1.set_fault(gen_1_fault);
+        elec_overhead.gen_2.set_fault(gen_2_fault);
+        elec_overhead.apu_gen.set_fault(apu_gen_fault);
+        elec_overhead.idg_1.set_fault(A320ElectricalCircuit::idg_has_fault(&self.engine_1_gen));
+        elec_overhead.idg_2.set_fault(A320ElectricalCircuit::idg_has_fault(&self.engine_2_gen));
+        elec_overhead.ac_ess_feed.set_fault(self.ac_ess_bus.output().is_unpowered());
+    }
+
+    fn idg_has_fault(generator: &EngineGenerator) -> bool {
+        generator.idg_oil_pressure_is_low() || A320ElectricalPage::idg_temperature_indication(generator).colour() == IndicationColour::Amber
+    }
+
+    /// A generator is at fault when its GCU latched a fault, or when it provides output which
+    /// the GCU does not accept while the GEN push button is on.
+    fn generator_has_fault(generator: &dyn PowerConductor, gcu: &GeneratorControlUnit, gen_push_button: &OnOffPushButton) -> bool {
+        gcu.has_fault() || (gen_push_button.is_on() && generator.output().is_powered() && !gcu.closes_line_contactor())
+    }
+
+    fn generator_indication(&self, generator: &dyn PowerConductor, source: PowerSource) -> GeneratorIndication {
+        match generator.output() {
+            Current::Alternating(_, frequency, voltage, _) => {
+                let load = self.source_load(source);
+                GeneratorIndication {
+                    load: Indication::green_when(load, load <= Ratio::new::<percent>(A320ElectricalPage::GENERATOR_MAX_LOAD_IN_PERCENT)),
+                    voltage: Indication::green_when(voltage, voltage >= ElectricPotential::new::<volt>(GeneratorControlUnit::MIN_VOLTAGE) &&
+                        voltage <= ElectricPotential::new::<volt>(GeneratorControlUnit::MAX_VOLTAGE)),
+                    frequency: Indication::green_when(frequency, frequency >= Frequency::new::<hertz>(GeneratorControlUnit::MIN_FREQUENCY) &&
+                        frequency <= Frequency::new::<hertz>(GeneratorControlUnit::MAX_FREQUENCY))
+                }
+            },
+            _ => GeneratorIndication {
+                load: Indication::crossed_out(Ratio::new::<percent>(0.)),
+                voltage: Indication::crossed_out(ElectricPotential::new::<volt>(0.)),
+                frequency: Indication::crossed_out(Frequency::new::<hertz>(0.))
+            }
+        }
+    }
+
+    /// The apparent power supplied by the given source to the AC consumers and TRs, relative
+    /// to a generator's rated apparent power.
+    fn source_load(&self, source: PowerSource) -> Ratio {
+        let consumers_power: f32 = self.protected_consumers.iter()
+            .filter(|protected| matches!(protected.bus, A320Bus::AcBus1 | A320Bus::AcBus2 | A320Bus::AcEssBus))
+            .filter_map(|protected| match self.bus(protected.bus).output() {
+                Current::Alternating(bus_source, _, voltage, _) if bus_source == source => Some(voltage.get::<volt>() * protected.consumer.load().get::<ampere>()),
+                _ => None
+            }).sum();
+
+        let transformer_rectifiers_power: f32 = [&self.tr_1, &self.tr_2, &self.tr_ess].iter()
+            .filter(|tr| tr.output().source() == source)
+            .map(|tr| tr.output_voltage().get::<volt>() * tr.output_current().get::<ampere>())
+            .sum();
+
+        Ratio::new::<ratio>((consumers_power + transformer_rectifiers_power) / A320ElectricalPage::GENERATOR_RATED_APPARENT_POWER_IN_VOLT_AMPERES)
+    }
+
+    /// The source of each bus and the path its power flows through, the generators' loads, the batteries' charge and potential,
+    /// the TRs' currents and the state of each contactor.
+    pub fn sample(&self) -> Sample {
+        let page = self.electrical_page();
+        let mut sample: Sample = self.buses().iter()
+            .map(|bus| (format!("{} SOURCE", bus.id()), SampleValue::Text(bus.output().source().to_string())))
+            .collect();
+        for bus in self.buses().iter() {
+            sample.push((format!("{} PATH", bus.id()), SampleValue::Text(self.power_path(bus.id()).join(" > "))));
+        }
+
+        for (name, generator) in [("GEN 1", &page.gen_1), ("GEN 2", &page.gen_2), ("APU GEN", &page.apu_gen)] {
+            sample.push((format!("{} LOAD", name), SampleValue::Number(generator.load.value().get::<percent>() as f64)));
+        }
+
+        for (name, battery) in [("BAT 1", &self.battery_1), ("BAT 2", &self.battery_2)] {
+            sample.push((format!("{} CHARGE", name), SampleValue::Number(battery.charge().get::<ampere_hour>() as f64)));
+            sample.push((format!("{} POTENTIAL", name), SampleValue::Number(battery.potential().get::<volt>() as f64)));
+        }
+
+        for (name, tr) in [("TR 1", &page.tr_1), ("TR 2", &page.tr_2), ("ESS TR", &page.tr_ess)] {
+            sample.push((format!("{} CURRENT", name), SampleValue::Number(tr.current.value().get::<ampere>() as f64)));
+        }
+
+        for contactor in page.contactors {
+            sample.push((format!("{} CLOSED", contactor.id), SampleValue::Bool(contactor.is_closed)));
+        }
+
+        sample
+    }
+
+    /// Returns the conductors through which power flows into the conductor with the given identifier,
+    /// starting at the source and ending at the conductor itself, e.g. GEN 2, 9XU2, 11XU2, 11XU1, AC BUS 1.
+    /// The path is empty when no power flows into the conductor.
+    pub fn power_path(&self, id: &str) -> Vec<&str> {
+        let wiring = self.wiring();
+        let mut path = vec![];
+        let mut conductor = match wiring.iter().find(|conductor| conductor.id == id) {
+            Some(conductor) if conductor.input.is_powered() => conductor,
+            _ => return path
+        };
+
+        loop {
+            path.push(conductor.id);
+            match conductor.feeders.feeder() {
+                Some(feeder) if !path.contains(&feeder) => match wiring.iter().find(|upstream| upstream.id == feeder) {
+                    Some(upstream) => conductor = upstream,
+                    None => {
+                        path.push(feeder);
+                        break;
+                    }
+                },
+                _ => break
+            }
+        }
+
+        path.reverse();
+        path
+    }
+
+    /// Returns the AC buses on which distinct sources are paralleled. Each bus is connected to the
+    /// sources and buses wired to it through closed contactors, such as the APU and external power
+    /// line contactors which reach an AC bus through its closed bus tie contactor.
+    pub fn paralleled_sources(&self) -> Vec<Paralleling> {
+        let wiring = self.wiring();
+        [&self.ac_bus_1, &self.ac_bus_2, &self.ac_ess_bus].iter().filter_map(|bus| {
+            let mut visited = vec![bus.id()];
+            let mut outputs = vec![];
+            self.collect_connected_outputs(&wiring, bus.id(), &mut visited, &mut outputs);
+            Paralleling::detect(bus.id(), &outputs)
+        }).collect()
+    }
+
+    fn collect_connected_outputs<'a>(&'a self, wiring: &[Wiring<'a>], id: &str, visited: &mut Vec<&'a str>, outputs: &mut Vec<Current>) {
+        let conductor = match wiring.iter().find(|conductor| conductor.id == id) {
+            Some(conductor) => conductor,
+            None => return
+        };
+
+        for wired in conductor.feeders.wired() {
+            if visited.contains(&wired) {
+                continue;
+            }
+            visited.push(wired);
+
+            match wiring.iter().find(|upstream| upstream.id == wired) {
+                Some(upstream) => match upstream.closed {
+                    Some(true) => self.collect_connected_outputs(wiring, wired, visited, outputs),
+                    Some(false) => {},
+                    None => outputs.push(upstream.output)
+                },
+                None => outputs.extend(self.sources().iter().filter(|(id, _)| *id == wired).map(|(_, output)| *output))
+            }
+        }
+    }
+
+    /// Exports the circuit as a Graphviz DOT graph of its sources, contactors, buses and TRs.
+    /// Contactors are filled green when closed. Each conductor has an edge from every conductor
+    /// it is wired to. The edge it is fed through is coloured by the source of the power flowing
+    /// through it, the other edges are dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph \"A320 ELECTRICAL\" {\n    node [fontname=\"Helvetica\"];\n");
+
+        for (id, output) in self.sources().iter() {
+            dot.push_str(&format!("    \"{}\" [shape=ellipse, color={}, penwidth=2];\n", id, A320ElectricalCircuit::dot_colour(*output)));
+        }
+        for battery in [&self.battery_1, &self.battery_2] {
+            dot.push_str(&format!("    \"{}\" [shape=ellipse, label=\"{}\\n{:.1} Ah\", color={}, penwidth=2];\n", battery.id(), battery.id(),
+                battery.charge().get::<ampere_hour>(), A320ElectricalCircuit::dot_colour(battery.output())));
+        }
+        for contactor in self.contactors() {
+            dot.push_str(&format!("    \"{}\" [shape=box, style=filled, fillcolor={}];\n", contactor.id(),
+                if contactor.is_closed() { "palegreen" } else { "white" }));
+        }
+        for bus in self.buses().iter() {
+            dot.push_str(&format!("    \"{}\" [shape=box, style=bold, width=2, color={}, tooltip=\"{}\"];\n", bus.id(),
+                A320ElectricalCircuit::dot_colour(bus.output()), self.power_path(bus.id()).join(" > ")));
+        }
+        for tr in [&self.tr_1, &self.tr_2, &self.tr_ess] {
+            dot.push_str(&format!("    \"{}\" [shape=box, style=rounded, color={}];\n", tr.id(), A320ElectricalCircuit::dot_colour(tr.output())));
+        }
+
+        for conductor in self.wiring() {
+            for wired in conductor.feeders.wired() {
+                dot.push_str(&if conductor.input.is_powered() && conductor.feeders.feeder() == Some(wired) {
+                    format!("    \"{}\" -> \"{}\" [color={}, penwidth=2, tooltip=\"{}\"];\n", wired, conductor.id,
+                        A320ElectricalCircuit::source_colour(conductor.input.source()), conductor.input.source())
+                } else {
+                    format!("    \"{}\" -> \"{}\" [color=grey, style=dashed];\n", wired, conductor.id)
+                });
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The sources of the circuit and their outputs. External power isn't part of the circuit,
+    /// its output is the input of its line contactor.
+    fn sources(&self) -> [(&str, Current); 5] {
+        [
+            (self.engine_1_gen.id(), self.engine_1_gen.output()),
+            (self.engine_2_gen.id(), self.engine_2_gen.output()),
+            (self.apu_gen.id(), self.apu_gen.output()),
+            ("EXT PWR", self.ext_pwr_contactor.get_input()),
+            (self.emergency_gen.id(), self.emergency_gen.output())
+        ]
+    }
+
+    /// The wiring of the circuit's contactors, buses, TRs and batteries.
+    fn wiring(&self) -> Vec<Wiring<'_>> {
+        let mut wiring: Vec<Wiring> = self.contactors().iter().map(|contactor| Wiring {
+            id: contactor.id(),
+            feeders: contactor.feeders(),
+            input: contactor.get_input(),
+            output: contactor.output(),
+            closed: Some(contactor.is_closed())
+        }).collect();
+        wiring.extend(self.buses().iter().map(|bus| Wiring {
+            id: bus.id(),
+            feeders: bus.feeders(),
+            input: bus.get_input(),
+            output: bus.output(),
+            closed: None
+        }));
+        wiring.extend([&self.tr_1, &self.tr_2, &self.tr_ess].iter().map(|tr| Wiring {
+            id: tr.id(),
+            feeders: tr.feeders(),
+            input: tr.get_input(),
+            output: tr.output(),
+            closed: None
+        }));
+        wiring.extend([&self.battery_1, &self.battery_2].iter().map(|battery| Wiring {
+            id: battery.id(),
+            feeders: battery.feeders(),
+            input: battery.get_input(),
+            output: battery.output(),
+            closed: None
+        }));
+
+        wiring
+    }
+
+    fn dot_colour(output: Current) -> &'static str {
+        if output.is_powered() {
+            A320ElectricalCircuit::source_colour(output.source())
+        } else {
+            "grey"
+        }
+    }
+
+    fn source_colour(source: PowerSource) -> &'static str {
+        match source {
+            PowerSource::None => "grey",
+            PowerSource::EngineGenerator(1) => "blue",
+            PowerSource::EngineGenerator(_) => "darkorange",
+            PowerSource::ApuGenerator => "purple",
+            PowerSource::External => "goldenrod",
+            PowerSource::EmergencyGenerator => "red",
+            PowerSource::Battery(_) => "brown"
+        }
+    }
+
+    fn contactors(&self) -> [&Contactor; 18] {
+        [
+            &self.engine_1_gen_contactor,
+            &self.engine_2_gen_contactor,
+            &self.bus_tie_1_contactor,
+            &self.bus_tie_2_contactor,
+            &self.apu_gen_contactor,
+            &self.ext_pwr_contactor,
+            &self.ac_ess_feed_contactor_1,
+            &self.ac_ess_feed_contactor_2,
+            &self.tr_1_contactor,
+            &self.tr_2_contactor,
+            &self.tr_ess_contactor,
+            &self.ac_ess_to_tr_ess_contactor,
+            &self.emergency_gen_contactor,
+            &self.dc_bus_1_tie_contactor,
+            &self.dc_bus_2_tie_contactor,
+            &self.dc_bat_bus_to_dc_ess_bus_contactor,
+            &self.battery_1_contactor,
+            &self.battery_2_contactor
+        ]
+    }
+
+    fn buses(&self) -> [&ElectricalBus; 7] {
+        [&self.ac_bus_1, &self.ac_bus_2, &self.ac_ess_bus, &self.dc_bus_1, &self.dc_bus_2, &self.dc_bat_bus, &self.dc_ess_bus]
+    }
+
+    /// The ids of all failures the circuit responds to, such as "TR 1" or "9XU1 WELDED".
+    pub fn failure_ids(&self) -> Vec<String> {
+        let mut ids = Vec::new();
+        ids.extend(self.engine_1_gen.failure_ids());
+        ids.extend(self.engine_2_gen.failure_ids());
+        ids.extend(self.apu_gen.failure_ids());
+        for tr in [&self.tr_1, &self.tr_2, &self.tr_ess] {
+            ids.extend(tr.failure_ids());
+        }
+        for bus in self.buses() {
+            ids.extend(bus.failure_ids());
+        }
+        for contactor in self.contactors() {
+            ids.extend(contactor.failure_ids());
+        }
+        for protected in self.protected_consumers.iter() {
+            ids.extend(protected.consumer.failure_ids());
+        }
+
+        ids
+    }
+
+    /// Returns the output of the bus with the given identifier, such as "AC BUS 1".
+    pub fn bus_output(&self, id: &str) -> Option<Current> {
+        self.buses().iter().find(|bus| bus.id() == id).map(|bus| bus.output())
+    }
+
+    fn bus(&self, bus: A320Bus) -> &ElectricalBus {
+        match bus {
+            A320Bus::AcBus1 => &self.ac_bus_1,
+            A320Bus::AcBus2 => &self.ac_bus_2,
+            A320Bus::AcEssBus => &self.ac_ess_bus,
+            A320Bus::DcBus1 => &self.dc_bus_1,
+            A320Bus::DcBus2 => &self.dc_bus_2,
+            A320Bus::DcBatBus => &self.dc_bat_bus,
+            A320Bus::DcEssBus => &self.dc_ess_bus
+        }
+    }
+
+    /// Returns the circuit breaker at the given position of the given circuit breaker panel.
+    pub fn circuit_breaker(&self, panel: &str, position: &str) -> Option<&CircuitBreaker> {
+        self.protected_consumers.iter().map(|protected| &protected.circuit_breaker)
+            .find(|breaker| breaker.panel() == panel && breaker.position() == position)
+    }
+
+    pub fn pull_circuit_breaker(&mut self, panel: &str, position: &str) {
+        if let Some(breaker) = self.circuit_breaker_mut(panel, position) {
+            breaker.pull();
+        }
+    }
+
+    pub fn reset_circuit_breaker(&mut self, panel: &str, position: &str) {
+        if let Some(breaker) = self.circuit_breaker_mut(panel, position) {
+            breaker.reset();
+        }
+    }
+
+    fn circuit_breaker_mut(&mut self, panel: &str, position: &str) -> Option<&mut CircuitBreaker> {
+        self.protected_consumers.iter_mut().map(|protected| &mut protected.circuit_breaker)
+            .find(|breaker| breaker.panel() == panel && breaker.position() == position)
+    }
+
+    /// Power transfers are break-before-make: contactors which are to be closed only start
+    /// closing once all contactors which are to be opened have opened.
+    fn toggle_break_before_make(context: &UpdateContext, contactors: Vec<(&mut Contactor, bool)>) {
+        let any_opening = contactors.iter().any(|(contactor, should_be_closed)| !should_be_closed && contactor.reports_closed());
+        for (contactor, should_be_closed) in contactors {
+            let may_close = contactor.reports_closed() || !any_opening;
+            contactor.toggle(context, should_be_closed && may_close);
+        }
+    }
+
+    fn update_failures(&mut self, failures: &Failures) {
+        for bus in [&mut self.ac_bus_1, &mut self.ac_bus_2, &mut self.ac_ess_bus, &mut self.dc_bus_1, &mut self.dc_bus_2, &mut self.dc_bat_bus, &mut self.dc_ess_bus] {
+            bus.update(failures);
+        }
+
+        for tr in [&mut self.tr_1, &mut self.tr_2, &mut self.tr_ess] {
+            tr.update(failures);
+        }
+
+        for contactor in [
+            &mut self.engine_1_gen_contactor,
+            &mut self.engine_2_gen_contactor,
+            &mut self.bus_tie_1_contactor,
+            &mut self.bus_tie_2_contactor,
+            &mut self.apu_gen_contactor,
+            &mut self.ext_pwr_contactor,
+            &mut self.ac_ess_feed_contactor_1,
+            &mut self.ac_ess_feed_contactor_2,
+            &mut self.tr_1_contactor,
+            &mut self.tr_2_contactor,
+            &mut self.tr_ess_contactor,
+            &mut self.ac_ess_to_tr_ess_contactor,
+            &mut self.emergency_gen_contactor,
+            &mut self.dc_bat_bus_to_dc_ess_bus_contactor,
+            &mut self.dc_bus_1_tie_contactor,
+            &mut self.dc_bus_2_tie_contactor,
+            &mut self.battery_1_contactor,
+            &mut self.battery_2_contactor
+        ] {
+            contactor.update(failures);
+        }
+    }
+}
+
+/// Each component writes its own variables. The circuit writes the load of each generator,
+/// which depends on the consumers across the circuit, as "ELEC_{GEN}_LOAD".
+impl SimulatorElement for A320ElectricalCircuit {
+    fn accept<T: SimulatorElementVisitor>(&mut self, visitor: &mut T) {
+        self.engine_1_gen.accept(visitor);
+        self.engine_2_gen.accept(visitor);
+        self.apu_gen.accept(visitor);
+        self.tr_1.accept(visitor);
+        self.tr_2.accept(visitor);
+        self.tr_ess.accept(visitor);
+        self.battery_1.accept(visitor);
+        self.battery_2.accept(visitor);
+        self.ac_bus_1.accept(visitor);
+        self.ac_bus_2.accept(visitor);
+        self.ac_ess_bus.accept(visitor);
+        self.dc_bus_1.accept(visitor);
+        self.dc_bus_2.accept(visitor);
+        self.dc_bat_bus.accept(visitor);
+        self.dc_ess_bus.accept(visitor);
+        self.engine_1_gen_contactor.accept(visitor);
+        self.engine_2_gen_contactor.accept(visitor);
+        self.bus_tie_1_contactor.accept(visitor);
+        self.bus_tie_2_contactor.accept(visitor);
+        self.apu_gen_contactor.accept(visitor);
+        self.ext_pwr_contactor.accept(visitor);
+        self.ac_ess_feed_contactor_1.accept(visitor);
+        self.ac_ess_feed_contactor_2.accept(visitor);
+        self.tr_1_contactor.accept(visitor);
+        self.tr_2_contactor.accept(visitor);
+        self.tr_ess_contactor.accept(visitor);
+        self.ac_ess_to_tr_ess_contactor.accept(visitor);
+        self.emergency_gen_contactor.accept(visitor);
+        self.dc_bus_1_tie_contactor.accept(visitor);
+        self.dc_bus_2_tie_contactor.accept(visitor);
+        self.dc_bat_bus_to_dc_ess_bus_contactor.accept(visitor);
+        self.battery_1_contactor.accept(visitor);
+        self.battery_2_contactor.accept(visitor);
+        visitor.visit(self);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        for (name, source) in [("GEN_1", PowerSource::EngineGenerator(1)), ("GEN_2", PowerSource::EngineGenerator(2)), ("APU_GEN", PowerSource::ApuGenerator)] {
+            writer.write_f64(&format!("ELEC_{}_LOAD", name), self.source_load(source).get::<percent>() as f64);
+        }
+    }
+}
+
+impl Stateful for A320ElectricalCircuit {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_element(state, "GEN 1", &mut self.engine_1_gen);
+        visit_element(state, "GEN 1 GCU", &mut self.engine_1_gcu);
+        visit_element(state, "9XU1", &mut self.engine_1_gen_contactor);
+        visit_element(state, "GEN 2", &mut self.engine_2_gen);
+        visit_element(state, "GEN 2 GCU", &mut self.engine_2_gcu);
+        visit_element(state, "9XU2", &mut self.engine_2_gen_contactor);
+        visit_element(state, "11XU1", &mut self.bus_tie_1_contactor);
+        visit_element(state, "11XU2", &mut self.bus_tie_2_contactor);
+        visit_element(state, "APU GEN", &mut self.apu_gen);
+        visit_element(state, "APU GEN GCU", &mut self.apu_gcu);
+        visit_element(state, "3XS", &mut self.apu_gen_contactor);
+        visit_element(state, "3XG", &mut self.ext_pwr_contactor);
+        visit_element(state, "AC BUS 1", &mut self.ac_bus_1);
+        visit_element(state, "AC BUS 2", &mut self.ac_bus_2);
+        visit_element(state, "AC ESS BUS", &mut self.ac_ess_bus);
+        visit_element(state, "3XC1", &mut self.ac_ess_feed_contactor_1);
+        visit_element(state, "3XC2", &mut self.ac_ess_feed_contactor_2);
+        visit_element(state, "AC ESS FEED DELAY", &mut self.ac_ess_feed_contactor_delay_logic_gate);
+        visit_element(state, "TR 1", &mut self.tr_1);
+        visit_element(state, "TR 1 MONITOR", &mut self.tr_1_monitor);
+        visit_element(state, "5PU1", &mut self.tr_1_contactor);
+        visit_element(state, "TR 2", &mut self.tr_2);
+        visit_element(state, "TR 2 MONITOR", &mut self.tr_2_monitor);
+        visit_element(state, "5PU2", &mut self.tr_2_contactor);
+        visit_element(state, "ESS TR", &mut self.tr_ess);
+        visit_element(state, "3PE", &mut self.tr_ess_contactor);
+        visit_element(state, "15XE1", &mut self.ac_ess_to_tr_ess_contactor);
+        visit_element(state, "EMER GEN", &mut self.emergency_gen);
+        visit_element(state, "2XE", &mut self.emergency_gen_contactor);
+        visit_element(state, "DC BUS 1", &mut self.dc_bus_1);
+        visit_element(state, "DC BUS 2", &mut self.dc_bus_2);
+        visit_element(state, "1PC1", &mut self.dc_bus_1_tie_contactor);
+        visit_element(state, "1PC2", &mut self.dc_bus_2_tie_contactor);
+        visit_element(state, "DC BAT BUS", &mut self.dc_bat_bus);
+        visit_element(state, "DC ESS BUS", &mut self.dc_ess_bus);
+        visit_element(state, "4PC", &mut self.dc_bat_bus_to_dc_ess_bus_contactor);
+        visit_element(state, "BAT 1", &mut self.battery_1);
+        visit_element(state, "6PB1", &mut self.battery_1_contactor);
+        visit_element(state, "BAT 2", &mut self.battery_2);
+        visit_element(state, "6PB2", &mut self.battery_2_contactor);
+
+        for protected in self.protected_consumers.iter_mut() {
+            let id = protected.consumer.id().to_string();
+            visit_element(state, &id, protected);
+        }
+    }
+}
+
+/// Identifies the bus which supplies a protected consumer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum A320Bus {
+    AcBus1,
+    AcBus2,
+    AcEssBus,
+    DcBus1,
+    DcBus2,
+    DcBatBus,
+    DcEssBus
+}
+
+/// A consumer supplied by one of the buses through a circuit breaker.
+/// Galleys are shed while the GALY & CAB push button is off.
+pub struct A320ProtectedConsumer {
+    bus: A320Bus,
+    circuit_breaker: CircuitBreaker,
+    consumer: PowerConsumer,
+    galley: bool
+}
+
+impl A320ProtectedConsumer {
+    pub fn new(bus: A320Bus, panel: &str, position: &str, rated_current: f32, id: &str, demand: f32) -> A320ProtectedConsumer {
+        A320ProtectedConsumer {
+            bus,
+            circuit_breaker: CircuitBreaker::new(String::from(panel), String::from(position), ElectricCurrent::new::<ampere>(rated_current)),
+            consumer: PowerConsumer::new(String::from(id), ElectricCurrent::new::<ampere>(demand)),
+            galley: false
+        }
+    }
+
+    pub fn is_galley(&self) -> bool {
+        self.galley
+    }
+
+    pub fn galley(bus: A320Bus, panel: &str, position: &str, rated_current: f32, id: &str, demand: f32) -> A320ProtectedConsumer {
+        A320ProtectedConsumer {
+            galley: true,
+            ..A320ProtectedConsumer::new(bus, panel, position, rated_current, id, demand)
+        }
     }
 
-    fn has_failed_or_is_unpowered(tr: &TransformerRectifier) -> bool {
-        tr.has_failed() || tr.output().is_unpowered()
+    /// The consumers of the A320. Only a selection of the aircraft's circuit breakers and consumers is modelled.
+    pub fn a320_consumers() -> Vec<A320ProtectedConsumer> {
+        vec!(
+            A320ProtectedConsumer::new(A320Bus::AcEssBus, "49VU", "C05", 3., "ECAM DU UPPER", 1.),
+            A320ProtectedConsumer::new(A320Bus::AcBus2, "49VU", "C06", 3., "ECAM DU LOWER", 1.),
+            A320ProtectedConsumer::new(A320Bus::AcBus1, "105VU", "H12", 15., "FUEL PUMP 1", 8.),
+            A320ProtectedConsumer::new(A320Bus::AcBus2, "105VU", "H13", 15., "FUEL PUMP 2", 8.),
+            A320ProtectedConsumer::new(A320Bus::DcBus1, "121VU", "M07", 10., "CABIN LIGHTING", 5.),
+            A320ProtectedConsumer::new(A320Bus::DcBus2, "121VU", "M08", 10., "LANDING LIGHTS", 6.),
+            A320ProtectedConsumer::new(A320Bus::DcBatBus, "49VU", "B02", 5., "ENG 1 FADEC", 2.),
+            A320ProtectedConsumer::new(A320Bus::DcEssBus, "49VU", "A04", 3., "RMP 1", 1.),
+            A320ProtectedConsumer::new(A320Bus::DcEssBus, "49VU", "A05", 3., A320ElectricalCircuit::ATTENTION_GETTERS, 0.5),
+            A320ProtectedConsumer::galley(A320Bus::AcBus2, "2000VU", "G01", 50., "FWD GALLEY", 30.),
+            A320ProtectedConsumer::galley(A320Bus::AcBus2, "2000VU", "G02", 50., "AFT GALLEY", 30.)
+        )
+    }
+
+    fn update(&mut self, context: &UpdateContext, bus: &ElectricalBus, galleys_shed: bool, failures: &Failures) {
+        self.consumer.update(failures);
+        self.circuit_breaker.powered_by(vec!(bus));
+        if self.galley && galleys_shed {
+            self.consumer.set_input(Current::None);
+        } else {
+            self.consumer.powered_by(vec!(&self.circuit_breaker));
+        }
+        self.circuit_breaker.update(context, self.consumer.load());
+    }
+}
+
+impl Stateful for A320ProtectedConsumer {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_element(state, "CIRCUIT BREAKER", &mut self.circuit_breaker);
+        visit_element(state, "CONSUMER", &mut self.consumer);
     }
 }
 
 pub struct A320ElectricalOverheadPanel {
-    bat_1: OnOffPushButton,
-    bat_2: OnOffPushButton,
-    idg_1: OnOffPushButton,
-    idg_2: OnOffPushButton,
-    gen_1: OnOffPushButton,
-    gen_2: OnOffPushButton,
-    apu_gen: OnOffPushButton,
-    bus_tie: OnOffPushButton,
-    ac_ess_feed: NormalAltnPushButton,
-    galy_and_cab: OnOffPushButton,
-    ext_pwr: OnOffPushButton,
-    commercial: OnOffPushButton    
+    pub bat_1: OnOffPushButton,
+    pub bat_2: OnOffPushButton,
+    pub idg_1: OnOffPushButton,
+    pub idg_2: OnOffPushButton,
+    pub gen_1: OnOffPushButton,
+    pub gen_2: OnOffPushButton,
+    pub apu_gen: OnOffPushButton,
+    pub bus_tie: OnOffPushButton,
+    pub ac_ess_feed: NormalAltnPushButton,
+    pub galy_and_cab: OnOffPushButton,
+    pub ext_pwr: OnOffPushButton,
+    pub commercial: OnOffPushButton    
 }
 
 impl A320ElectricalOverheadPanel {
@@ -204,61 +1239,643 @@ impl A320ElectricalOverheadPanel {
     }
 }
 
-pub struct A320HydraulicCircuit {
-    // Until hydraulic is implemented, we'll fake it with this boolean.
-    blue_pressurised: bool,
+/// The colour in which the ECAM displays an indication. A crossed out indication is displayed
+/// as amber "XX", as the parameter cannot be measured.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IndicationColour {
+    Green,
+    Amber,
+    CrossedOut
 }
 
-impl A320HydraulicCircuit {
-    pub fn new() -> A320HydraulicCircuit {
-        A320HydraulicCircuit {
-            blue_pressurised: true
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Indication<T> {
+    value: T,
+    colour: IndicationColour
+}
+
+impl<T: Copy> Indication<T> {
+    fn green_when(value: T, normal: bool) -> Indication<T> {
+        Indication {
+            value,
+            colour: if normal { IndicationColour::Green } else { IndicationColour::Amber }
         }
     }
 
-    fn is_blue_pressurised(&self) -> bool {
-        self.blue_pressurised
+    fn crossed_out(value: T) -> Indication<T> {
+        Indication {
+            value,
+            colour: IndicationColour::CrossedOut
+        }
+    }
+
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    pub fn colour(&self) -> IndicationColour {
+        self.colour
     }
 }
 
-#[cfg(test)]
-mod a320_electrical_circuit_tests {
-    use crate::electrical::{Current, PowerSource};
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeneratorIndication {
+    pub load: Indication<Ratio>,
+    pub voltage: Indication<ElectricPotential>,
+    pub frequency: Indication<Frequency>
+}
 
-    use super::*;
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BatteryIndication {
+    pub voltage: Indication<ElectricPotential>,
+    pub current: Indication<ElectricCurrent>
+}
 
-    /// # Source
-    /// A320 manual electrical distribution table
-    #[test]
-    fn distribution_table_norm_conf() {
-        let tester = tester_with().running_engines().run();
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TransformerRectifierIndication {
+    pub voltage: Indication<ElectricPotential>,
+    pub current: Indication<ElectricCurrent>
+}
 
-        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
-        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
-        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(1));
-        assert_eq!(tester.tr_1_output().source(), PowerSource::EngineGenerator(1));
-        assert_eq!(tester.tr_2_output().source(), PowerSource::EngineGenerator(2));
-        assert_eq!(tester.tr_ess_output().source(), PowerSource::None);
-        assert_eq!(tester.dc_bus_1_output().source(), PowerSource::EngineGenerator(1));
-        assert_eq!(tester.dc_bus_2_output().source(), PowerSource::EngineGenerator(2));
-        assert_eq!(tester.dc_bat_bus_output().source(), PowerSource::EngineGenerator(1));
+#[derive(Clone, Debug, PartialEq)]
+pub struct BusIndication {
+    pub id: String,
+    pub powered: Indication<bool>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContactorIndication {
+    pub id: String,
+    pub is_closed: bool
+}
+
+/// A read-only snapshot of the electrical circuit, holding what the ECAM ELEC page displays.
+#[derive(Clone, Debug, PartialEq)]
+pub struct A320ElectricalPage {
+    pub gen_1: GeneratorIndication,
+    pub gen_2: GeneratorIndication,
+    pub apu_gen: GeneratorIndication,
+    pub idg_1_temperature: Indication<ThermodynamicTemperature>,
+    pub idg_2_temperature: Indication<ThermodynamicTemperature>,
+    pub battery_1: BatteryIndication,
+    pub battery_2: BatteryIndication,
+    pub tr_1: TransformerRectifierIndication,
+    pub tr_2: TransformerRectifierIndication,
+    pub tr_ess: TransformerRectifierIndication,
+    pub buses: Vec<BusIndication>,
+    pub contactors: Vec<ContactorIndication>
+}
+
+impl A320ElectricalPage {
+    pub const GENERATOR_RATED_APPARENT_POWER_IN_VOLT_AMPERES: f32 = 90_000.;
+    pub const GENERATOR_MAX_LOAD_IN_PERCENT: f32 = 100.;
+    pub const IDG_MAX_OIL_OUTLET_TEMPERATURE_IN_CELSIUS: f32 = 185.;
+    pub const BATTERY_MIN_VOLTAGE: f32 = 25.;
+    pub const BATTERY_MAX_VOLTAGE: f32 = 31.;
+    pub const BATTERY_MAX_DISCHARGE_CURRENT_IN_AMPERES: f32 = 5.;
+    pub const TR_MIN_VOLTAGE: f32 = 25.;
+    pub const TR_MAX_VOLTAGE: f32 = 31.;
+    /// A TR supplying this current or less is displayed in amber.
+    pub const TR_MIN_CURRENT_IN_AMPERES: f32 = 5.;
+
+    pub fn bus(&self, id: &str) -> Option<&BusIndication> {
+        self.buses.iter().find(|bus| bus.id == id)
     }
 
-    /// # Source
-    /// A320 manual electrical distribution table
-    #[test]
-    fn distribution_table_only_gen_1_available() {
-        let tester = tester_with().running_engine_1().run();
+    pub fn contactor(&self, id: &str) -> Option<&ContactorIndication> {
+        self.contactors.iter().find(|contactor| contactor.id == id)
+    }
 
-        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
-        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(1));
-        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(1));
+    fn idg_temperature_indication(generator: &EngineGenerator) -> Indication<ThermodynamicTemperature> {
+        let temperature = generator.idg_oil_outlet_temperature();
+        Indication::green_when(temperature, temperature <= ThermodynamicTemperature::new::<degree_celsius>(A320ElectricalPage::IDG_MAX_OIL_OUTLET_TEMPERATURE_IN_CELSIUS))
+    }
+
+    fn battery_indication(battery: &Battery) -> BatteryIndication {
+        let voltage = battery.potential();
+        let current = battery.current();
+        BatteryIndication {
+            voltage: Indication::green_when(voltage, voltage >= ElectricPotential::new::<volt>(A320ElectricalPage::BATTERY_MIN_VOLTAGE) &&
+                voltage <= ElectricPotential::new::<volt>(A320ElectricalPage::BATTERY_MAX_VOLTAGE)),
+            current: Indication::green_when(current, current >= ElectricCurrent::new::<ampere>(-A320ElectricalPage::BATTERY_MAX_DISCHARGE_CURRENT_IN_AMPERES))
+        }
+    }
+
+    fn transformer_rectifier_indication(tr: &TransformerRectifier) -> TransformerRectifierIndication {
+        let voltage = tr.output_voltage();
+        let current = tr.output_current();
+        TransformerRectifierIndication {
+            voltage: Indication::green_when(voltage, voltage >= ElectricPotential::new::<volt>(A320ElectricalPage::TR_MIN_VOLTAGE) &&
+                voltage <= ElectricPotential::new::<volt>(A320ElectricalPage::TR_MAX_VOLTAGE)),
+            current: Indication::green_when(current, current > ElectricCurrent::new::<ampere>(A320ElectricalPage::TR_MIN_CURRENT_IN_AMPERES))
+        }
+    }
+}
+
+impl A320ElectricalOverheadPanel {
+    /// The push buttons whose FAULT light is driven by the electrical circuit.
+    const FAULT_LIGHTS: [&'static str; 5] = ["IDG_1", "IDG_2", "GEN_1", "GEN_2", "APU_GEN"];
+
+    /// Returns the simulator variable which presses the named push button into the given position, and
+    /// whether that position sets it, e.g. ("OVHD_ELEC_GEN_1_PB_IS_ON", false) for "GEN 1" and "off".
+    /// Names and positions are case insensitive, and names may use underscores instead of spaces.
+    /// AC ESS FEED is pressed normal or altn, all other push buttons on or off.
+    pub fn push_button_input(push_button: &str, position: &str) -> Result<(String, bool), String> {
+        let name = push_button.trim().to_uppercase().replace(" & ", "_AND_").replace(' ', "_");
+        let is_on_off = A320ElectricalOverheadPanel::new().on_off_push_buttons().iter().any(|(on_off_name, _)| *on_off_name == name);
+        if !is_on_off && name != "AC_ESS_FEED" {
+            return Err(format!("Unknown push button '{}'.", push_button.trim()));
+        }
+
+        match (position.to_lowercase().as_str(), is_on_off) {
+            ("on", true) => Ok((format!("OVHD_ELEC_{}_PB_IS_ON", name), true)),
+            ("off", true) => Ok((format!("OVHD_ELEC_{}_PB_IS_ON", name), false)),
+            ("normal", false) => Ok((format!("OVHD_ELEC_{}_PB_IS_NORMAL", name), true)),
+            ("altn", false) => Ok((format!("OVHD_ELEC_{}_PB_IS_NORMAL", name), false)),
+            _ => Err(format!("Unknown position '{}' of push button '{}'.", position, push_button.trim()))
+        }
+    }
+
+    fn on_off_push_buttons(&self) -> [(&str, &OnOffPushButton); 11] {
+        [
+            ("BAT_1", &self.bat_1),
+            ("BAT_2", &self.bat_2),
+            ("IDG_1", &self.idg_1),
+            ("IDG_2", &self.idg_2),
+            ("GEN_1", &self.gen_1),
+            ("GEN_2", &self.gen_2),
+            ("APU_GEN", &self.apu_gen),
+            ("BUS_TIE", &self.bus_tie),
+            ("GALY_AND_CAB", &self.galy_and_cab),
+            ("EXT_PWR", &self.ext_pwr),
+            ("COMMERCIAL", &self.commercial)
+        ]
+    }
+
+    fn on_off_push_buttons_mut(&mut self) -> [(&str, &mut OnOffPushButton); 11] {
+        [
+            ("BAT_1", &mut self.bat_1),
+            ("BAT_2", &mut self.bat_2),
+            ("IDG_1", &mut self.idg_1),
+            ("IDG_2", &mut self.idg_2),
+            ("GEN_1", &mut self.gen_1),
+            ("GEN_2", &mut self.gen_2),
+            ("APU_GEN", &mut self.apu_gen),
+            ("BUS_TIE", &mut self.bus_tie),
+            ("GALY_AND_CAB", &mut self.galy_and_cab),
+            ("EXT_PWR", &mut self.ext_pwr),
+            ("COMMERCIAL", &mut self.commercial)
+        ]
+    }
+}
+
+/// The push buttons are read as "OVHD_ELEC_{NAME}_PB_IS_ON". The fault lights driven by the
+/// electrical circuit are written as "OVHD_ELEC_{NAME}_PB_HAS_FAULT".
+impl SimulatorElement for A320ElectricalOverheadPanel {
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        for (name, push_button) in self.on_off_push_buttons_mut() {
+            match reader.read_bool(&format!("OVHD_ELEC_{}_PB_IS_ON", name)) {
+                Some(true) => push_button.push_on(),
+                Some(false) => push_button.push_off(),
+                None => {}
+            }
+        }
+
+        match reader.read_bool("OVHD_ELEC_AC_ESS_FEED_PB_IS_NORMAL") {
+            Some(true) => self.ac_ess_feed.push_normal(),
+            Some(false) => self.ac_ess_feed.push_altn(),
+            None => {}
+        }
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        for (name, push_button) in self.on_off_push_buttons().iter().filter(|(name, _)| A320ElectricalOverheadPanel::FAULT_LIGHTS.contains(name)) {
+            writer.write_bool(&format!("OVHD_ELEC_{}_PB_HAS_FAULT", name), push_button.has_fault());
+        }
+
+        writer.write_bool("OVHD_ELEC_AC_ESS_FEED_PB_HAS_FAULT", self.ac_ess_feed.has_fault());
+    }
+}
+
+impl Stateful for A320ElectricalOverheadPanel {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        for (name, push_button) in self.on_off_push_buttons_mut() {
+            visit_element(state, name, push_button);
+        }
+
+        visit_element(state, "AC_ESS_FEED", &mut self.ac_ess_feed);
+    }
+}
+
+pub struct A320HydraulicCircuit {
+    // Until hydraulic is implemented, we'll fake it with this boolean.
+    blue_pressurised: bool,
+}
+
+impl A320HydraulicCircuit {
+    pub fn new() -> A320HydraulicCircuit {
+        A320HydraulicCircuit {
+            blue_pressurised: true
+        }
+    }
+
+    pub fn update(&mut self, _context: &UpdateContext) {
+        // Until hydraulic is implemented, there is nothing to update.
+    }
+
+    fn is_blue_pressurised(&self) -> bool {
+        self.blue_pressurised
+    }
+}
+
+impl Stateful for A320HydraulicCircuit {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_bool("BLUE PRESSURISED", &mut self.blue_pressurised);
+    }
+}
+
+#[cfg(test)]
+mod a320_tests {
+    use std::time::Duration;
+    use uom::si::{f32::Ratio, ratio::percent};
+
+    use crate::{a321::A321, shared::Simulation, simulator::{InMemorySimulatorReaderWriter, SimulatorReaderWriter, read_from_simulator, write_to_simulator}, snapshot::{Snapshot, SnapshotError}};
+
+    use super::*;
+
+    #[test]
+    fn tick_powers_ac_buses_from_running_engines() {
+        let mut a320 = A320::new();
+        a320.engine_mut(1).unwrap().n2 = Ratio::new::<percent>(80.);
+        a320.engine_mut(2).unwrap().n2 = Ratio::new::<percent>(80.);
+        tick_for(&mut a320, Duration::from_millis(500));
+
+        assert!(a320.electrical().electrical_page().bus("AC BUS 1").unwrap().powered.value());
+        assert!(a320.electrical().electrical_page().bus("AC BUS 2").unwrap().powered.value());
+    }
+
+    #[test]
+    fn tick_updates_failures() {
+        let mut a320 = A320::new();
+        a320.engine_mut(1).unwrap().n2 = Ratio::new::<percent>(80.);
+        a320.engine_mut(2).unwrap().n2 = Ratio::new::<percent>(80.);
+        a320.failures_mut().activate_at("AC BUS 1", Duration::from_millis(200));
+        tick_for(&mut a320, Duration::from_millis(500));
+
+        assert!(!a320.electrical().electrical_page().bus("AC BUS 1").unwrap().powered.value());
+    }
+
+    #[test]
+    fn tick_updates_alerts_and_attention_getters() {
+        let mut a320 = A320::new();
+        a320.engine_mut(1).unwrap().n2 = Ratio::new::<percent>(80.);
+        a320.engine_mut(2).unwrap().n2 = Ratio::new::<percent>(80.);
+        a320.set_flight_phase(FlightPhase::AboveFifteenHundredFeet);
+        tick_for(&mut a320, Duration::from_millis(500));
+        a320.failures_mut().activate("IDG 1 OIL LEAK");
+        tick_for(&mut a320, Duration::from_millis(1));
+
+        assert!(a320.fwc().is_active("ELEC IDG 1 OIL LO PR"));
+        assert!(a320.attention_getters().master_caution_is_illuminated());
+    }
+
+    #[test]
+    fn has_no_engine_other_than_1_and_2() {
+        let mut a320 = A320::new();
+
+        assert!(a320.engine(0).is_none());
+        assert!(a320.engine_mut(3).is_none());
+    }
+
+    #[test]
+    fn reading_from_simulator_visits_all_systems() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        reader_writer.write("TURB ENG CORRECTED N2:1", 80.);
+        reader_writer.write("TURB ENG CORRECTED N2:2", 70.);
+        reader_writer.write("OVHD_ELEC_GEN_1_PB_IS_ON", 1.);
+        reader_writer.write("OVHD_ELEC_GEN_2_PB_IS_ON", 0.);
+
+        let mut a320 = A320::new();
+        read_from_simulator(&mut a320, &mut reader_writer);
+
+        assert_eq!(a320.engine(1).unwrap().n2, Ratio::new::<percent>(80.));
+        assert_eq!(a320.engine(2).unwrap().n2, Ratio::new::<percent>(70.));
+        assert!(a320.electrical_overhead().gen_1.is_on());
+        assert!(a320.electrical_overhead().gen_2.is_off());
+    }
+
+    #[test]
+    fn push_buttons_missing_from_simulator_keep_their_position() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        reader_writer.write("TURB ENG CORRECTED N2:1", 80.);
+        reader_writer.write("TURB ENG CORRECTED N2:2", 80.);
+
+        let mut simulation = Simulation::new(A320::new());
+        for _ in 0..500 {
+            simulation.tick(Duration::from_millis(1), &mut reader_writer);
+        }
+
+        assert!(simulation.aircraft().electrical_overhead().gen_1.is_on());
+        assert!(simulation.aircraft().electrical_overhead().ac_ess_feed.is_normal());
+        assert_eq!(reader_writer.read("ELEC_AC_BUS_1_IS_POWERED"), Some(1.));
+    }
+
+    #[test]
+    fn writing_to_simulator_visits_all_systems() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        let mut a320 = A320::new();
+        a320.engine_mut(1).unwrap().n2 = Ratio::new::<percent>(80.);
+        tick_for(&mut a320, Duration::from_millis(500));
+        write_to_simulator(&mut a320, &mut reader_writer);
+
+        assert_eq!(reader_writer.read("ELEC_AC_BUS_1_IS_POWERED"), Some(1.));
+        assert_eq!(reader_writer.read("OVHD_ELEC_GEN_1_PB_HAS_FAULT"), Some(0.));
+    }
+
+    #[test]
+    fn failed_generator_illuminates_its_fault_light() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        let mut a320 = A320::new();
+        a320.engine_mut(1).unwrap().n2 = Ratio::new::<percent>(80.);
+        a320.failures_mut().activate("GEN 1");
+        tick_for(&mut a320, Duration::from_millis(500));
+        write_to_simulator(&mut a320, &mut reader_writer);
+
+        assert!(a320.electrical_overhead().gen_1.has_fault());
+        assert_eq!(reader_writer.read("OVHD_ELEC_GEN_1_PB_HAS_FAULT"), Some(1.));
+        assert_eq!(reader_writer.read("OVHD_ELEC_GEN_2_PB_HAS_FAULT"), Some(0.));
+    }
+
+    #[test]
+    fn leaking_idg_illuminates_its_fault_light() {
+        let mut a320 = A320::new();
+        a320.engine_mut(1).unwrap().n2 = Ratio::new::<percent>(80.);
+        a320.failures_mut().activate("IDG 1 OIL LEAK");
+        tick_for(&mut a320, Duration::from_millis(500));
+
+        assert!(a320.electrical_overhead().idg_1.has_fault());
+        assert!(!a320.electrical_overhead().idg_2.has_fault());
+    }
+
+    #[test]
+    fn unpowered_ac_ess_bus_illuminates_ac_ess_feed_fault_light() {
+        let mut a320 = A320::new();
+        tick_for(&mut a320, Duration::from_millis(1));
+
+        assert!(a320.electrical_overhead().ac_ess_feed.has_fault());
+    }
+
+    #[test]
+    fn fault_lights_without_a_fault_condition_are_not_written() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        write_to_simulator(&mut A320::new(), &mut reader_writer);
+
+        assert_eq!(reader_writer.read("OVHD_ELEC_BUS_TIE_PB_HAS_FAULT"), None);
+        assert_eq!(reader_writer.read("OVHD_ELEC_BAT_1_PB_HAS_FAULT"), None);
+    }
+
+    #[test]
+    fn restored_snapshot_continues_identically() {
+        let mut a320 = A320::new();
+        a320.engine_mut(1).unwrap().n2 = Ratio::new::<percent>(80.);
+        a320.engine_mut(2).unwrap().n2 = Ratio::new::<percent>(80.);
+        a320.set_flight_phase(FlightPhase::AboveFifteenHundredFeet);
+        a320.failures_mut().activate_randomly("AC BUS 2", Duration::from_secs(2));
+        a320.failures_mut().activate_at("IDG 1 OIL LEAK", Duration::from_millis(700));
+        tick_for(&mut a320, Duration::from_millis(500));
+
+        let snapshot = Snapshot::from_text(&Snapshot::save(&mut a320).to_text()).unwrap();
+        let mut restored = A320::new();
+        assert_eq!(snapshot.restore(&mut restored), Ok(()));
+        assert!(!a320.electrical().power_path("AC BUS 2").is_empty());
+        assert_eq!(restored.electrical().power_path("AC BUS 2"), a320.electrical().power_path("AC BUS 2"));
+
+        tick_for(&mut a320, Duration::from_secs(3));
+        tick_for(&mut restored, Duration::from_secs(3));
+
+        assert_eq!(Snapshot::save(&mut restored), Snapshot::save(&mut a320));
+        assert!(restored.fwc().is_active("ELEC IDG 1 OIL LO PR"));
+    }
+
+    #[test]
+    fn restoring_snapshot_restores_alerts_and_attention_getters() {
+        let mut a320 = A320::new();
+        a320.engine_mut(1).unwrap().n2 = Ratio::new::<percent>(80.);
+        a320.engine_mut(2).unwrap().n2 = Ratio::new::<percent>(80.);
+        a320.set_flight_phase(FlightPhase::AboveFifteenHundredFeet);
+        tick_for(&mut a320, Duration::from_millis(500));
+        a320.failures_mut().activate("IDG 1 OIL LEAK");
+        tick_for(&mut a320, Duration::from_millis(1));
+
+        let mut restored = A320::new();
+        assert_eq!(Snapshot::save(&mut a320).restore(&mut restored), Ok(()));
+
+        assert!(restored.fwc().is_active("ELEC IDG 1 OIL LO PR"));
+        assert!(restored.attention_getters().master_caution_is_illuminated());
+        assert!(restored.failures().is_active("IDG 1 OIL LEAK"));
+    }
+
+    #[test]
+    fn a320_snapshot_cannot_be_restored_into_a321() {
+        let snapshot = Snapshot::save(&mut A320::new());
+
+        assert!(matches!(snapshot.restore(&mut A321::new()), Err(SnapshotError::Mismatch { .. })));
+    }
+
+    #[test]
+    fn snapshot_saved_before_generator_threshold_confirmation_is_unsupported() {
+        let text = Snapshot::save(&mut A320::new()).to_text()
+            .replacen(&format!("SNAPSHOT {}", Snapshot::VERSION), "SNAPSHOT 1", 1)
+            .lines().filter(|line| !line.contains("ABOVE THRESHOLD")).map(|line| format!("{}\n", line)).collect::<String>();
+
+        assert_eq!(Snapshot::from_text(&text), Err(SnapshotError::UnsupportedVersion(1)));
+    }
+
+    #[test]
+    fn snapshot_saved_before_feeders_is_unsupported() {
+        let text = Snapshot::save(&mut A320::new()).to_text()
+            .replacen(&format!("SNAPSHOT {}", Snapshot::VERSION), "SNAPSHOT 4", 1)
+            .lines().filter(|line| !line.contains("/FEEDERS/")).map(|line| format!("{}\n", line)).collect::<String>();
+
+        assert_eq!(Snapshot::from_text(&text), Err(SnapshotError::UnsupportedVersion(4)));
+    }
+
+    #[test]
+    fn snapshot_saved_before_fixed_steps_is_unsupported() {
+        let text = Snapshot::save(&mut Simulation::new(A320::new())).to_text()
+            .replacen(&format!("SNAPSHOT {}", Snapshot::VERSION), "SNAPSHOT 5", 1)
+            .lines().filter(|line| !line.starts_with("UNSPENT TIME\t")).map(|line| format!("{}\n", line)).collect::<String>();
+
+        assert_eq!(Snapshot::from_text(&text), Err(SnapshotError::UnsupportedVersion(5)));
+    }
+
+    #[test]
+    fn snapshot_saved_before_battery_charging_is_unsupported() {
+        let text = Snapshot::save(&mut A320::new()).to_text()
+            .replacen(&format!("SNAPSHOT {}", Snapshot::VERSION), "SNAPSHOT 3", 1)
+            .replace("/CONSUMED CHARGE\t", "/CHARGE\t")
+            .lines().filter(|line| !line.contains("/BAT 1/CURRENT\t") && !line.contains("/BAT 2/CURRENT\t")).map(|line| format!("{}\n", line)).collect::<String>();
+
+        assert_eq!(Snapshot::from_text(&text), Err(SnapshotError::UnsupportedVersion(3)));
+    }
+
+    #[test]
+    fn snapshot_saved_without_push_button_availability_is_unsupported() {
+        let text = Snapshot::save(&mut A320::new()).to_text()
+            .replacen(&format!("SNAPSHOT {}", Snapshot::VERSION), "SNAPSHOT 2", 1)
+            .lines().filter(|line| !line.ends_with("/AVAILABLE\tb\t0")).map(|line| format!("{}\n", line)).collect::<String>();
+
+        assert_eq!(Snapshot::from_text(&text), Err(SnapshotError::UnsupportedVersion(2)));
+    }
+
+    #[test]
+    fn sample_contains_bus_sources_batteries_and_contactors() {
+        let mut a320 = A320::new();
+        a320.engine_mut(1).unwrap().n2 = Ratio::new::<percent>(80.);
+        a320.engine_mut(2).unwrap().n2 = Ratio::new::<percent>(80.);
+        tick_for(&mut a320, Duration::from_millis(500));
+
+        let sample = ScenarioAircraft::sample(&a320);
+        let value = |name: &str| sample.iter().find(|(column, _)| column == name).map(|(_, value)| value.clone());
+
+        assert_eq!(value("AC BUS 1 SOURCE"), Some(SampleValue::Text(String::from("GEN 1"))));
+        assert_eq!(value("AC BUS 2 SOURCE"), Some(SampleValue::Text(String::from("GEN 2"))));
+        assert_eq!(value("AC BUS 2 PATH"), Some(SampleValue::Text(String::from("GEN 2 > 9XU2 > AC BUS 2"))));
+        assert!(matches!(value("BAT 1 CHARGE"), Some(SampleValue::Number(charge)) if charge > 0.));
+        assert_eq!(value("9XU1 CLOSED"), Some(SampleValue::Bool(true)));
+        assert_eq!(value("3XG CLOSED"), Some(SampleValue::Bool(false)));
+    }
+
+    #[test]
+    fn single_large_tick_powers_ac_buses_from_running_engines() {
+        let mut reader_writer = running_engines_reader_writer();
+        let mut simulation = Simulation::new(A320::new());
+        simulation.tick(Duration::from_secs(1), &mut reader_writer);
+
+        assert_eq!(reader_writer.read("ELEC_AC_BUS_1_IS_POWERED"), Some(1.));
+        assert_eq!(reader_writer.read("ELEC_AC_BUS_2_IS_POWERED"), Some(1.));
+    }
+
+    #[test]
+    fn idg_temperature_converges_as_the_max_step_shrinks() {
+        let errors: Vec<f64> = [Duration::from_secs(10), Duration::from_secs(1), Duration::from_millis(100)].iter().map(|max_step| {
+            let (mut simulation, mut reader_writer) = connected_simulation(*max_step);
+            let initial = reader_writer.read("ELEC_IDG_1_TEMPERATURE").unwrap();
+
+            simulation.tick(Duration::from_secs(120), &mut reader_writer);
+
+            let nominal = EngineGenerator::IDG_NOMINAL_OIL_OUTLET_TEMPERATURE_IN_CELSIUS as f64;
+            let expected = nominal - (nominal - initial) * (-120. / EngineGenerator::IDG_THERMAL_TIME_CONSTANT_IN_SECONDS as f64).exp();
+            (reader_writer.read("ELEC_IDG_1_TEMPERATURE").unwrap() - expected).abs()
+        }).collect();
+
+        assert!(errors[0] > errors[1] && errors[1] > errors[2], "{:?}", errors);
+        assert!(errors[2] < 0.05, "{:?}", errors);
+    }
+
+    #[test]
+    fn battery_charge_converges_as_the_max_step_shrinks() {
+        let errors: Vec<f64> = [Duration::from_secs(10), Duration::from_secs(1), Duration::from_millis(100)].iter().map(|max_step| {
+            let (mut simulation, mut reader_writer) = connected_simulation_with(Battery::empty(1), *max_step);
+            let initial = simulation.aircraft().electrical().battery_1.charge().get::<ampere_hour>() as f64;
+
+            simulation.tick(Duration::from_secs(1200), &mut reader_writer);
+
+            // The charging current is limited to its maximum until the consumed charge has decayed far enough
+            // for the current to decay exponentially with the charging time constant.
+            let full = Battery::MAX_ELECTRIC_CHARGE_AMPERE_HOURS as f64;
+            let time_constant = Battery::CHARGING_TIME_CONSTANT_IN_SECONDS as f64;
+            let max_current = Battery::MAX_CHARGING_CURRENT_IN_AMPERES as f64;
+            let exponential_consumed = max_current * time_constant / 3600.;
+            let limited_duration = (full - initial - exponential_consumed) * 3600. / max_current;
+            let expected = full - exponential_consumed * (-(1200. - limited_duration) / time_constant).exp();
+            (simulation.aircraft().electrical().battery_1.charge().get::<ampere_hour>() as f64 - expected).abs()
+        }).collect();
+
+        assert!(errors[0] > errors[1] && errors[1] > errors[2], "{:?}", errors);
+        assert!(errors[2] < 0.01, "{:?}", errors);
+    }
+
+    /// Runs a simulation with running engines until the generators are connected, advancing it by the same
+    /// steps for every given max step, such that only what follows differs. The simulation then continues with
+    /// the given max step.
+    fn connected_simulation(max_step: Duration) -> (Simulation<A320>, InMemorySimulatorReaderWriter) {
+        connected_simulation_with(Battery::full(1), max_step)
+    }
+
+    fn connected_simulation_with(battery_1: Battery, max_step: Duration) -> (Simulation<A320>, InMemorySimulatorReaderWriter) {
+        let mut reader_writer = running_engines_reader_writer();
+        let mut a320 = A320::new();
+        a320.electrical_mut().battery_1 = battery_1;
+        let mut simulation = Simulation::with_max_step(a320, Duration::from_millis(100));
+        for _ in 0..10 {
+            simulation.tick(Duration::from_millis(100), &mut reader_writer);
+        }
+        simulation.set_max_step(max_step);
+
+        (simulation, reader_writer)
+    }
+
+    fn running_engines_reader_writer() -> InMemorySimulatorReaderWriter {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        reader_writer.write("TURB ENG CORRECTED N2:1", 80.);
+        reader_writer.write("TURB ENG CORRECTED N2:2", 80.);
+
+        reader_writer
+    }
+
+    fn tick_for(a320: &mut A320, duration: Duration) {
+        let step = Duration::from_millis(1);
+        let mut elapsed = Duration::from_millis(0);
+        while elapsed < duration {
+            a320.tick(&UpdateContext::new(step));
+            elapsed += step;
+        }
+    }
+}
+
+#[cfg(test)]
+mod a320_electrical_circuit_tests {
+    use uom::si::{electric_potential::volt, f32::{ElectricPotential, Ratio}, ratio::percent};
+
+    use crate::{ecam::{AttentionGetters, AuralAlert, FlightWarningComputer}, electrical::{Current, PowerSource}, simulator::{InMemorySimulatorReaderWriter, SimulatorReaderWriter, read_from_simulator, write_to_simulator}};
+
+    use super::*;
+
+    /// # Source
+    /// A320 manual electrical distribution table
+    #[test]
+    fn distribution_table_norm_conf() {
+        let tester = tester_with().running_engines().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
+        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(tester.tr_1_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(tester.tr_2_output().source(), PowerSource::EngineGenerator(2));
+        assert_eq!(tester.tr_ess_output().source(), PowerSource::None);
+        assert_eq!(tester.dc_bus_1_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(tester.dc_bus_2_output().source(), PowerSource::EngineGenerator(2));
+        assert_eq!(tester.dc_bat_bus_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(tester.dc_ess_bus_output().source(), PowerSource::EngineGenerator(1));
+    }
+
+    /// # Source
+    /// A320 manual electrical distribution table
+    #[test]
+    fn distribution_table_only_gen_1_available() {
+        let tester = tester_with().running_engine_1().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(1));
         assert_eq!(tester.tr_1_output().source(), PowerSource::EngineGenerator(1));
         assert_eq!(tester.tr_2_output().source(), PowerSource::EngineGenerator(1));
         assert_eq!(tester.tr_ess_output().source(), PowerSource::None);
         assert_eq!(tester.dc_bus_1_output().source(), PowerSource::EngineGenerator(1));
         assert_eq!(tester.dc_bus_2_output().source(), PowerSource::EngineGenerator(1));
         assert_eq!(tester.dc_bat_bus_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(tester.dc_ess_bus_output().source(), PowerSource::EngineGenerator(1));
     }
 
     /// # Source
@@ -276,6 +1893,7 @@ mod a320_electrical_circuit_tests {
         assert_eq!(tester.dc_bus_1_output().source(), PowerSource::EngineGenerator(2));
         assert_eq!(tester.dc_bus_2_output().source(), PowerSource::EngineGenerator(2));
         assert_eq!(tester.dc_bat_bus_output().source(), PowerSource::EngineGenerator(2));
+        assert_eq!(tester.dc_ess_bus_output().source(), PowerSource::EngineGenerator(2));
     }
 
     /// # Source
@@ -293,6 +1911,7 @@ mod a320_electrical_circuit_tests {
         assert_eq!(tester.dc_bus_1_output().source(), PowerSource::ApuGenerator);
         assert_eq!(tester.dc_bus_2_output().source(), PowerSource::ApuGenerator);
         assert_eq!(tester.dc_bat_bus_output().source(), PowerSource::ApuGenerator);
+        assert_eq!(tester.dc_ess_bus_output().source(), PowerSource::ApuGenerator);
     }
 
     /// # Source
@@ -300,7 +1919,7 @@ mod a320_electrical_circuit_tests {
     #[test]
     #[ignore]
     fn distribution_table_emergency_config_before_emergency_gen_available() {
-        let tester = tester_with().run();
+        let _tester = tester_with().run();
 
         // TODO
     }
@@ -309,7 +1928,7 @@ mod a320_electrical_circuit_tests {
     /// A320 manual electrical distribution table
     #[test]
     fn distribution_table_emergency_config_after_emergency_gen_available() {
-        let tester = tester_with().running_emergency_generator().run();
+        let tester = tester_with().running_emergency_generator().run_waiting_for_tr_undercurrent_confirmation();
 
         assert_eq!(tester.ac_bus_1_output().source(), PowerSource::None);
         assert_eq!(tester.ac_bus_2_output().source(), PowerSource::None);
@@ -320,13 +1939,14 @@ mod a320_electrical_circuit_tests {
         assert_eq!(tester.dc_bus_1_output().source(), PowerSource::None);
         assert_eq!(tester.dc_bus_2_output().source(), PowerSource::None);
         assert_eq!(tester.dc_bat_bus_output().source(), PowerSource::None);
+        assert_eq!(tester.dc_ess_bus_output().source(), PowerSource::EmergencyGenerator);
     }
 
     /// # Source
     /// A320 manual electrical distribution table
     #[test]
     fn distribution_table_tr_1_fault() {
-        let tester = tester_with().running_engines().and().failed_tr_1().run();
+        let tester = tester_with().running_engines().and().failed_tr_1().run_waiting_for_tr_undercurrent_confirmation();
 
         assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
         assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
@@ -337,13 +1957,14 @@ mod a320_electrical_circuit_tests {
         assert_eq!(tester.dc_bus_1_output().source(), PowerSource::EngineGenerator(2));
         assert_eq!(tester.dc_bus_2_output().source(), PowerSource::EngineGenerator(2));
         assert_eq!(tester.dc_bat_bus_output().source(), PowerSource::EngineGenerator(2));
+        assert_eq!(tester.dc_ess_bus_output().source(), PowerSource::EngineGenerator(1));
     }
 
     /// # Source
     /// A320 manual electrical distribution table
     #[test]
     fn distribution_table_tr_2_fault() {
-        let tester = tester_with().running_engines().and().failed_tr_2().run();
+        let tester = tester_with().running_engines().and().failed_tr_2().run_waiting_for_tr_undercurrent_confirmation();
 
         assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
         assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
@@ -354,13 +1975,14 @@ mod a320_electrical_circuit_tests {
         assert_eq!(tester.dc_bus_1_output().source(), PowerSource::EngineGenerator(1));
         assert_eq!(tester.dc_bus_2_output().source(), PowerSource::EngineGenerator(1));
         assert_eq!(tester.dc_bat_bus_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(tester.dc_ess_bus_output().source(), PowerSource::EngineGenerator(1));
     }
 
     /// # Source
     /// A320 manual electrical distribution table
     #[test]
     fn distribution_table_tr_1_and_2_fault() {
-        let tester = tester_with().running_engines().failed_tr_1().and().failed_tr_2().run();
+        let tester = tester_with().running_engines().failed_tr_1().and().failed_tr_2().run_waiting_for_tr_undercurrent_confirmation();
 
         assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
         assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
@@ -371,6 +1993,7 @@ mod a320_electrical_circuit_tests {
         assert_eq!(tester.dc_bus_1_output().source(), PowerSource::None);
         assert_eq!(tester.dc_bus_2_output().source(), PowerSource::None);
         assert_eq!(tester.dc_bat_bus_output().source(), PowerSource::None);
+        assert_eq!(tester.dc_ess_bus_output().source(), PowerSource::EngineGenerator(1));
     }
 
     /// # Source
@@ -427,305 +2050,909 @@ mod a320_electrical_circuit_tests {
     }
 
     #[test]
-    fn when_no_power_source_ac_bus_1_is_unpowered() {
-        let tester = tester().run();
+    fn when_engine_1_gen_frequency_out_of_tolerance_engine_1_gen_does_not_power_ac_buses() {
+        let tester = tester_with().engine_1_running_at(Ratio::new::<percent>(EngineGenerator::ENGINE_N2_POWER_OUTPUT_THRESHOLD + 0.5)).run();
+
+        assert!(tester.ac_bus_1_output().is_unpowered());
+        assert!(tester.ac_bus_2_output().is_unpowered());
+    }
+
+    #[test]
+    fn when_engine_1_gen_frequency_comes_within_tolerance_engine_1_gen_powers_ac_bus_1() {
+        let tester = tester_with().engine_1_running_at(Ratio::new::<percent>(EngineGenerator::ENGINE_N2_POWER_OUTPUT_THRESHOLD + 0.5)).run()
+            .then_continue_with().running_engine_1().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
+    }
+
+    #[test]
+    fn engine_1_n2_hovering_around_the_power_output_threshold_does_not_switch_the_ac_ess_feed() {
+        let mut tester = tester_with().running_engine_1().run();
+        assert!(tester.elec.ac_ess_feed_contactor_1.is_closed());
+
+        let mut elapsed = Duration::from_millis(0);
+        let mut above = true;
+        while elapsed < A320ElectricalCircuit::AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS * 2 {
+            let n2 = EngineGenerator::ENGINE_N2_POWER_OUTPUT_THRESHOLD + if above { 1. } else { -1. };
+            tester = tester.engine_1_running_at(Ratio::new::<percent>(n2)).run_for(ElectricalCircuitTester::STEP * 3);
+
+            assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
+            assert!(tester.elec.ac_ess_feed_contactor_1.is_closed());
+            assert!(tester.ac_ess_feed_contactor_2_open());
+            assert!(!tester.elec.ac_ess_feed_contactor_delay_logic_gate.output());
+            elapsed += ElectricalCircuitTester::STEP * 3;
+            above = !above;
+        }
+    }
+
+    #[test]
+    fn when_no_power_source_ac_bus_1_is_unpowered() {
+        let tester = tester().run();
+
+        assert!(tester.ac_bus_1_output().is_unpowered());
+    }
+
+    #[test]
+    fn when_no_power_source_ac_bus_2_is_unpowered() {
+        let tester = tester().run();
+
+        assert!(tester.ac_bus_2_output().is_unpowered());
+    }
+
+    #[test]
+    fn when_engine_1_and_apu_running_apu_powers_ac_bus_2() {
+        let tester = tester_with().running_engine_1().and().running_apu().run();
+
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::ApuGenerator);
+    }
+
+    #[test]
+    fn when_engine_2_and_apu_running_apu_powers_ac_bus_1() {
+        let tester = tester_with().running_engine_2().and().running_apu().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::ApuGenerator);
+    }        
+
+    #[test]
+    fn when_only_apu_running_apu_powers_ac_bus_1_and_2() {
+        let tester = tester_with().running_apu().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::ApuGenerator);
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::ApuGenerator);
+    }
+    
+    #[test]
+    fn when_engine_1_running_and_external_power_connected_ext_pwr_powers_ac_bus_2() {
+        let tester = tester_with().running_engine_1().and().connected_external_power().run();
+
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::External);
+    }
+
+    #[test]
+    fn when_engine_2_running_and_external_power_connected_ext_pwr_powers_ac_bus_1() {
+        let tester = tester_with().running_engine_2().and().connected_external_power().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::External);
+    }
+
+    #[test]
+    fn when_only_external_power_connected_ext_pwr_powers_ac_bus_1_and_2() {
+        let tester = tester_with().connected_external_power().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::External);
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::External);
+    }
+
+    #[test]
+    fn when_external_power_connected_and_apu_running_external_power_has_priority() {
+        let tester = tester_with().connected_external_power().and().running_apu().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::External);
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::External);
+    }
+
+    #[test]
+    fn when_both_engines_running_and_external_power_connected_engines_power_ac_buses() {
+        let tester = tester_with().running_engines().and().connected_external_power().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
+    }
+
+    #[test]
+    fn when_both_engines_running_and_apu_running_engines_power_ac_buses() {
+        let tester = tester_with().running_engines().and().running_apu().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
+    }
+
+    #[test]
+    fn when_transferring_from_external_power_to_apu_ac_buses_are_briefly_unpowered() {
+        let tester = tester_with().connected_external_power().and().running_apu().run()
+            .then_continue_with().ext_pwr_off().run_for(Contactor::DEFAULT_OPENING_TIME + Duration::from_millis(5));
+
+        assert!(tester.ac_bus_1_output().is_unpowered());
+        assert!(tester.ac_bus_2_output().is_unpowered());
+    }
+
+    #[test]
+    fn when_transferring_from_external_power_to_apu_apu_powers_ac_buses_once_transfer_completed() {
+        let tester = tester_with().connected_external_power().and().running_apu().run()
+            .then_continue_with().ext_pwr_off().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::ApuGenerator);
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::ApuGenerator);
+    }
+
+    #[test]
+    fn when_engine_2_gen_becomes_available_ac_bus_2_is_briefly_unpowered_before_engine_2_gen_powers_it() {
+        let tester = tester_with().running_engine_1().run()
+            .then_continue_with().running_engine_2().run_for(EngineGenerator::POWER_OUTPUT_CONFIRMATION_TIME + Contactor::DEFAULT_OPENING_TIME + Duration::from_millis(5));
+
+        assert!(tester.ac_bus_2_output().is_unpowered());
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
+
+        let tester = tester.run();
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
+    }
+
+    #[test]
+    fn ac_bus_1_powers_ac_ess_bus_whenever_it_is_powered() {
+        let tester = tester_with().running_engines().run();
+        
+        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(1));
+    }
+
+    #[test]
+    fn when_ac_bus_1_becomes_unpowered_nothing_powers_ac_ess_bus_for_a_while() {
+        let tester = tester_with().running_engines().and().failed_ac_bus_1()
+            .run_waiting_until_just_before_ac_ess_feed_transition();
+
+        assert!(tester.ac_ess_bus_output().is_unpowered());
+    }
+
+    /// # Source
+    /// Discord (komp#1821):
+    /// > The fault light will extinguish after 3 seconds. That's the time delay before automatic switching is activated in case of AC BUS 1 loss.
+    #[test]
+    fn with_ac_bus_1_being_unpowered_after_a_delay_ac_bus_2_powers_ac_ess_bus() {
+        let tester = tester_with().running_engines().and().failed_ac_bus_1()
+            .run_waiting_for_ac_ess_feed_transition();
+
+        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(2));
+    }
+
+    /// # Source
+    /// Discord (komp#1821):
+    /// > When AC BUS 1 is available again, it will switch back automatically without delay, unless the AC ESS FEED button is on ALTN.
+    #[test]
+    fn ac_bus_1_powers_ac_ess_bus_immediately_when_ac_bus_1_becomes_powered_after_ac_bus_2_was_powering_ac_ess_bus() {
+        let tester = tester_with().running_engines().and().failed_ac_bus_1()
+            .run_waiting_for_ac_ess_feed_transition()
+            .then_continue_with().normal_ac_bus_1().run();
+
+        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(1));
+    }
+
+    #[test]
+    // For now...
+    fn nothing_powers_ac_ess_bus_when_ac_bus_1_and_2_failed() {
+        let tester = tester_with().running_engines().failed_ac_bus_1().and().failed_ac_bus_2().run();
+
+        assert!(tester.ac_ess_bus_output().is_unpowered());
+    }
+
+    #[test]
+    fn when_gen_1_off_and_only_engine_1_running_nothing_powers_ac_buses() {
+        let tester = tester_with().running_engine_1().and().gen_1_off().run();
+
+        assert!(tester.ac_bus_1_output().is_unpowered());
+        assert!(tester.ac_bus_2_output().is_unpowered());
+    }
+
+    #[test]
+    fn when_gen_1_off_and_both_engines_running_engine_2_powers_ac_buses() {
+        let tester = tester_with().running_engines().and().gen_1_off().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(2));
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
+    }
+
+    #[test]
+    fn when_gen_2_off_and_only_engine_2_running_nothing_powers_ac_buses() {
+        let tester = tester_with().running_engine_2().and().gen_2_off().run();
+
+        assert!(tester.ac_bus_1_output().is_unpowered());
+        assert!(tester.ac_bus_2_output().is_unpowered());
+    }
+
+    #[test]
+    fn when_gen_2_off_and_both_engines_running_engine_1_powers_ac_buses() {
+        let tester = tester_with().running_engines().and().gen_2_off().run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(1));
+    }
+
+    #[test]
+    fn when_ac_ess_feed_push_button_altn_ac_bus_2_powers_ac_ess_bus() {
+        let tester = tester_with().running_engines().and().ac_ess_feed_altn().run();
+
+        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(2));
+    }
+
+    #[test]
+    fn when_only_apu_running_but_apu_gen_push_button_off_nothing_powers_ac_bus_1_and_2() {
+        let tester = tester_with().running_apu().and().apu_gen_off().run();
+
+        assert!(tester.ac_bus_1_output().is_unpowered());
+        assert!(tester.ac_bus_2_output().is_unpowered());
+    }
+
+    #[test]
+    fn when_only_external_power_connected_but_ext_pwr_push_button_off_nothing_powers_ac_bus_1_and_2() {
+        let tester = tester_with().connected_external_power().and().ext_pwr_off().run();
+
+        assert!(tester.ac_bus_1_output().is_unpowered());
+        assert!(tester.ac_bus_2_output().is_unpowered());
+    }
+
+    #[test]
+    fn when_ac_bus_1_powered_tr_1_is_powered() {
+        let tester = tester_with().running_engines().run();
+
+        assert!(tester.tr_1_output().is_powered());
+    }
+
+    #[test]
+    fn when_ac_bus_1_unpowered_tr_1_is_unpowered() {
+        let tester = tester().run();
+
+        assert!(tester.tr_1_output().is_unpowered());
+    }
+
+    #[test]
+    fn when_ac_bus_2_powered_tr_2_is_powered() {
+        let tester = tester_with().running_engines().run();
+
+        assert!(tester.tr_2_output().is_powered());
+    }
+
+    #[test]
+    fn when_ac_bus_2_unpowered_tr_2_is_unpowered() {
+        let tester = tester().run();
+
+        assert!(tester.tr_2_output().is_unpowered());
+    }
+
+    #[test]
+    fn when_tr_1_failed_ess_tr_powered() {
+        let tester = tester_with().running_engines().and().failed_tr_1().run_waiting_for_tr_undercurrent_confirmation();
+
+        assert!(tester.tr_ess_output().is_powered());
+    }
+
+    #[test]
+    fn before_tr_1_undercurrent_confirmed_ess_tr_unpowered() {
+        let tester = tester_with().running_engines().run()
+            .then_continue_with().failed_tr_1().run_for(TransformerRectifierMonitor::UNDERCURRENT_CONFIRMATION_TIME - Duration::from_millis(100));
+
+        assert!(tester.tr_ess_output().is_unpowered());
+        assert_eq!(tester.dc_ess_bus_output().source(), PowerSource::EngineGenerator(2));
+    }
+
+    #[test]
+    fn when_tr_1_overheats_its_contactor_opens_and_ess_tr_powered() {
+        let tester = tester_with().running_engines().and().failed("TR 1 OVERHEAT").run_for(Duration::from_secs(120));
+
+        assert!(tester.elec.tr_1_contactor.is_open());
+        assert!(tester.tr_ess_output().is_powered());
+        assert_eq!(tester.dc_bus_1_output().source(), PowerSource::EngineGenerator(2));
+    }
+
+    #[test]
+    fn when_tr_1_unpowered_ess_tr_powered() {
+        let tester = tester_with().running_engines().and().failed_ac_bus_1()
+            // AC ESS BUS which powers TR ESS is only supplied with power after the delay.
+            .run_waiting_for_ac_ess_feed_transition();
+
+        assert!(tester.tr_ess_output().is_powered());
+    }
+
+    #[test]
+    fn when_tr_2_failed_ess_tr_powered() {
+        let tester = tester_with().running_engines().and().failed_tr_2().run_waiting_for_tr_undercurrent_confirmation();
+
+        assert!(tester.tr_ess_output().is_powered());
+    }
+
+    #[test]
+    fn when_tr_2_unpowered_ess_tr_powered() {
+        let tester = tester_with().running_engines().and().failed_ac_bus_2().run_waiting_for_tr_undercurrent_confirmation();
+
+        assert!(tester.tr_ess_output().is_powered());
+    }
+
+    #[test]
+    fn when_tr_1_and_2_normal_ess_tr_unpowered() {
+        let tester = tester_with().running_engines().run();
+
+        assert!(tester.tr_ess_output().is_unpowered());
+    }
+
+    #[test]
+    fn when_ac_bus_1_and_ac_bus_2_are_lost_a_running_emergency_gen_powers_tr_ess() {
+        let tester = tester_with().running_engines()
+            .failed_ac_bus_1().failed_ac_bus_2().and().running_emergency_generator().run();
+
+        assert!(tester.tr_ess_output().is_powered());
+        assert_eq!(tester.tr_ess_output().source(), PowerSource::EmergencyGenerator);
+    }
+
+    #[test]
+    fn when_ac_bus_1_and_ac_bus_2_are_lost_a_running_emergency_gen_powers_ac_ess_bus() {
+        let tester = tester_with().running_engines()
+            .failed_ac_bus_1().failed_ac_bus_2().and().running_emergency_generator().run_waiting_for_tr_undercurrent_confirmation();
+
+        assert!(tester.ac_ess_bus_output().is_powered());
+        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EmergencyGenerator);
+    }
+
+    #[test]
+    fn when_ac_bus_1_and_ac_bus_2_are_lost_neither_ac_ess_feed_contactor_is_closed() {
+        let tester = tester_with().running_engines()
+            .failed_ac_bus_1().and().failed_ac_bus_2().run();
+
+        assert!(tester.both_ac_ess_feed_contactors_open());
+    }
+
+    #[test]
+    fn when_engine_1_gen_contactor_stuck_open_engine_1_gen_does_not_power_ac_bus_1() {
+        let tester = tester_with().running_engines().and().failed("9XU1 STUCK OPEN").run();
+
+        assert!(tester.ac_bus_1_output().is_unpowered());
+        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
+    }
+
+    #[test]
+    fn when_bus_tie_1_contactor_stuck_closed_and_only_engine_2_running_engine_2_gen_powers_ac_bus_1() {
+        let tester = tester_with().running_engine_2().and().failed("11XU1 STUCK CLOSED").run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(2));
+    }
+
+    #[test]
+    fn when_ac_ess_feed_contactor_1_stuck_closed_ac_ess_feed_altn_does_not_transfer_ac_ess_bus_to_ac_bus_2() {
+        let tester = tester_with().running_engines().run()
+            .then_continue_with().failed("3XC1 STUCK CLOSED").and().ac_ess_feed_altn().run();
+
+        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(1));
+        assert!(tester.ac_ess_feed_contactor_2_open());
+    }
+
+    #[test]
+    fn when_ac_ess_feed_contactor_1_welded_ac_ess_feed_altn_closes_ac_ess_feed_contactor_2() {
+        let tester = tester_with().running_engines().run()
+            .then_continue_with().failed("3XC1 WELDED").and().ac_ess_feed_altn().run();
+
+        assert!(!tester.ac_ess_feed_contactor_2_open());
+    }
+
+    #[test]
+    fn apu_gen_and_engine_gen_are_not_paralleled() {
+        let tester = tester_with().running_engine_1().and().running_apu().run();
+
+        assert!(tester.elec.paralleled_sources().is_empty());
+    }
+
+    #[test]
+    fn when_bus_tie_1_contactor_welded_engine_1_gen_and_apu_gen_are_paralleled_on_ac_buses() {
+        let tester = tester_with().running_engine_1().and().running_apu().and().failed("11XU1 WELDED").run();
+
+        assert_eq!(tester.elec.paralleled_sources(), vec![
+            Paralleling { bus: String::from("AC BUS 1"), sources: (PowerSource::EngineGenerator(1), PowerSource::ApuGenerator) },
+            Paralleling { bus: String::from("AC BUS 2"), sources: (PowerSource::ApuGenerator, PowerSource::EngineGenerator(1)) }
+        ]);
+    }
+
+    #[test]
+    fn when_ac_ess_feed_contactor_1_welded_and_ac_ess_feed_altn_engine_gens_are_paralleled_on_ac_ess_bus() {
+        let tester = tester_with().running_engines().run()
+            .then_continue_with().failed("3XC1 WELDED").and().ac_ess_feed_altn().run();
+
+        assert_eq!(tester.elec.paralleled_sources().iter().map(|paralleling| paralleling.to_string()).collect::<Vec<String>>(),
+            vec!["GEN 1 and GEN 2 are paralleled on AC ESS BUS"]);
+    }
+
+    #[test]
+    fn when_engine_1_gen_failed_engine_2_gen_powers_ac_bus_1() {
+        let tester = tester_with().running_engines().and().failed("GEN 1").run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(2));
+    }
+
+    #[test]
+    fn when_engine_1_gen_fails_while_online_engine_2_gen_powers_ac_bus_1() {
+        let tester = tester_with().running_engines().run()
+            .then_continue_with().failed("GEN 1").run();
+
+        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(2));
+        assert!(tester.elec.engine_1_gcu.has_fault());
+    }
+
+    #[test]
+    fn when_scheduled_ac_bus_1_failure_time_reached_ac_bus_1_is_unpowered() {
+        let tester = tester_with().running_engines().and().failed_at("AC BUS 1", Duration::from_millis(200)).run();
+
+        assert!(tester.ac_bus_1_output().is_unpowered());
+    }
+
+    #[test]
+    fn before_scheduled_ac_bus_1_failure_time_reached_ac_bus_1_is_powered() {
+        let tester = tester_with().running_engines().and().failed_at("AC BUS 1", Duration::from_secs(10)).run();
+
+        assert!(tester.ac_bus_1_output().is_powered());
+    }
+
+    #[test]
+    fn consumer_is_powered_by_its_bus_through_its_circuit_breaker() {
+        let tester = tester_with().running_engines().run();
+
+        assert!(tester.consumer_is_powered("FUEL PUMP 1"));
+    }
+
+    #[test]
+    fn when_circuit_breaker_pulled_consumer_is_unpowered() {
+        let tester = tester_with().running_engines().and().pulled_circuit_breaker("105VU", "H12").run();
+
+        assert!(tester.elec.circuit_breaker("105VU", "H12").unwrap().is_pulled());
+        assert!(!tester.consumer_is_powered("FUEL PUMP 1"));
+    }
+
+    #[test]
+    fn when_consumer_short_circuited_its_circuit_breaker_trips() {
+        let tester = tester_with().running_engines().and().failed("FUEL PUMP 1 SHORT CIRCUIT").run_for(Duration::from_secs(1));
+
+        assert!(tester.elec.circuit_breaker("105VU", "H12").unwrap().is_tripped());
+        assert!(!tester.consumer_is_powered("FUEL PUMP 1"));
+    }
+
+    #[test]
+    fn when_tripped_circuit_breaker_reset_after_short_circuit_cleared_consumer_is_powered() {
+        let tester = tester_with().running_engines().and().failed("FUEL PUMP 1 SHORT CIRCUIT").run_for(Duration::from_secs(1))
+            .then_continue_with().normal("FUEL PUMP 1 SHORT CIRCUIT").and().reset_circuit_breaker("105VU", "H12").run();
+
+        assert!(tester.elec.circuit_breaker("105VU", "H12").unwrap().is_closed());
+        assert!(tester.consumer_is_powered("FUEL PUMP 1"));
+    }
+
+    #[test]
+    fn unknown_circuit_breaker_cannot_be_found() {
+        assert!(tester().elec.circuit_breaker("49VU", "Z99").is_none());
+    }
+
+    #[test]
+    fn tr_1_supplies_the_load_of_dc_bus_1_dc_bat_bus_and_dc_ess_bus() {
+        let tester = tester_with().running_engines().run();
+
+        assert_eq!(tester.elec.tr_1_output_current(), tester.demand_on(&[A320Bus::DcBus1, A320Bus::DcBatBus, A320Bus::DcEssBus]));
+        assert_eq!(tester.elec.tr_2_output_current(), tester.demand_on(&[A320Bus::DcBus2]));
+    }
+
+    #[test]
+    fn when_tr_2_failed_tr_1_supplies_the_load_of_dc_buses_1_and_2_and_dc_bat_bus() {
+        let tester = tester_with().running_engines().and().failed_tr_2().run_waiting_for_tr_undercurrent_confirmation();
+
+        assert_eq!(tester.elec.tr_1_output_current(), tester.demand_on(&[A320Bus::DcBus1, A320Bus::DcBus2, A320Bus::DcBatBus]));
+        assert_eq!(tester.elec.tr_2_output_current(), ElectricCurrent::new::<ampere>(0.));
+    }
+
+    #[test]
+    fn when_ess_tr_engaged_it_supplies_the_load_of_dc_ess_bus() {
+        let tester = tester_with().running_engines().and().failed_tr_1().run_waiting_for_tr_undercurrent_confirmation();
+
+        assert_eq!(tester.elec.tr_ess_output_current(), tester.demand_on(&[A320Bus::DcEssBus]));
+    }
+
+    #[test]
+    fn when_only_modelled_dc_bus_2_consumer_unpowered_tr_2_has_no_undercurrent() {
+        let tester = tester_with().running_engines().and().pulled_circuit_breaker("121VU", "M08").run_waiting_for_tr_undercurrent_confirmation();
+
+        assert!(!tester.active_alerts_in(FlightPhase::AboveFifteenHundredFeet).is_active("ELEC TR 2 FAULT"));
+        assert_eq!(tester.tr_ess_output().source(), PowerSource::None);
+        assert_eq!(tester.elec.tr_2_output_current(), ElectricCurrent::new::<ampere>(A320ElectricalCircuit::UNMODELLED_DC_BUS_LOAD_IN_AMPERES));
+    }
+
+    #[test]
+    fn dc_bus_1_current_follows_a_load_change_on_the_same_tick() {
+        let mut tester = tester_with().running_engines().run();
+        let tr_1_load_before = tester.elec.tr_1_output_current();
+
+        tester.elec.pull_circuit_breaker("121VU", "M07");
+        let tester = tester.run_for(ElectricalCircuitTester::STEP);
+
+        assert_eq!(tester.elec.tr_1_output_current(), tr_1_load_before - ElectricCurrent::new::<ampere>(5.));
+        assert!(matches!(tester.dc_bus_1_output(), Current::Direct(_, _, current) if current == tester.elec.tr_1_output_current()));
+        assert!(matches!(tester.dc_ess_bus_output(), Current::Direct(_, _, current) if current == tester.elec.tr_1_output_current()));
+    }
+
+    #[test]
+    fn dc_bus_1_voltage_droops_below_no_load_voltage_under_load() {
+        let tester = tester_with().running_engines().run();
+
+        assert!(matches!(tester.dc_bus_1_output(), Current::Direct(_, voltage, _)
+            if voltage < ElectricPotential::new::<volt>(TransformerRectifier::NO_LOAD_OUTPUT_VOLTAGE)));
+    }
+
+    #[test]
+    fn when_battery_1_full_it_is_not_powered_by_dc_bat_bus() {
+        let tester = tester_with().running_engines().run();
+
+        assert!(tester.battery_1_input().is_unpowered())
+    }
+
+    #[test]
+    fn when_battery_1_not_full_it_is_powered_by_dc_bat_bus() {
+        let tester = tester_with().running_engines().and().empty_battery_1().run();
+        
+        assert!(tester.battery_1_input().is_powered());
+    }
+
+    #[test]
+    fn when_battery_2_full_it_is_not_powered_by_dc_bat_bus() {
+        // Battery 2 supplies the DC BAT BUS until the TRs are available, after which it is recharged.
+        let tester = tester_with().running_engines().run_for(Duration::from_secs(1)).run();
+
+        assert!(tester.battery_2_input().is_unpowered())
+    }
+
+    #[test]
+    fn when_battery_2_not_full_it_is_powered_by_dc_bat_bus() {
+        let tester = tester_with().running_engines().and().empty_battery_2().run();
+        
+        assert!(tester.battery_2_input().is_powered());
+    }
+
+    #[test]
+    fn electrical_page_shows_running_generator_in_green() {
+        let page = tester_with().running_engines().run().elec.electrical_page();
 
-        assert!(tester.ac_bus_1_output().is_unpowered());
+        assert_eq!(page.gen_1.voltage.value(), ElectricPotential::new::<volt>(115.));
+        assert_eq!(page.gen_1.voltage.colour(), IndicationColour::Green);
+        assert_eq!(page.gen_1.frequency.colour(), IndicationColour::Green);
+        assert_eq!(page.gen_1.load.colour(), IndicationColour::Green);
+        assert!(page.gen_1.load.value() > Ratio::new::<percent>(0.));
     }
 
     #[test]
-    fn when_no_power_source_ac_bus_2_is_unpowered() {
-        let tester = tester().run();
+    fn power_path_through_bus_tie() {
+        let tester = tester_with().running_engine_1().run();
 
-        assert!(tester.ac_bus_2_output().is_unpowered());
+        assert_eq!(tester.elec.power_path("AC BUS 2"), vec!["GEN 1", "9XU1", "11XU1", "11XU2", "AC BUS 2"]);
     }
 
     #[test]
-    fn when_engine_1_and_apu_running_apu_powers_ac_bus_2() {
-        let tester = tester_with().running_engine_1().and().running_apu().run();
+    fn power_path_of_dc_bus_1_through_dc_bat_bus_when_tr_1_failed() {
+        let tester = tester_with().running_engines().and().failed_tr_1().run_waiting_for_tr_undercurrent_confirmation();
 
-        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::ApuGenerator);
+        assert_eq!(tester.elec.power_path("DC BUS 1"),
+            vec!["GEN 2", "9XU2", "AC BUS 2", "TR 2", "5PU2", "DC BUS 2", "1PC2", "DC BAT BUS", "1PC1", "DC BUS 1"]);
+        assert_eq!(tester.elec.power_path("DC ESS BUS"), vec!["GEN 1", "9XU1", "AC BUS 1", "3XC1", "AC ESS BUS", "15XE1", "ESS TR", "3PE", "DC ESS BUS"]);
     }
 
     #[test]
-    fn when_engine_2_and_apu_running_apu_powers_ac_bus_1() {
-        let tester = tester_with().running_engine_2().and().running_apu().run();
+    fn power_path_from_emergency_generator() {
+        let tester = tester_with().running_emergency_generator().run_waiting_for_tr_undercurrent_confirmation();
 
-        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::ApuGenerator);
-    }        
+        assert_eq!(tester.elec.power_path("AC ESS BUS"), vec!["EMER GEN", "2XE", "15XE1", "AC ESS BUS"]);
+    }
 
     #[test]
-    fn when_only_apu_running_apu_powers_ac_bus_1_and_2() {
-        let tester = tester_with().running_apu().run();
+    fn power_path_of_unpowered_or_unknown_conductor_is_empty() {
+        let tester = tester_with().run();
 
-        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::ApuGenerator);
-        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::ApuGenerator);
+        assert!(tester.elec.power_path("AC BUS 1").is_empty());
+        assert!(tester.elec.power_path("AC BUS 3").is_empty());
     }
-    
+
     #[test]
-    fn when_engine_1_running_and_external_power_connected_ext_pwr_powers_ac_bus_2() {
-        let tester = tester_with().running_engine_1().and().connected_external_power().run();
+    fn dot_colours_edges_by_the_source_flowing_through_them() {
+        let tester = tester_with().running_engine_1().run();
+        let dot = tester.elec.to_dot();
 
-        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::External);
+        assert!(dot.starts_with("digraph \"A320 ELECTRICAL\" {\n"));
+        assert!(dot.contains("\"GEN 1\" -> \"9XU1\" [color=blue, penwidth=2, tooltip=\"GEN 1\"];"));
+        assert!(dot.contains("\"11XU1\" -> \"11XU2\" [color=blue, penwidth=2, tooltip=\"GEN 1\"];"));
+        assert!(dot.contains("\"11XU2\" -> \"11XU1\" [color=grey, style=dashed];"));
+        assert!(dot.contains("\"GEN 2\" -> \"9XU2\" [color=grey, style=dashed];"));
+        assert!(dot.contains("\"AC BUS 2\" [shape=box, style=bold, width=2, color=blue, tooltip=\"GEN 1 > 9XU1 > 11XU1 > 11XU2 > AC BUS 2\"];"));
+        assert!(dot.ends_with("}\n"));
     }
 
     #[test]
-    fn when_engine_2_running_and_external_power_connected_ext_pwr_powers_ac_bus_1() {
-        let tester = tester_with().running_engine_2().and().connected_external_power().run();
+    fn dot_only_colours_the_edge_a_conductor_is_fed_through() {
+        let tester = tester_with().running_engine_1().run();
+        let dot = tester.elec.to_dot();
 
-        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::External);
+        assert!(dot.contains("\"9XU1\" -> \"AC BUS 1\" [color=blue, penwidth=2, tooltip=\"GEN 1\"];"));
+        assert!(dot.contains("\"11XU1\" -> \"AC BUS 1\" [color=grey, style=dashed];"));
+        assert!(dot.contains("\"DC BAT BUS\" -> \"6PB1\" [color=blue, penwidth=2, tooltip=\"GEN 1\"];"));
+        assert!(dot.contains("\"6PB1\" -> \"DC BAT BUS\" [color=grey, style=dashed];"));
     }
 
     #[test]
-    fn when_only_external_power_connected_ext_pwr_powers_ac_bus_1_and_2() {
-        let tester = tester_with().connected_external_power().run();
+    fn dot_fills_closed_contactors() {
+        let tester = tester_with().running_engine_1().run();
+        let dot = tester.elec.to_dot();
 
-        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::External);
-        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::External);
+        assert!(dot.contains("\"9XU1\" [shape=box, style=filled, fillcolor=palegreen];"));
+        assert!(dot.contains("\"9XU2\" [shape=box, style=filled, fillcolor=white];"));
     }
 
     #[test]
-    fn when_external_power_connected_and_apu_running_external_power_has_priority() {
-        let tester = tester_with().connected_external_power().and().running_apu().run();
+    fn electrical_page_crosses_out_generator_without_output() {
+        let page = tester_with().running_engine_1().run().elec.electrical_page();
 
-        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::External);
-        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::External);
+        assert_eq!(page.gen_2.voltage.colour(), IndicationColour::CrossedOut);
+        assert_eq!(page.gen_2.frequency.colour(), IndicationColour::CrossedOut);
+        assert_eq!(page.gen_2.load.colour(), IndicationColour::CrossedOut);
     }
 
     #[test]
-    fn when_both_engines_running_and_external_power_connected_engines_power_ac_buses() {
-        let tester = tester_with().running_engines().and().connected_external_power().run();
+    fn electrical_page_shows_failed_generator_voltage_in_amber() {
+        let page = tester_with().running_engines().and().failed("GEN 1").run().elec.electrical_page();
 
-        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
-        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
+        assert_eq!(page.gen_1.voltage.colour(), IndicationColour::Amber);
     }
 
     #[test]
-    fn when_both_engines_running_and_apu_running_engines_power_ac_buses() {
-        let tester = tester_with().running_engines().and().running_apu().run();
+    fn electrical_page_shows_only_load_supplied_by_the_generator() {
+        let page = tester_with().running_engine_1().run().elec.electrical_page();
 
-        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
-        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
+        assert!(page.gen_1.load.value() > tester_with().running_engines().run().elec.electrical_page().gen_1.load.value());
     }
 
     #[test]
-    fn ac_bus_1_powers_ac_ess_bus_whenever_it_is_powered() {
+    fn electrical_page_shows_tr_current_at_or_below_minimum_in_amber() {
         let tester = tester_with().running_engines().run();
-        
-        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(1));
+        let page = tester.elec.electrical_page();
+
+        assert_eq!(page.tr_1.current.value(), tester.demand_on(&[A320Bus::DcBus1, A320Bus::DcBatBus, A320Bus::DcEssBus]));
+        assert_eq!(page.tr_1.current.colour(), IndicationColour::Green);
+        assert_eq!(page.tr_1.voltage.colour(), IndicationColour::Green);
+        assert_eq!(page.tr_ess.current.colour(), IndicationColour::Amber);
     }
 
     #[test]
-    fn when_ac_bus_1_becomes_unpowered_nothing_powers_ac_ess_bus_for_a_while() {
-        let tester = tester_with().running_engines().and().failed_ac_bus_1()
-            .run_waiting_until_just_before_ac_ess_feed_transition();
+    fn electrical_page_shows_battery_voltage() {
+        let page = tester_with().running_engines().run().elec.electrical_page();
 
-        assert!(tester.ac_ess_bus_output().is_unpowered());
+        assert_eq!(page.battery_1.voltage.value(), ElectricPotential::new::<volt>(28.5));
+        assert_eq!(page.battery_1.voltage.colour(), IndicationColour::Green);
     }
 
-    /// # Source
-    /// Discord (komp#1821):
-    /// > The fault light will extinguish after 3 seconds. That's the time delay before automatic switching is activated in case of AC BUS 1 loss.
     #[test]
-    fn with_ac_bus_1_being_unpowered_after_a_delay_ac_bus_2_powers_ac_ess_bus() {
-        let tester = tester_with().running_engines().and().failed_ac_bus_1()
-            .run_waiting_for_ac_ess_feed_transition();
+    fn electrical_page_shows_discharge_of_battery_supplying_the_dc_bat_bus_in_amber() {
+        let tester = tester().run();
+        let page = tester.elec.electrical_page();
 
-        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(2));
+        assert_eq!(tester.dc_bat_bus_output().source(), PowerSource::Battery(2));
+        assert_eq!(page.battery_2.current.value(), -tester.demand_on(&[A320Bus::DcBatBus, A320Bus::DcEssBus]));
+        assert_eq!(page.battery_2.current.colour(), IndicationColour::Amber);
+        assert_eq!(page.battery_1.current.value(), ElectricCurrent::new::<ampere>(0.));
     }
 
-    /// # Source
-    /// Discord (komp#1821):
-    /// > When AC BUS 1 is available again, it will switch back automatically without delay, unless the AC ESS FEED button is on ALTN.
     #[test]
-    fn ac_bus_1_powers_ac_ess_bus_immediately_when_ac_bus_1_becomes_powered_after_ac_bus_2_was_powering_ac_ess_bus() {
-        let tester = tester_with().running_engines().and().failed_ac_bus_1()
-            .run_waiting_for_ac_ess_feed_transition()
-            .then_continue_with().normal_ac_bus_1().run();
+    fn electrical_page_shows_charging_current_of_battery_connected_to_powered_dc_bat_bus() {
+        let page = tester_with().running_engines().and().empty_battery_1().run().elec.electrical_page();
 
-        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(1));
+        assert_eq!(page.battery_1.current.value(), ElectricCurrent::new::<ampere>(Battery::MAX_CHARGING_CURRENT_IN_AMPERES));
+        assert_eq!(page.battery_1.current.colour(), IndicationColour::Green);
     }
 
     #[test]
-    // For now...
-    fn nothing_powers_ac_ess_bus_when_ac_bus_1_and_2_failed() {
-        let tester = tester_with().running_engines().failed_ac_bus_1().and().failed_ac_bus_2().run();
+    fn electrical_page_shows_no_current_of_full_battery() {
+        let page = tester_with().running_engines().run().elec.electrical_page();
 
-        assert!(tester.ac_ess_bus_output().is_unpowered());
+        assert_eq!(page.battery_1.current.value(), ElectricCurrent::new::<ampere>(0.));
+        assert_eq!(page.battery_1.current.colour(), IndicationColour::Green);
     }
 
     #[test]
-    fn when_gen_1_off_and_only_engine_1_running_nothing_powers_ac_buses() {
-        let tester = tester_with().running_engine_1().and().gen_1_off().run();
+    fn electrical_page_shows_bus_powered_state() {
+        let page = tester_with().running_engines().and().failed_ac_bus_1().run().elec.electrical_page();
 
-        assert!(tester.ac_bus_1_output().is_unpowered());
-        assert!(tester.ac_bus_2_output().is_unpowered());
+        assert!(page.bus("AC BUS 2").unwrap().powered.value());
+        assert_eq!(page.bus("AC BUS 2").unwrap().powered.colour(), IndicationColour::Green);
+        assert!(!page.bus("AC BUS 1").unwrap().powered.value());
+        assert_eq!(page.bus("AC BUS 1").unwrap().powered.colour(), IndicationColour::Amber);
     }
 
     #[test]
-    fn when_gen_1_off_and_both_engines_running_engine_2_powers_ac_buses() {
-        let tester = tester_with().running_engines().and().gen_1_off().run();
+    fn electrical_page_shows_contactor_state() {
+        let page = tester_with().running_engines().run().elec.electrical_page();
 
-        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(2));
-        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(2));
+        assert!(page.contactor("9XU1").unwrap().is_closed);
+        assert!(!page.contactor("11XU1").unwrap().is_closed);
     }
 
     #[test]
-    fn when_gen_2_off_and_only_engine_2_running_nothing_powers_ac_buses() {
-        let tester = tester_with().running_engine_2().and().gen_2_off().run();
+    fn electrical_page_shows_overheating_idg_temperature_in_amber() {
+        let page = tester_with().running_engines().and().failed("IDG 1 OVERHEAT").run_for(Duration::from_secs(120)).elec.electrical_page();
 
-        assert!(tester.ac_bus_1_output().is_unpowered());
-        assert!(tester.ac_bus_2_output().is_unpowered());
+        assert_eq!(page.idg_1_temperature.colour(), IndicationColour::Amber);
+        assert_eq!(page.idg_2_temperature.colour(), IndicationColour::Green);
     }
 
     #[test]
-    fn when_gen_2_off_and_both_engines_running_engine_1_powers_ac_buses() {
-        let tester = tester_with().running_engines().and().gen_2_off().run();
+    fn normal_configuration_triggers_no_alerts() {
+        let tester = tester_with().running_engines().run();
 
-        assert_eq!(tester.ac_bus_1_output().source(), PowerSource::EngineGenerator(1));
-        assert_eq!(tester.ac_bus_2_output().source(), PowerSource::EngineGenerator(1));
+        assert!(tester.active_alerts_in(FlightPhase::AboveFifteenHundredFeet).active_alerts().is_empty());
     }
 
     #[test]
-    fn when_ac_ess_feed_push_button_altn_ac_bus_2_powers_ac_ess_bus() {
-        let tester = tester_with().running_engines().and().ac_ess_feed_altn().run();
+    fn loss_of_ac_bus_1_and_2_triggers_emer_config_instead_of_bus_faults() {
+        let tester = tester_with().running_engines().failed_ac_bus_1().and().failed_ac_bus_2().run();
+        let fwc = tester.active_alerts_in(FlightPhase::AboveFifteenHundredFeet);
 
-        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EngineGenerator(2));
+        assert_eq!(fwc.active_alerts()[0], ELEC_EMER_CONFIG);
+        assert!(!fwc.is_active("ELEC AC BUS 1 FAULT"));
+        assert!(!fwc.is_active("ELEC AC BUS 2 FAULT"));
     }
 
     #[test]
-    fn when_only_apu_running_but_apu_gen_push_button_off_nothing_powers_ac_bus_1_and_2() {
-        let tester = tester_with().running_apu().and().apu_gen_off().run();
+    fn cold_and_dark_aircraft_triggers_no_emer_config() {
+        let tester = tester().run();
 
-        assert!(tester.ac_bus_1_output().is_unpowered());
-        assert!(tester.ac_bus_2_output().is_unpowered());
+        assert!(!tester.active_alerts_in(FlightPhase::ElectricalPower).is_active("ELEC EMER CONFIG"));
+        assert!(!tester.active_alerts_in(FlightPhase::SecondEngineShutdown).is_active("ELEC EMER CONFIG"));
     }
 
     #[test]
-    fn when_only_external_power_connected_but_ext_pwr_push_button_off_nothing_powers_ac_bus_1_and_2() {
-        let tester = tester_with().connected_external_power().and().ext_pwr_off().run();
+    fn loss_of_ac_bus_1_triggers_ac_bus_1_fault() {
+        let tester = tester_with().running_engines().and().failed_ac_bus_1().run_waiting_for_ac_ess_feed_transition();
+        let fwc = tester.active_alerts_in(FlightPhase::AboveFifteenHundredFeet);
 
-        assert!(tester.ac_bus_1_output().is_unpowered());
-        assert!(tester.ac_bus_2_output().is_unpowered());
+        assert!(fwc.is_active("ELEC AC BUS 1 FAULT"));
+        assert!(!fwc.is_active("ELEC AC ESS BUS FAULT"));
     }
 
     #[test]
-    fn when_ac_bus_1_powered_tr_1_is_powered() {
-        let tester = tester_with().running_engines().run();
+    fn gen_1_failing_while_online_triggers_gen_1_fault() {
+        let tester = tester_with().running_engines().run()
+            .then_continue_with().failed("GEN 1").run();
 
-        assert!(tester.tr_1_output().is_powered());
+        assert!(tester.active_alerts_in(FlightPhase::AboveFifteenHundredFeet).is_active("ELEC GEN 1 FAULT"));
     }
 
     #[test]
-    fn when_ac_bus_1_unpowered_tr_1_is_unpowered() {
-        let tester = tester().run();
+    fn gen_1_fault_is_inhibited_during_takeoff() {
+        let tester = tester_with().running_engines().run()
+            .then_continue_with().failed("GEN 1").run();
 
-        assert!(tester.tr_1_output().is_unpowered());
+        assert!(!tester.active_alerts_in(FlightPhase::EightyKnots).is_active("ELEC GEN 1 FAULT"));
     }
 
     #[test]
-    fn when_ac_bus_2_powered_tr_2_is_powered() {
-        let tester = tester_with().running_engines().run();
+    fn gen_1_off_does_not_trigger_gen_1_fault() {
+        let tester = tester_with().running_engines().and().gen_1_off().run();
 
-        assert!(tester.tr_2_output().is_powered());
+        assert!(!tester.active_alerts_in(FlightPhase::AboveFifteenHundredFeet).is_active("ELEC GEN 1 FAULT"));
     }
 
     #[test]
-    fn when_ac_bus_2_unpowered_tr_2_is_unpowered() {
-        let tester = tester().run();
+    fn tr_1_failure_triggers_tr_1_fault_once_confirmed() {
+        let tester = tester_with().running_engines().and().failed_tr_1().run_waiting_for_tr_undercurrent_confirmation();
 
-        assert!(tester.tr_2_output().is_unpowered());
+        assert!(tester.active_alerts_in(FlightPhase::AboveFifteenHundredFeet).is_active("ELEC TR 1 FAULT"));
     }
 
     #[test]
-    fn when_tr_1_failed_ess_tr_powered() {
-        let tester = tester_with().running_engines().and().failed_tr_1().run();
+    fn leaking_idg_1_triggers_idg_1_oil_lo_pr_with_its_procedure() {
+        let tester = tester_with().running_engines().and().failed("IDG 1 OIL LEAK").run();
+        let fwc = tester.active_alerts_in(FlightPhase::AboveFifteenHundredFeet);
 
-        assert!(tester.tr_ess_output().is_powered());
+        assert!(fwc.is_active("ELEC IDG 1 OIL LO PR"));
+        assert_eq!(fwc.upper_ecam_lines(), vec!["ELEC IDG 1 OIL LO PR", "-IDG 1.....DISC"]);
     }
 
     #[test]
-    fn when_tr_1_unpowered_ess_tr_powered() {
-        let tester = tester_with().running_engines().and().failed_ac_bus_1()
-            // AC ESS BUS which powers TR ESS is only supplied with power after the delay.
-            .run_waiting_for_ac_ess_feed_transition();
+    fn empty_battery_without_charging_triggers_bat_fault() {
+        let tester = tester_with().empty_battery_1().run();
 
-        assert!(tester.tr_ess_output().is_powered());
+        assert!(tester.active_alerts_in(FlightPhase::ElectricalPower).is_active("ELEC BAT 1 FAULT"));
     }
 
     #[test]
-    fn when_tr_2_failed_ess_tr_powered() {
-        let tester = tester_with().running_engines().and().failed_tr_2().run();
+    fn attention_getters_are_powered_by_dc_ess_bus() {
+        let tester = tester_with().running_engines().run();
 
-        assert!(tester.tr_ess_output().is_powered());
+        assert!(tester.elec.attention_getters_are_powered());
     }
 
     #[test]
-    fn when_tr_2_unpowered_ess_tr_powered() {
-        let tester = tester_with().running_engines().and().failed_ac_bus_2().run();
+    fn when_attention_getters_circuit_breaker_pulled_attention_getters_are_unpowered() {
+        let tester = tester_with().running_engines().and().pulled_circuit_breaker("49VU", "A05").run();
 
-        assert!(tester.tr_ess_output().is_powered());
+        assert!(!tester.elec.attention_getters_are_powered());
     }
 
     #[test]
-    fn when_tr_1_and_2_normal_ess_tr_unpowered() {
-        let tester = tester_with().running_engines().run();
+    fn emer_config_illuminates_master_warning() {
+        let tester = tester_with().running_engines().and().running_emergency_generator().run()
+            .then_continue_with().failed_ac_bus_1().and().failed_ac_bus_2().run_waiting_for_tr_undercurrent_confirmation();
+        let mut attention_getters = AttentionGetters::new();
+        attention_getters.update(&tester.active_alerts_in(FlightPhase::AboveFifteenHundredFeet), tester.elec.attention_getters_are_powered());
 
-        assert!(tester.tr_ess_output().is_unpowered());
+        assert!(attention_getters.master_warning_is_illuminated());
+        assert_eq!(attention_getters.aural_alert(), Some(AuralAlert::ContinuousRepetitiveChime));
     }
 
     #[test]
-    fn when_ac_bus_1_and_ac_bus_2_are_lost_a_running_emergency_gen_powers_tr_ess() {
-        let tester = tester_with().running_engines()
-            .failed_ac_bus_1().failed_ac_bus_2().and().running_emergency_generator().run();
+    fn circuit_writes_its_outputs_to_the_simulator() {
+        let mut tester = tester_with().running_engines().run();
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        write_to_simulator(&mut tester.elec, &mut reader_writer);
 
-        assert!(tester.tr_ess_output().is_powered());
-        assert_eq!(tester.tr_ess_output().source(), PowerSource::EmergencyGenerator);
+        assert_eq!(reader_writer.read("ELEC_AC_BUS_1_IS_POWERED"), Some(1.));
+        assert_eq!(reader_writer.read("ELEC_CONTACTOR_9XU1_IS_CLOSED"), Some(1.));
+        assert_eq!(reader_writer.read("ELEC_CONTACTOR_11XU1_IS_CLOSED"), Some(0.));
+        assert_eq!(reader_writer.read("ELEC_GEN_1_FREQUENCY"), Some(400.));
+        assert_eq!(reader_writer.read("ELEC_TR_1_CURRENT"), Some(tester.demand_on(&[A320Bus::DcBus1, A320Bus::DcBatBus, A320Bus::DcEssBus]).get::<ampere>() as f64));
+        assert_eq!(reader_writer.read("ELEC_BAT_1_CURRENT"), Some(0.));
     }
 
     #[test]
-    fn when_ac_bus_1_and_ac_bus_2_are_lost_a_running_emergency_gen_powers_ac_ess_bus() {
-        let tester = tester_with().running_engines()
-            .failed_ac_bus_1().failed_ac_bus_2().and().running_emergency_generator().run();
+    fn failure_ids_include_those_of_all_components() {
+        let ids = tester().elec.failure_ids();
 
-        assert!(tester.ac_ess_bus_output().is_powered());
-        assert_eq!(tester.ac_ess_bus_output().source(), PowerSource::EmergencyGenerator);
+        for id in ["GEN 1", "IDG 2 OIL LEAK", "APU GEN", "TR 1 OVERHEAT", "DC ESS BUS", "9XU1 WELDED", "FUEL PUMP 1 SHORT CIRCUIT"] {
+            assert!(ids.iter().any(|known| known == id), "{}", id);
+        }
     }
 
     #[test]
-    fn when_ac_bus_1_and_ac_bus_2_are_lost_neither_ac_ess_feed_contactor_is_closed() {
-        let tester = tester_with().running_engines()
-            .failed_ac_bus_1().and().failed_ac_bus_2().run();
+    fn components_write_their_own_variables_named_after_their_id() {
+        let mut tester = tester_with().running_engines().run();
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        write_to_simulator(&mut tester.elec, &mut reader_writer);
 
-        assert!(tester.both_ac_ess_feed_contactors_open());
+        assert_eq!(reader_writer.read("ELEC_DC_ESS_BUS_IS_POWERED"), Some(1.));
+        assert_eq!(reader_writer.read("ELEC_ESS_TR_CURRENT"), Some(0.));
+        assert_eq!(reader_writer.read("ELEC_APU_GEN_FREQUENCY"), Some(0.));
+        assert_eq!(reader_writer.read("ELEC_IDG_1_TEMPERATURE"), Some(tester.elec.engine_1_gen.idg_oil_outlet_temperature().get::<degree_celsius>() as f64));
+        assert!(reader_writer.read("ELEC_GEN_1_LOAD").unwrap() > 0.);
     }
 
     #[test]
-    fn when_battery_1_full_it_is_not_powered_by_dc_bat_bus() {
-        let tester = tester_with().running_engines().run();
+    fn overhead_panel_reads_push_button_states_from_the_simulator() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        reader_writer.write("OVHD_ELEC_GEN_1_PB_IS_ON", 0.);
+        reader_writer.write("OVHD_ELEC_GEN_2_PB_IS_ON", 1.);
+        reader_writer.write("OVHD_ELEC_AC_ESS_FEED_PB_IS_NORMAL", 0.);
 
-        assert!(tester.battery_1_input().is_unpowered())
-    }
+        let mut overhead = A320ElectricalOverheadPanel::new();
+        read_from_simulator(&mut overhead, &mut reader_writer);
 
-    #[test]
-    fn when_battery_1_not_full_it_is_powered_by_dc_bat_bus() {
-        let tester = tester_with().running_engines().and().empty_battery_1().run();
-        
-        assert!(tester.battery_1_input().is_powered());
+        assert!(overhead.gen_1.is_off());
+        assert!(overhead.gen_2.is_on());
+        assert!(overhead.ac_ess_feed.is_altn());
     }
 
     #[test]
-    fn when_battery_2_full_it_is_not_powered_by_dc_bat_bus() {
-        let tester = tester_with().running_engines().run();
+    fn engine_reads_its_own_n2_from_the_simulator() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        reader_writer.write("TURB ENG CORRECTED N2:2", 62.);
 
-        assert!(tester.battery_2_input().is_unpowered())
-    }
+        let mut engine1 = Engine::new(1);
+        let mut engine2 = Engine::new(2);
+        read_from_simulator(&mut engine1, &mut reader_writer);
+        read_from_simulator(&mut engine2, &mut reader_writer);
 
-    #[test]
-    fn when_battery_2_not_full_it_is_powered_by_dc_bat_bus() {
-        let tester = tester_with().running_engines().and().empty_battery_2().run();
-        
-        assert!(tester.battery_2_input().is_powered());
+        assert_eq!(engine1.n2, Ratio::new::<percent>(0.));
+        assert_eq!(engine2.n2, Ratio::new::<percent>(62.));
     }
 
     fn tester_with() -> ElectricalCircuitTester {
@@ -743,29 +2970,40 @@ mod a320_electrical_circuit_tests {
         ext_pwr: ExternalPowerSource,
         hyd: A320HydraulicCircuit,
         elec: A320ElectricalCircuit,
-        overhead: A320ElectricalOverheadPanel
+        overhead: A320ElectricalOverheadPanel,
+        failures: Failures
     }
     
     impl ElectricalCircuitTester {
+        const STEP: Duration = Duration::from_millis(1);
+        const CONTACTOR_TRANSITIONS_COMPLETED_DURATION: Duration = Duration::from_millis(500);
+
         fn new() -> ElectricalCircuitTester {
             ElectricalCircuitTester {
-                engine1: ElectricalCircuitTester::new_stopped_engine(),
-                engine2: ElectricalCircuitTester::new_stopped_engine(),
+                engine1: ElectricalCircuitTester::new_stopped_engine(1),
+                engine2: ElectricalCircuitTester::new_stopped_engine(2),
                 apu: ElectricalCircuitTester::new_stopped_apu(),
                 ext_pwr: ElectricalCircuitTester::new_disconnected_external_power(),
                 hyd: A320HydraulicCircuit::new(),
                 elec: A320ElectricalCircuit::new(),
-                overhead: A320ElectricalOverheadPanel::new()
+                overhead: A320ElectricalOverheadPanel::new(),
+                failures: Failures::new()
             }
         }
 
         fn running_engine_1(mut self) -> ElectricalCircuitTester {
-            self.engine1 = ElectricalCircuitTester::new_running_engine();
+            self.engine1 = ElectricalCircuitTester::new_running_engine(1);
+            self
+        }
+
+        fn engine_1_running_at(mut self, n2: Ratio) -> ElectricalCircuitTester {
+            self.engine1 = Engine::new(1);
+            self.engine1.n2 = n2;
             self
         }
 
         fn running_engine_2(mut self) -> ElectricalCircuitTester {
-            self.engine2 = ElectricalCircuitTester::new_running_engine();            
+            self.engine2 = ElectricalCircuitTester::new_running_engine(2);            
             self
         }
 
@@ -802,27 +3040,52 @@ mod a320_electrical_circuit_tests {
         }
 
         fn failed_ac_bus_1(mut self) -> ElectricalCircuitTester {
-            self.elec.ac_bus_1.fail();
+            self.failures.activate("AC BUS 1");
             self
         }
 
         fn failed_ac_bus_2(mut self) -> ElectricalCircuitTester {
-            self.elec.ac_bus_2.fail();
+            self.failures.activate("AC BUS 2");
             self
         }
 
         fn failed_tr_1(mut self) -> ElectricalCircuitTester {
-            self.elec.tr_1.fail();
+            self.failures.activate("TR 1");
             self
         }
 
         fn failed_tr_2(mut self) -> ElectricalCircuitTester {
-            self.elec.tr_2.fail();
+            self.failures.activate("TR 2");
+            self
+        }
+
+        fn failed(mut self, id: &str) -> ElectricalCircuitTester {
+            self.failures.activate(id);
+            self
+        }
+
+        fn normal(mut self, id: &str) -> ElectricalCircuitTester {
+            self.failures.deactivate(id);
+            self
+        }
+
+        fn pulled_circuit_breaker(mut self, panel: &str, position: &str) -> ElectricalCircuitTester {
+            self.elec.pull_circuit_breaker(panel, position);
+            self
+        }
+
+        fn reset_circuit_breaker(mut self, panel: &str, position: &str) -> ElectricalCircuitTester {
+            self.elec.reset_circuit_breaker(panel, position);
+            self
+        }
+
+        fn failed_at(mut self, id: &str, time: Duration) -> ElectricalCircuitTester {
+            self.failures.activate_at(id, time);
             self
         }
 
         fn normal_ac_bus_1(mut self) -> ElectricalCircuitTester {
-            self.elec.ac_bus_1.normal();
+            self.failures.deactivate("AC BUS 1");
             self
         }
 
@@ -892,6 +3155,10 @@ mod a320_electrical_circuit_tests {
             self.elec.dc_bat_bus.output()
         }
 
+        fn dc_ess_bus_output(&self) -> Current {
+            self.elec.dc_ess_bus.output()
+        }
+
         fn battery_1_input(&self) -> Current {
             self.elec.battery_1.get_input()
         }
@@ -900,46 +3167,82 @@ mod a320_electrical_circuit_tests {
             self.elec.battery_2.get_input()
         }
 
+        fn active_alerts_in(&self, flight_phase: FlightPhase) -> FlightWarningComputer {
+            let mut fwc = FlightWarningComputer::new();
+            fwc.update(flight_phase, self.elec.triggered_alerts(&self.overhead));
+
+            fwc
+        }
+
+        fn consumer_is_powered(&self, id: &str) -> bool {
+            self.elec.consumer_is_powered(id)
+        }
+
+        /// The summed demand of the consumers on the given powered DC buses, including their unmodelled load.
+        fn demand_on(&self, buses: &[A320Bus]) -> ElectricCurrent {
+            let unmodelled_load = ElectricCurrent::new::<ampere>(buses.len() as f32 * A320ElectricalCircuit::UNMODELLED_DC_BUS_LOAD_IN_AMPERES);
+
+            self.elec.protected_consumers.iter().filter(|protected| buses.contains(&protected.bus))
+                .fold(unmodelled_load, |demand, protected| demand + protected.consumer.demand())
+        }
+
+        fn ac_ess_feed_contactor_2_open(&self) -> bool {
+            self.elec.ac_ess_feed_contactor_2.is_open()
+        }
+
         fn both_ac_ess_feed_contactors_open(&self) -> bool {
             self.elec.ac_ess_feed_contactor_1.is_open() && self.elec.ac_ess_feed_contactor_2.is_open()
         }
 
-        fn run(mut self) -> ElectricalCircuitTester {
-            let context = UpdateContext::new(Duration::from_millis(1));
-            self.elec.update(&context, &self.engine1, &self.engine2, &self.apu, &self.ext_pwr, &self.hyd, &self.overhead);
-
-            self
+        /// Unless a contactor failed, the contactor logic must never parallel AC sources.
+        fn assert_no_paralleled_sources(&self) {
+            if self.elec.contactors().iter().all(|contactor| contactor.failure().is_none()) {
+                let paralleled = self.elec.paralleled_sources();
+                assert!(paralleled.is_empty(), "{}", paralleled.iter().map(|paralleling| paralleling.to_string()).collect::<Vec<String>>().join(", "));
+            }
         }
 
-        fn run_waiting_for(mut self, delta: Duration) -> ElectricalCircuitTester {
-            // Firstly run without any time passing at all, such that if the DelayedTrueLogicGate reaches
-            // the true state after waiting for the given time it will be reflected in its output.
-            let context = UpdateContext::new(Duration::from_secs(0));
-            self.elec.update(&context, &self.engine1, &self.engine2, &self.apu, &self.ext_pwr, &self.hyd, &self.overhead);
+        /// Runs the circuit long enough for all contactor transitions to complete.
+        fn run(self) -> ElectricalCircuitTester {
+            self.run_for(ElectricalCircuitTester::CONTACTOR_TRANSITIONS_COMPLETED_DURATION)
+        }
 
-            let context = UpdateContext::new(delta);
-            self.elec.update(&context, &self.engine1, &self.engine2, &self.apu, &self.ext_pwr, &self.hyd, &self.overhead);
+        fn run_for(mut self, duration: Duration) -> ElectricalCircuitTester {
+            let context = UpdateContext::new(ElectricalCircuitTester::STEP);
+            let mut elapsed = Duration::from_millis(0);
+            while elapsed < duration {
+                self.failures.update(&context);
+                self.elec.update(&context, &self.engine1, &self.engine2, &self.apu, &self.ext_pwr, &self.hyd, &self.overhead, &self.failures);
+                self.assert_no_paralleled_sources();
+                elapsed += ElectricalCircuitTester::STEP;
+            }
 
             self
         }
 
         fn run_waiting_for_ac_ess_feed_transition(self) -> ElectricalCircuitTester {
-            self.run_waiting_for(A320ElectricalCircuit::AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS)
+            self.run_for(A320ElectricalCircuit::AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS).run()
+        }
+
+        /// Runs the circuit long enough for the TR monitors to confirm an undercurrent
+        /// and for the resulting contactor transitions to complete.
+        fn run_waiting_for_tr_undercurrent_confirmation(self) -> ElectricalCircuitTester {
+            self.run_for(TransformerRectifierMonitor::UNDERCURRENT_CONFIRMATION_TIME).run()
         }
 
         fn run_waiting_until_just_before_ac_ess_feed_transition(self) -> ElectricalCircuitTester {
-            self.run_waiting_for(A320ElectricalCircuit::AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS - Duration::from_millis(1))
+            self.run_for(A320ElectricalCircuit::AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS - ElectricalCircuitTester::STEP)
         }
 
-        fn new_running_engine() -> Engine {
-            let mut engine = Engine::new();
-            engine.n2 = Ratio::new::<percent>(EngineGenerator::ENGINE_N2_POWER_OUTPUT_THRESHOLD + 1.);
+        fn new_running_engine(number: u8) -> Engine {
+            let mut engine = Engine::new(number);
+            engine.n2 = Ratio::new::<percent>(EngineGenerator::ENGINE_N2_NOMINAL_POWER_OUTPUT_THRESHOLD + 1.);
     
             engine
         }
 
-        fn new_stopped_engine() -> Engine {
-            let mut engine = Engine::new();
+        fn new_stopped_engine(number: u8) -> Engine {
+            let mut engine = Engine::new(number);
             engine.n2 = Ratio::new::<percent>(0.);
     
             engine
@@ -960,9 +3263,7 @@ mod a320_electrical_circuit_tests {
         }
     
         fn new_disconnected_external_power() -> ExternalPowerSource {
-            let ext_pwr = ExternalPowerSource::new();
-            
-            ext_pwr
+            ExternalPowerSource::new()
         }
     
         fn new_connected_external_power() -> ExternalPowerSource {
@@ -972,4 +3273,222 @@ mod a320_electrical_circuit_tests {
             ext_pwr
         }
     }
-}
\ No newline at end of file
+}
+
+/// Drives the A320 through random sequences of engine, APU and external power inputs, push button presses,
+/// failures and time steps, checking the electrical invariants after every tick. A violating sequence is
+/// shrunk to the actions needed to reproduce it. Set PROPERTY_CASES to run more cases, or PROPERTY_SEED
+/// to run the case with the given seed only.
+#[cfg(test)]
+mod a320_electrical_property_tests {
+    use std::{env, time::Duration};
+
+    use crate::{shared::{Random, Simulation}, simulator::{InMemorySimulatorReaderWriter, SimulatorReaderWriter}};
+
+    use super::*;
+
+    const CASES: u64 = 16;
+    const ACTIONS_PER_CASE: usize = 20;
+    const TICK: Duration = Duration::from_millis(5);
+    const MAX_WAIT_IN_MILLISECONDS: f64 = 6000.;
+    /// The AC ESS FEED transfer delay with some margin for the contactor transitions.
+    const AC_ESS_BUS_SETTLING_TIME: Duration = Duration::from_secs(4);
+    const INPUTS: [&str; 4] = ["TURB ENG CORRECTED N2:1", "TURB ENG CORRECTED N2:2", "APU PCT RPM", "EXTERNAL POWER AVAILABLE:1"];
+    const PUSH_BUTTONS: [&str; 12] = ["BAT_1", "BAT_2", "IDG_1", "IDG_2", "GEN_1", "GEN_2", "APU_GEN", "BUS_TIE", "GALY_AND_CAB", "EXT_PWR",
+        "COMMERCIAL", "AC_ESS_FEED"];
+    /// Contactor failures are left out, as a welded contactor legitimately parallels sources.
+    const FAILURES: [&str; 11] = ["GEN 1", "GEN 2", "APU GEN", "TR 1", "TR 2", "AC BUS 1", "AC BUS 2", "DC BUS 1", "DC BUS 2",
+        "IDG 1 OIL LEAK", "IDG 2 OIL LEAK"];
+
+    #[derive(Clone, Debug)]
+    enum Action {
+        Set(&'static str, f64),
+        Press(&'static str, &'static str),
+        Fail(&'static str),
+        Repair(&'static str),
+        Wait(Duration)
+    }
+
+    /// Tracks how long the AC ESS FEED selection and the availability of each AC source have been unchanged,
+    /// as a change of available sources transfers the AC buses between them.
+    #[derive(PartialEq)]
+    struct AcEssBusSupply {
+        feed_is_normal: bool,
+        available_ac_sources: [bool; 4]
+    }
+
+    #[test]
+    fn electrical_invariants_hold_for_random_sequences() {
+        let seeds: Vec<u64> = match (env::var("PROPERTY_SEED"), env::var("PROPERTY_CASES")) {
+            (Ok(seed), _) => vec![seed.parse().expect("PROPERTY_SEED must be a number.")],
+            (_, Ok(cases)) => (1..=cases.parse().expect("PROPERTY_CASES must be a number.")).collect(),
+            _ => (1..=CASES).collect()
+        };
+
+        for seed in seeds {
+            let actions = generate(seed);
+            if run(&actions).is_err() {
+                let shrunk = shrink(actions);
+                panic!("Seed {}: {}\nShrunk sequence: {:#?}", seed, run(&shrunk).unwrap_err(), shrunk);
+            }
+        }
+    }
+
+    #[test]
+    fn generation_is_deterministic() {
+        assert_eq!(format!("{:?}", generate(7)), format!("{:?}", generate(7)));
+        assert_ne!(format!("{:?}", generate(7)), format!("{:?}", generate(8)));
+    }
+
+    #[test]
+    fn paralleled_sources_violate_the_invariants() {
+        let actions = vec![
+            Action::Set("TURB ENG CORRECTED N2:1", 80.),
+            Action::Set("APU PCT RPM", 100.),
+            Action::Fail("11XU1 WELDED"),
+            Action::Wait(Duration::from_secs(1))
+        ];
+
+        assert!(matches!(run(&actions), Err(violation) if violation.contains("GEN 1 and APU GEN are paralleled on AC BUS 1")));
+    }
+
+    #[test]
+    fn failed_ac_bus_feeding_the_ac_ess_bus_does_not_violate_the_invariants() {
+        let actions = vec![
+            Action::Set("TURB ENG CORRECTED N2:1", 80.),
+            Action::Press("AC_ESS_FEED", "altn"),
+            Action::Fail("AC BUS 2"),
+            Action::Wait(Duration::from_secs(6))
+        ];
+
+        assert_eq!(run(&actions), Ok(()));
+    }
+
+    #[test]
+    fn shrinking_keeps_only_the_actions_needed_for_the_violation() {
+        let actions = vec![
+            Action::Set("TURB ENG CORRECTED N2:1", 80.),
+            Action::Press("COMMERCIAL", "off"),
+            Action::Set("APU PCT RPM", 100.),
+            Action::Wait(Duration::from_millis(200)),
+            Action::Fail("11XU1 WELDED"),
+            Action::Fail("TR 2"),
+            Action::Wait(Duration::from_secs(1))
+        ];
+
+        assert_eq!(shrink(actions).len(), 4);
+    }
+
+    fn generate(seed: u64) -> Vec<Action> {
+        let mut random = Random::new(seed);
+        (0..ACTIONS_PER_CASE).map(|_| match pick(&mut random, 6) {
+            0 => {
+                let input = INPUTS[pick(&mut random, INPUTS.len())];
+                let value = if input.starts_with("EXTERNAL") { pick(&mut random, 2) as f64 } else { (random.next_f64() * 100.).round() };
+                Action::Set(input, value)
+            },
+            1 => {
+                let push_button = PUSH_BUTTONS[pick(&mut random, PUSH_BUTTONS.len())];
+                let positions = if push_button == "AC_ESS_FEED" { ["altn", "normal"] } else { ["off", "on"] };
+                Action::Press(push_button, positions[pick(&mut random, 2)])
+            },
+            2 => Action::Fail(FAILURES[pick(&mut random, FAILURES.len())]),
+            3 => Action::Repair(FAILURES[pick(&mut random, FAILURES.len())]),
+            _ => Action::Wait(TICK * (random.next_f64() * MAX_WAIT_IN_MILLISECONDS / TICK.as_millis() as f64) as u32)
+        }).collect()
+    }
+
+    fn pick(random: &mut Random, count: usize) -> usize {
+        (random.next_f64() * count as f64) as usize
+    }
+
+    /// Runs the actions, returning the first invariant violation.
+    fn run(actions: &[Action]) -> Result<(), String> {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        let mut simulation = Simulation::with_max_step(A320::new(), TICK);
+        let mut supply = (AcEssBusSupply { feed_is_normal: true, available_ac_sources: [false; 4] }, Duration::from_secs(0));
+
+        for (index, action) in actions.iter().enumerate() {
+            match action {
+                Action::Set(input, value) => reader_writer.write(input, *value),
+                Action::Press(push_button, position) => {
+                    let (input, is_on) = A320ElectricalOverheadPanel::push_button_input(push_button, position)?;
+                    reader_writer.write(&input, if is_on { 1. } else { 0. });
+                },
+                Action::Fail(id) => simulation.aircraft_mut().failures_mut().activate(id),
+                Action::Repair(id) => simulation.aircraft_mut().failures_mut().deactivate(id),
+                Action::Wait(duration) => {
+                    let until = simulation.simulation_time() + *duration;
+                    while simulation.simulation_time() < until {
+                        simulation.tick(TICK, &mut reader_writer);
+                        check_invariants(&simulation, &mut supply)
+                            .map_err(|violation| format!("After action {} at {:?}: {}", index, simulation.simulation_time(), violation))?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_invariants(simulation: &Simulation<A320>, supply: &mut (AcEssBusSupply, Duration)) -> Result<(), String> {
+        let a320 = simulation.aircraft();
+        let elec = a320.electrical();
+
+        if let Some(paralleling) = elec.paralleled_sources().first() {
+            return Err(paralleling.to_string());
+        }
+
+        if elec.engine_1_gen_contactor.is_closed() && elec.engine_2_gen_contactor.is_closed()
+            && (elec.bus_tie_1_contactor.is_closed() || elec.bus_tie_2_contactor.is_closed()) {
+            return Err(String::from("A bus tie contactor is closed while both engine generators are on."));
+        }
+
+        let overhead = a320.electrical_overhead();
+        let current_supply = AcEssBusSupply {
+            feed_is_normal: overhead.ac_ess_feed.is_normal(),
+            available_ac_sources: [
+                generator_is_available(a320, "GEN 1", &elec.engine_1_gen, &elec.engine_1_gcu, &overhead.gen_1),
+                generator_is_available(a320, "GEN 2", &elec.engine_2_gen, &elec.engine_2_gcu, &overhead.gen_2),
+                generator_is_available(a320, "APU GEN", &elec.apu_gen, &elec.apu_gcu, &overhead.apu_gen),
+                a320.ext_pwr.output().is_powered() && overhead.ext_pwr.is_on()
+            ]
+        };
+        if current_supply != supply.0 {
+            *supply = (current_supply, simulation.simulation_time());
+        }
+
+        // A failed AC bus legitimately leaves the AC ESS BUS unpowered when it is the bus feeding it.
+        let ac_bus_failed = a320.failures().is_active("AC BUS 1") || a320.failures().is_active("AC BUS 2");
+        let (current_supply, since) = &*supply;
+        if current_supply.available_ac_sources.contains(&true) && !ac_bus_failed && simulation.simulation_time() - *since >= AC_ESS_BUS_SETTLING_TIME
+            && elec.ac_ess_bus.output().is_unpowered() {
+            return Err(String::from("The AC ESS BUS is unpowered while an AC source is available."));
+        }
+
+        Ok(())
+    }
+
+    /// A generator is faulted when its voltage regulator failed or its GCU latched a fault. A generator
+    /// switched off on the overhead panel is not available to the distribution network either.
+    fn generator_is_available(a320: &A320, id: &str, generator: &dyn PowerConductor, gcu: &GeneratorControlUnit,
+        gen_push_button: &OnOffPushButton) -> bool {
+        generator.output().is_powered() && !a320.failures().is_active(id) && !gcu.has_fault() && gen_push_button.is_on()
+    }
+
+    /// Removes actions one at a time, keeping each removal which still violates an invariant.
+    fn shrink(mut actions: Vec<Action>) -> Vec<Action> {
+        let mut index = 0;
+        while index < actions.len() {
+            let mut candidate = actions.clone();
+            candidate.remove(index);
+            if run(&candidate).is_err() {
+                actions = candidate;
+            } else {
+                index += 1;
+            }
+        }
+
+        actions
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{a320::{A320, A320Bus, A320ElectricalCircuit, A320ProtectedConsumer}, ecam::FlightPhase, electrical::{Current, Paralleling}, scenario::ScenarioAircraft, shared::{Aircraft, Failures, UpdateContext}, simulator::{SimulatorElement, SimulatorElementVisitor}, snapshot::{StateVisitor, Stateful}, timeseries::Sample};
+
+/// The A321 shares the A320's systems. Its longer cabin has an additional galley and
+/// larger galleys overall, which places a higher load on the electrical circuit.
+pub struct A321 {
+    systems: A320
+}
+
+impl A321 {
+    pub fn new() -> A321 {
+        A321 {
+            systems: A320::with_electrical_circuit(A320ElectricalCircuit::with_consumers(A321::consumers()))
+        }
+    }
+
+    fn consumers() -> Vec<A320ProtectedConsumer> {
+        let mut consumers: Vec<A320ProtectedConsumer> = A320ProtectedConsumer::a320_consumers().into_iter()
+            .filter(|protected| !protected.is_galley()).collect();
+        consumers.extend(vec!(
+            A320ProtectedConsumer::galley(A320Bus::AcBus1, "2000VU", "G01", 80., "FWD GALLEY", 45.),
+            A320ProtectedConsumer::galley(A320Bus::AcBus2, "2000VU", "G02", 50., "MID GALLEY", 25.),
+            A320ProtectedConsumer::galley(A320Bus::AcBus2, "2000VU", "G03", 80., "AFT GALLEY", 45.)
+        ));
+
+        consumers
+    }
+
+    pub fn systems(&self) -> &A320 {
+        &self.systems
+    }
+
+    pub fn systems_mut(&mut self) -> &mut A320 {
+        &mut self.systems
+    }
+}
+
+impl Aircraft for A321 {
+    fn tick(&mut self, context: &UpdateContext) {
+        self.systems.tick(context);
+    }
+
+    fn failures_mut(&mut self) -> &mut Failures {
+        self.systems.failures_mut()
+    }
+}
+
+impl SimulatorElement for A321 {
+    fn accept<T: SimulatorElementVisitor>(&mut self, visitor: &mut T) {
+        self.systems.accept(visitor);
+    }
+}
+
+impl ScenarioAircraft for A321 {
+    fn bus_output(&self, id: &str) -> Option<Current> {
+        self.systems.bus_output(id)
+    }
+
+    fn alert_is_active(&self, title: &str) -> bool {
+        self.systems.alert_is_active(title)
+    }
+
+    fn set_flight_phase(&mut self, flight_phase: FlightPhase) {
+        self.systems.set_flight_phase(flight_phase);
+    }
+
+    fn sample(&self) -> Sample {
+        self.systems.sample()
+    }
+
+    fn paralleled_sources(&self) -> Vec<Paralleling> {
+        self.systems.paralleled_sources()
+    }
+
+    fn failure_ids(&self) -> Vec<String> {
+        self.systems.failure_ids()
+    }
+
+    fn push_button_input(&self, push_button: &str, position: &str) -> Result<(String, bool), String> {
+        self.systems.push_button_input(push_button, position)
+    }
+}
+
+impl Stateful for A321 {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        self.systems.visit_state(state);
+    }
+}
+
+#[cfg(test)]
+mod a321_tests {
+    use std::time::Duration;
+    use uom::si::{f32::Ratio, ratio::percent};
+
+    use crate::{shared::Simulation, simulator::{InMemorySimulatorReaderWriter, SimulatorReaderWriter}, snapshot::Snapshot};
+
+    use super::*;
+
+    #[test]
+    fn a321_galleys_load_generators_more_than_a320_galleys() {
+        let a320 = run_with_running_engines(A320::new());
+        let a321 = run_with_running_engines(A321::new());
+
+        assert!(a321.systems().electrical().electrical_page().gen_1.load.value() > a320.electrical().electrical_page().gen_1.load.value());
+    }
+
+    #[test]
+    fn a321_has_a_mid_galley() {
+        let a321 = run_with_running_engines(A321::new());
+
+        assert!(a321.systems().electrical().consumer_is_powered("MID GALLEY"));
+        assert!(!run_with_running_engines(A320::new()).electrical().consumer_is_powered("MID GALLEY"));
+    }
+
+    #[test]
+    fn galy_and_cab_off_sheds_a321_galleys() {
+        let mut a321 = A321::new();
+        a321.systems_mut().electrical_overhead_mut().galy_and_cab.push_off();
+        let a321 = run_with_running_engines(a321);
+
+        assert!(!a321.systems().electrical().consumer_is_powered("MID GALLEY"));
+        assert!(a321.systems().electrical().consumer_is_powered("FUEL PUMP 1"));
+    }
+
+    #[test]
+    fn simulation_drives_a321_from_simulator_variables() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        reader_writer.write("TURB ENG CORRECTED N2:1", 80.);
+        reader_writer.write("TURB ENG CORRECTED N2:2", 80.);
+
+        let mut simulation = Simulation::new(A321::new());
+        for _ in 0..500 {
+            simulation.tick(Duration::from_millis(1), &mut reader_writer);
+        }
+
+        assert_eq!(simulation.simulation_time(), Duration::from_millis(500));
+        assert_eq!(reader_writer.read("ELEC_AC_BUS_1_IS_POWERED"), Some(1.));
+        assert!(simulation.aircraft().systems().electrical().consumer_is_powered("AFT GALLEY"));
+    }
+
+    #[test]
+    fn simulation_snapshot_restores_simulation_time() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        let mut simulation = Simulation::new(A321::new());
+        simulation.tick(Duration::from_millis(250), &mut reader_writer);
+
+        let mut restored = Simulation::new(A321::new());
+        assert_eq!(Snapshot::save(&mut simulation).restore(&mut restored), Ok(()));
+
+        assert_eq!(restored.simulation_time(), Duration::from_millis(250));
+    }
+
+    fn run_with_running_engines<T: Aircraft + EngineAccess>(mut aircraft: T) -> T {
+        aircraft.set_running_engines();
+        for _ in 0..500 {
+            aircraft.tick(&UpdateContext::new(Duration::from_millis(1)));
+        }
+
+        aircraft
+    }
+
+    trait EngineAccess {
+        fn set_running_engines(&mut self);
+    }
+
+    impl EngineAccess for A320 {
+        fn set_running_engines(&mut self) {
+            self.engine_mut(1).unwrap().n2 = Ratio::new::<percent>(80.);
+            self.engine_mut(2).unwrap().n2 = Ratio::new::<percent>(80.);
+        }
+    }
+
+    impl EngineAccess for A321 {
+        fn set_running_engines(&mut self) {
+            self.systems.set_running_engines();
+        }
+    }
+}
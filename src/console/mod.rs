@@ -0,0 +1,271 @@
+use std::time::Duration;
+
+use crate::{ecam::FlightPhase, scenario::{Scenario, ScenarioAircraft}, shared::Simulation, simulator::{InMemorySimulatorReaderWriter, SimulatorReaderWriter}, timeseries::SampleValue};
+
+pub const HELP: &str = "Commands:
+  press <PUSH BUTTON> on|off|normal|altn   e.g. press gen_1 off, press ac_ess_feed altn
+  set <INPUT> <VALUE>                      e.g. set engine1.n2 80, set apu.rpm 100, set ext_pwr.available 1
+  fail <FAILURE>                           e.g. fail tr_1
+  repair <FAILURE>
+  phase <1-10>
+  advance <DURATION>                       e.g. advance 500ms, advance 5s
+  show [buses|contactors]
+  help
+  quit";
+
+/// Inputs which can be set by a short name instead of their simulator variable name.
+const INPUT_ALIASES: [(&str, &str); 4] = [
+    ("ENGINE1.N2", "TURB ENG CORRECTED N2:1"),
+    ("ENGINE2.N2", "TURB ENG CORRECTED N2:2"),
+    ("APU.RPM", "APU PCT RPM"),
+    ("EXT_PWR.AVAILABLE", "EXTERNAL POWER AVAILABLE:1")
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Synoptic {
+    Buses,
+    Contactors,
+    All
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Press { push_button: String, position: String },
+    Set { input: String, value: f64 },
+    Fail(String),
+    Repair(String),
+    Phase(FlightPhase),
+    Advance(Duration),
+    Show(Synoptic),
+    Help,
+    Quit
+}
+
+impl Command {
+    /// Parses a console command. Names are case insensitive and may use underscores instead of spaces.
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let line = line.trim();
+        let (keyword, arguments) = line.split_once(char::is_whitespace).map(|(keyword, arguments)| (keyword, arguments.trim())).unwrap_or((line, ""));
+        match keyword.to_lowercase().as_str() {
+            "press" => {
+                let (push_button, position) = arguments.rsplit_once(char::is_whitespace).ok_or("Expected: press <PUSH BUTTON> on|off|normal|altn")?;
+                Ok(Command::Press { push_button: push_button.trim().to_string(), position: position.to_string() })
+            },
+            "set" => {
+                let (name, value) = arguments.rsplit_once(char::is_whitespace).ok_or("Expected: set <INPUT> <VALUE>")?;
+                let value = value.parse::<f64>().map_err(|_| format!("'{}' is not a number.", value))?;
+                let name = name.trim();
+                let input = INPUT_ALIASES.iter().find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+                    .map_or_else(|| name.to_string(), |(_, input)| input.to_string());
+                Ok(Command::Set { input, value })
+            },
+            "fail" if !arguments.is_empty() => Ok(Command::Fail(Command::failure_id(arguments))),
+            "repair" if !arguments.is_empty() => Ok(Command::Repair(Command::failure_id(arguments))),
+            "phase" => Scenario::parse_flight_phase(arguments).map(Command::Phase),
+            "advance" => Scenario::parse_duration(arguments).map(Command::Advance),
+            "show" => match arguments.to_lowercase().as_str() {
+                "" => Ok(Command::Show(Synoptic::All)),
+                "buses" => Ok(Command::Show(Synoptic::Buses)),
+                "contactors" => Ok(Command::Show(Synoptic::Contactors)),
+                _ => Err(format!("Cannot show '{}', use buses or contactors.", arguments))
+            },
+            "help" => Ok(Command::Help),
+            "quit" | "exit" => Ok(Command::Quit),
+            _ => Err(format!("Unknown command '{}'. Type help for a list of commands.", line))
+        }
+    }
+
+    fn failure_id(text: &str) -> String {
+        text.to_uppercase().replace('_', " ")
+    }
+}
+
+/// Drives a simulation interactively, one command at a time.
+pub struct Console<T: ScenarioAircraft> {
+    simulation: Simulation<T>,
+    reader_writer: InMemorySimulatorReaderWriter
+}
+
+impl<T: ScenarioAircraft> Console<T> {
+    pub fn new(aircraft: T) -> Console<T> {
+        Console {
+            simulation: Simulation::with_max_step(aircraft, Scenario::TICK),
+            reader_writer: InMemorySimulatorReaderWriter::new()
+        }
+    }
+
+    pub fn simulation(&self) -> &Simulation<T> {
+        &self.simulation
+    }
+
+    /// Executes the command. Inputs, failures and the flight phase take effect on the next advance.
+    /// Help and quit are left to the caller. Returns an error for push buttons, inputs and failures
+    /// the aircraft does not have.
+    pub fn execute(&mut self, command: &Command) -> Result<(), String> {
+        match command {
+            Command::Press { push_button, position } => {
+                let (input, is_on) = self.simulation.aircraft().push_button_input(push_button, position)?;
+                self.simulation.aircraft_mut().check_input(&input)?;
+                self.reader_writer.write(&input, if is_on { 1. } else { 0. });
+            },
+            Command::Set { input, value } => {
+                self.simulation.aircraft_mut().check_input(input)?;
+                self.reader_writer.write(input, *value);
+            },
+            Command::Fail(id) => {
+                self.simulation.aircraft().check_failure_id(id)?;
+                self.simulation.aircraft_mut().failures_mut().activate(id);
+            },
+            Command::Repair(id) => {
+                self.simulation.aircraft().check_failure_id(id)?;
+                self.simulation.aircraft_mut().failures_mut().deactivate(id);
+            },
+            Command::Phase(flight_phase) => self.simulation.aircraft_mut().set_flight_phase(*flight_phase),
+            Command::Advance(duration) => {
+                let until = self.simulation.simulation_time() + *duration;
+                while self.simulation.simulation_time() < until {
+                    self.simulation.tick(Scenario::TICK.min(until - self.simulation.simulation_time()), &mut self.reader_writer);
+                }
+            },
+            Command::Show(_) | Command::Help | Command::Quit => {}
+        }
+
+        Ok(())
+    }
+
+    /// A textual synoptic of the source powering each bus and the state of each contactor,
+    /// followed by a warning for each bus on which AC sources are paralleled.
+    pub fn synoptic(&self, synoptic: Synoptic) -> String {
+        let sample = self.simulation.aircraft().sample();
+        let mut text = format!("T+{:.3}s\n", self.simulation.simulation_time().as_secs_f64());
+
+        if synoptic != Synoptic::Contactors {
+            for (name, value) in sample.iter() {
+                if let (Some(bus), SampleValue::Text(source)) = (name.strip_suffix(" SOURCE"), value) {
+                    text.push_str(&format!("  {:<12}{}\n", bus, if source == "NONE" { "---" } else { source }));
+                }
+            }
+        }
+
+        if synoptic != Synoptic::Buses {
+            let contactors: Vec<String> = sample.iter().filter_map(|(name, value)| match (name.strip_suffix(" CLOSED"), value) {
+                (Some(contactor), SampleValue::Bool(is_closed)) => Some(format!("{:<6}{:<8}", contactor, if *is_closed { "CLOSED" } else { "open" })),
+                _ => None
+            }).collect();
+            for row in contactors.chunks(6) {
+                text.push_str(&format!("  {}\n", row.concat().trim_end()));
+            }
+        }
+
+        for paralleling in self.simulation.aircraft().paralleled_sources() {
+            text.push_str(&format!("  WARNING: {}\n", paralleling));
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod console_tests {
+    use crate::a320::A320;
+
+    use super::*;
+
+    fn execute(console: &mut Console<A320>, line: &str) {
+        console.execute(&Command::parse(line).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn parses_push_buttons() {
+        assert_eq!(Command::parse("press gen_1 off"), Ok(Command::Press { push_button: String::from("gen_1"), position: String::from("off") }));
+        assert_eq!(Command::parse("PRESS AC ESS FEED altn"), Ok(Command::Press { push_button: String::from("AC ESS FEED"), position: String::from("altn") }));
+    }
+
+    #[test]
+    fn parses_input_aliases_and_simulator_variables() {
+        assert_eq!(Command::parse("set engine1.n2 80"), Ok(Command::Set { input: String::from("TURB ENG CORRECTED N2:1"), value: 80. }));
+        assert_eq!(Command::parse("set APU PCT RPM 100"), Ok(Command::Set { input: String::from("APU PCT RPM"), value: 100. }));
+    }
+
+    #[test]
+    fn parses_failures_advance_and_show() {
+        assert_eq!(Command::parse("fail tr_1"), Ok(Command::Fail(String::from("TR 1"))));
+        assert_eq!(Command::parse("advance 5s"), Ok(Command::Advance(Duration::from_secs(5))));
+        assert_eq!(Command::parse("show buses"), Ok(Command::Show(Synoptic::Buses)));
+    }
+
+    #[test]
+    fn invalid_commands_are_errors() {
+        assert!(Command::parse("jump").is_err());
+        assert!(Command::parse("set engine1.n2 high").is_err());
+        assert!(Command::parse("phase 11").is_err());
+        assert!(Command::parse("advance 1e30s").is_err());
+    }
+
+    #[test]
+    fn unknown_push_button_or_position_is_an_error() {
+        let mut console = Console::new(A320::new());
+
+        assert_eq!(console.execute(&Command::parse("press gen1 off").unwrap()), Err(String::from("Unknown push button 'gen1'.")));
+        assert!(console.execute(&Command::parse("press gen_1 sideways").unwrap()).is_err());
+    }
+
+    #[test]
+    fn unknown_input_is_an_error() {
+        let mut console = Console::new(A320::new());
+
+        assert_eq!(console.execute(&Command::parse("set engine1.n 80").unwrap()), Err(String::from("There is no input 'engine1.n'.")));
+    }
+
+    #[test]
+    fn pressing_push_buttons_with_underscores_and_any_case_sets_their_input() {
+        let mut console = Console::new(A320::new());
+        execute(&mut console, "PRESS AC_ESS_FEED altn");
+        execute(&mut console, "advance 1ms");
+
+        assert!(console.simulation().aircraft().electrical_overhead().ac_ess_feed.is_altn());
+    }
+
+    #[test]
+    fn unknown_failure_is_an_error() {
+        let mut console = Console::new(A320::new());
+
+        assert_eq!(console.execute(&Command::parse("fail tr1").unwrap()), Err(String::from("There is no failure 'TR1'.")));
+    }
+
+    #[test]
+    fn commands_drive_the_simulation() {
+        let mut console = Console::new(A320::new());
+        execute(&mut console, "set engine1.n2 80");
+        execute(&mut console, "set engine2.n2 80");
+        execute(&mut console, "press gen_2 off");
+        execute(&mut console, "advance 500ms");
+
+        assert_eq!(console.simulation().simulation_time(), Duration::from_millis(500));
+        assert!(console.synoptic(Synoptic::Buses).contains("AC BUS 2    GEN 1\n"));
+    }
+
+    #[test]
+    fn synoptic_shows_buses_and_contactors() {
+        let mut console = Console::new(A320::new());
+        execute(&mut console, "advance 100ms");
+
+        let synoptic = console.synoptic(Synoptic::All);
+        assert!(synoptic.starts_with("T+0.100s\n"));
+        assert!(synoptic.contains("AC BUS 1    ---\n"));
+        assert!(synoptic.contains("9XU1  open"));
+        assert!(!console.synoptic(Synoptic::Buses).contains("9XU1"));
+        assert!(!console.synoptic(Synoptic::Contactors).contains("AC BUS 1"));
+    }
+
+    #[test]
+    fn synoptic_warns_of_paralleled_sources() {
+        let mut console = Console::new(A320::new());
+        execute(&mut console, "set engine1.n2 80");
+        execute(&mut console, "set apu.rpm 100");
+        execute(&mut console, "fail 11xu1_welded");
+        execute(&mut console, "advance 500ms");
+
+        assert!(console.synoptic(Synoptic::All).contains("  WARNING: GEN 1 and APU GEN are paralleled on AC BUS 1\n"));
+    }
+}
@@ -0,0 +1,426 @@
+use crate::snapshot::{StateVisitor, visit_collection};
+
+/// The flight phases as determined by the flight warning computer (FWC).
+/// Alerts which are not essential during a flight phase are inhibited in that phase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FlightPhase {
+    ElectricalPower,
+    FirstEngineStarted,
+    FirstEngineTakeoffPower,
+    EightyKnots,
+    LiftOff,
+    AboveFifteenHundredFeet,
+    BelowEightHundredFeet,
+    Touchdown,
+    BelowEightyKnots,
+    SecondEngineShutdown
+}
+
+impl FlightPhase {
+    /// The number of the flight phase as shown in the FCOM, ranging from 1 to 10.
+    pub fn number(&self) -> u8 {
+        *self as u8 + 1
+    }
+}
+
+/// The alert levels in increasing order of priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlertLevel {
+    /// An amber caution requiring crew monitoring, without attention getters.
+    Level1,
+    /// An amber caution requiring crew awareness but not immediate action.
+    Level2,
+    /// A red warning requiring immediate crew action.
+    Level3
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Alert {
+    title: &'static str,
+    level: AlertLevel,
+    inhibited_in: &'static [FlightPhase],
+    procedure: &'static [&'static str]
+}
+
+impl Alert {
+    pub const fn new(title: &'static str, level: AlertLevel, inhibited_in: &'static [FlightPhase], procedure: &'static [&'static str]) -> Alert {
+        Alert {
+            title,
+            level,
+            inhibited_in,
+            procedure
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        self.title
+    }
+
+    pub fn level(&self) -> AlertLevel {
+        self.level
+    }
+
+    pub fn is_inhibited_in(&self, flight_phase: FlightPhase) -> bool {
+        self.inhibited_in.contains(&flight_phase)
+    }
+
+    /// The procedure lines displayed on the upper ECAM below the alert's title.
+    pub fn procedure(&self) -> &[&str] {
+        self.procedure
+    }
+}
+
+/// The flight warning computer (FWC) turns the alerts triggered by the systems into the list of
+/// alerts displayed on the upper ECAM. Alerts inhibited in the current flight phase are not displayed
+/// until the aircraft leaves that flight phase. Alerts are listed in decreasing order of priority.
+pub struct FlightWarningComputer {
+    active_alerts: Vec<Alert>
+}
+
+impl FlightWarningComputer {
+    pub fn new() -> FlightWarningComputer {
+        FlightWarningComputer {
+            active_alerts: vec![]
+        }
+    }
+
+    pub fn update(&mut self, flight_phase: FlightPhase, triggered_alerts: Vec<Alert>) {
+        let mut active_alerts: Vec<Alert> = triggered_alerts.into_iter().filter(|alert| !alert.is_inhibited_in(flight_phase)).collect();
+        active_alerts.sort_by_key(|alert| std::cmp::Reverse(alert.level));
+
+        self.active_alerts = active_alerts;
+    }
+
+    pub fn active_alerts(&self) -> &[Alert] {
+        &self.active_alerts
+    }
+
+    pub fn is_active(&self, title: &str) -> bool {
+        self.active_alerts.iter().any(|alert| alert.title == title)
+    }
+
+    /// The highest level of the active alerts, if any.
+    pub fn highest_level(&self) -> Option<AlertLevel> {
+        self.active_alerts.iter().map(|alert| alert.level).max()
+    }
+
+    /// The lines displayed on the upper ECAM: each active alert's title followed by its procedure.
+    pub fn upper_ecam_lines(&self) -> Vec<String> {
+        self.active_alerts.iter().flat_map(|alert| {
+            std::iter::once(alert.title.to_string()).chain(alert.procedure.iter().map(|line| format!("-{}", line)))
+        }).collect()
+    }
+
+    /// Visits the active alerts by their titles. Restored titles are looked up in the given
+    /// alerts, which must include every alert the aircraft can trigger.
+    pub fn visit_state(&mut self, state: &mut dyn StateVisitor, alerts: &[Alert]) {
+        let mut titles: Vec<&'static str> = self.active_alerts.iter().map(|alert| alert.title).collect();
+        visit_titles(state, "ACTIVE ALERTS", &mut titles, alerts);
+
+        self.active_alerts = titles.iter().filter_map(|title| alerts.iter().find(|alert| alert.title == *title).copied()).collect();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AuralAlert {
+    SingleChime,
+    ContinuousRepetitiveChime
+}
+
+/// The master warning and master caution lights and the chimes which draw the crew's attention
+/// to newly active alerts. A new level 3 alert illuminates the MASTER WARN light and sounds the
+/// continuous repetitive chime until acknowledged. A new level 2 alert illuminates the MASTER CAUT
+/// light until acknowledged and sounds a single chime. Level 1 alerts do not draw attention.
+/// The lights only illuminate while their supply is powered.
+pub struct AttentionGetters {
+    known_alerts: Vec<&'static str>,
+    unacknowledged_warnings: Vec<&'static str>,
+    unacknowledged_cautions: Vec<&'static str>,
+    single_chime: bool,
+    lights_powered: bool
+}
+
+impl AttentionGetters {
+    pub fn new() -> AttentionGetters {
+        AttentionGetters {
+            known_alerts: vec![],
+            unacknowledged_warnings: vec![],
+            unacknowledged_cautions: vec![],
+            single_chime: false,
+            lights_powered: false
+        }
+    }
+
+    pub fn update(&mut self, fwc: &FlightWarningComputer, lights_powered: bool) {
+        self.unacknowledged_warnings.retain(|title| fwc.is_active(title));
+        self.unacknowledged_cautions.retain(|title| fwc.is_active(title));
+        self.single_chime = false;
+
+        let known_alerts = &self.known_alerts;
+        for alert in fwc.active_alerts().iter().filter(|alert| !known_alerts.contains(&alert.title)) {
+            match alert.level {
+                AlertLevel::Level3 => self.unacknowledged_warnings.push(alert.title),
+                AlertLevel::Level2 => {
+                    self.unacknowledged_cautions.push(alert.title);
+                    self.single_chime = true;
+                },
+                AlertLevel::Level1 => {}
+            }
+        }
+
+        self.known_alerts = fwc.active_alerts().iter().map(|alert| alert.title).collect();
+        self.lights_powered = lights_powered;
+    }
+
+    pub fn acknowledge_master_warning(&mut self) {
+        self.unacknowledged_warnings.clear();
+    }
+
+    pub fn acknowledge_master_caution(&mut self) {
+        self.unacknowledged_cautions.clear();
+    }
+
+    pub fn master_warning_is_illuminated(&self) -> bool {
+        self.lights_powered && !self.unacknowledged_warnings.is_empty()
+    }
+
+    pub fn master_caution_is_illuminated(&self) -> bool {
+        self.lights_powered && !self.unacknowledged_cautions.is_empty()
+    }
+
+    /// The aural alert requested during the last update. A single chime is only requested
+    /// in the update in which the new caution became active.
+    pub fn aural_alert(&self) -> Option<AuralAlert> {
+        if !self.unacknowledged_warnings.is_empty() {
+            Some(AuralAlert::ContinuousRepetitiveChime)
+        } else if self.single_chime {
+            Some(AuralAlert::SingleChime)
+        } else {
+            None
+        }
+    }
+
+    /// Visits the alerts by their titles. Restored titles are looked up in the given
+    /// alerts, which must include every alert the aircraft can trigger.
+    pub fn visit_state(&mut self, state: &mut dyn StateVisitor, alerts: &[Alert]) {
+        visit_titles(state, "KNOWN ALERTS", &mut self.known_alerts, alerts);
+        visit_titles(state, "UNACKNOWLEDGED WARNINGS", &mut self.unacknowledged_warnings, alerts);
+        visit_titles(state, "UNACKNOWLEDGED CAUTIONS", &mut self.unacknowledged_cautions, alerts);
+        state.visit_bool("SINGLE CHIME", &mut self.single_chime);
+        state.visit_bool("LIGHTS POWERED", &mut self.lights_powered);
+    }
+}
+
+fn visit_titles(state: &mut dyn StateVisitor, name: &str, titles: &mut Vec<&'static str>, alerts: &[Alert]) {
+    let mut texts: Vec<String> = titles.iter().map(|title| title.to_string()).collect();
+    visit_collection(state, name, &mut texts, String::new);
+    state.enter(name);
+    for (index, text) in texts.iter_mut().enumerate() {
+        state.visit_text(&index.to_string(), text);
+    }
+    state.leave();
+
+    *titles = texts.iter().filter_map(|text| {
+        let title = alerts.iter().find(|alert| alert.title == text).map(|alert| alert.title);
+        if title.is_none() {
+            state.reject(name);
+        }
+
+        title
+    }).collect();
+}
+
+#[cfg(test)]
+mod flight_warning_computer_tests {
+    use super::*;
+
+    const CAUTION: Alert = Alert::new("ELEC GEN 1 FAULT", AlertLevel::Level2, &[FlightPhase::FirstEngineTakeoffPower, FlightPhase::EightyKnots],
+        &["GEN 1.....OFF THEN ON"]);
+    const WARNING: Alert = Alert::new("ELEC EMER CONFIG", AlertLevel::Level3, &[], &["MIN RAT SPEED.....140 KT"]);
+    const ADVISORY: Alert = Alert::new("ELEC TR 1 FAULT", AlertLevel::Level1, &[], &[]);
+
+    #[test]
+    fn flight_phases_are_numbered_from_1_to_10() {
+        assert_eq!(FlightPhase::ElectricalPower.number(), 1);
+        assert_eq!(FlightPhase::SecondEngineShutdown.number(), 10);
+    }
+
+    #[test]
+    fn starts_without_active_alerts() {
+        let fwc = FlightWarningComputer::new();
+
+        assert!(fwc.active_alerts().is_empty());
+        assert_eq!(fwc.highest_level(), None);
+    }
+
+    #[test]
+    fn triggered_alert_is_active() {
+        let mut fwc = FlightWarningComputer::new();
+        fwc.update(FlightPhase::AboveFifteenHundredFeet, vec![CAUTION]);
+
+        assert!(fwc.is_active("ELEC GEN 1 FAULT"));
+    }
+
+    #[test]
+    fn alert_inhibited_in_flight_phase_is_not_active() {
+        let mut fwc = FlightWarningComputer::new();
+        fwc.update(FlightPhase::EightyKnots, vec![CAUTION]);
+
+        assert!(!fwc.is_active("ELEC GEN 1 FAULT"));
+    }
+
+    #[test]
+    fn inhibited_alert_becomes_active_when_leaving_the_flight_phase() {
+        let mut fwc = FlightWarningComputer::new();
+        fwc.update(FlightPhase::EightyKnots, vec![CAUTION]);
+        fwc.update(FlightPhase::LiftOff, vec![CAUTION]);
+
+        assert!(fwc.is_active("ELEC GEN 1 FAULT"));
+    }
+
+    #[test]
+    fn no_longer_triggered_alert_is_not_active() {
+        let mut fwc = FlightWarningComputer::new();
+        fwc.update(FlightPhase::AboveFifteenHundredFeet, vec![CAUTION]);
+        fwc.update(FlightPhase::AboveFifteenHundredFeet, vec![]);
+
+        assert!(!fwc.is_active("ELEC GEN 1 FAULT"));
+    }
+
+    #[test]
+    fn alerts_are_listed_in_decreasing_order_of_priority() {
+        let mut fwc = FlightWarningComputer::new();
+        fwc.update(FlightPhase::AboveFifteenHundredFeet, vec![ADVISORY, CAUTION, WARNING]);
+
+        assert_eq!(fwc.active_alerts(), &[WARNING, CAUTION, ADVISORY]);
+        assert_eq!(fwc.highest_level(), Some(AlertLevel::Level3));
+    }
+
+    #[test]
+    fn upper_ecam_lines_list_titles_followed_by_procedure() {
+        let mut fwc = FlightWarningComputer::new();
+        fwc.update(FlightPhase::AboveFifteenHundredFeet, vec![CAUTION, WARNING]);
+
+        assert_eq!(fwc.upper_ecam_lines(), vec!["ELEC EMER CONFIG", "-MIN RAT SPEED.....140 KT", "ELEC GEN 1 FAULT", "-GEN 1.....OFF THEN ON"]);
+    }
+}
+
+#[cfg(test)]
+mod attention_getters_tests {
+    use super::*;
+
+    const CAUTION: Alert = Alert::new("ELEC GEN 1 FAULT", AlertLevel::Level2, &[], &[]);
+    const WARNING: Alert = Alert::new("ELEC EMER CONFIG", AlertLevel::Level3, &[], &[]);
+    const ADVISORY: Alert = Alert::new("ELEC TR 1 FAULT", AlertLevel::Level1, &[], &[]);
+
+    #[test]
+    fn starts_without_attention_getters() {
+        let attention_getters = AttentionGetters::new();
+
+        assert!(!attention_getters.master_warning_is_illuminated());
+        assert!(!attention_getters.master_caution_is_illuminated());
+        assert_eq!(attention_getters.aural_alert(), None);
+    }
+
+    #[test]
+    fn new_warning_illuminates_master_warning_and_sounds_continuous_repetitive_chime() {
+        let attention_getters = attention_getters_after(&[vec![WARNING]], true);
+
+        assert!(attention_getters.master_warning_is_illuminated());
+        assert_eq!(attention_getters.aural_alert(), Some(AuralAlert::ContinuousRepetitiveChime));
+    }
+
+    #[test]
+    fn continuous_repetitive_chime_continues_until_acknowledged() {
+        let mut attention_getters = attention_getters_after(&[vec![WARNING], vec![WARNING]], true);
+        assert_eq!(attention_getters.aural_alert(), Some(AuralAlert::ContinuousRepetitiveChime));
+
+        attention_getters.acknowledge_master_warning();
+
+        assert!(!attention_getters.master_warning_is_illuminated());
+        assert_eq!(attention_getters.aural_alert(), None);
+    }
+
+    #[test]
+    fn acknowledged_warning_does_not_draw_attention_again_while_active() {
+        let mut attention_getters = attention_getters_after(&[vec![WARNING]], true);
+        attention_getters.acknowledge_master_warning();
+        attention_getters.update(&fwc_with(vec![WARNING]), true);
+
+        assert!(!attention_getters.master_warning_is_illuminated());
+    }
+
+    #[test]
+    fn warning_reoccurring_after_it_cleared_draws_attention_again() {
+        let mut attention_getters = attention_getters_after(&[vec![WARNING]], true);
+        attention_getters.acknowledge_master_warning();
+        attention_getters.update(&fwc_with(vec![]), true);
+        attention_getters.update(&fwc_with(vec![WARNING]), true);
+
+        assert!(attention_getters.master_warning_is_illuminated());
+    }
+
+    #[test]
+    fn new_caution_illuminates_master_caution_and_sounds_single_chime() {
+        let attention_getters = attention_getters_after(&[vec![CAUTION]], true);
+
+        assert!(attention_getters.master_caution_is_illuminated());
+        assert_eq!(attention_getters.aural_alert(), Some(AuralAlert::SingleChime));
+    }
+
+    #[test]
+    fn single_chime_sounds_once() {
+        let attention_getters = attention_getters_after(&[vec![CAUTION], vec![CAUTION]], true);
+
+        assert!(attention_getters.master_caution_is_illuminated());
+        assert_eq!(attention_getters.aural_alert(), None);
+    }
+
+    #[test]
+    fn acknowledged_caution_extinguishes_master_caution() {
+        let mut attention_getters = attention_getters_after(&[vec![CAUTION]], true);
+        attention_getters.acknowledge_master_caution();
+
+        assert!(!attention_getters.master_caution_is_illuminated());
+    }
+
+    #[test]
+    fn cleared_caution_extinguishes_master_caution() {
+        let attention_getters = attention_getters_after(&[vec![CAUTION], vec![]], true);
+
+        assert!(!attention_getters.master_caution_is_illuminated());
+    }
+
+    #[test]
+    fn level_1_alert_does_not_draw_attention() {
+        let attention_getters = attention_getters_after(&[vec![ADVISORY]], true);
+
+        assert!(!attention_getters.master_caution_is_illuminated());
+        assert_eq!(attention_getters.aural_alert(), None);
+    }
+
+    #[test]
+    fn unpowered_lights_do_not_illuminate() {
+        let attention_getters = attention_getters_after(&[vec![WARNING, CAUTION]], false);
+
+        assert!(!attention_getters.master_warning_is_illuminated());
+        assert!(!attention_getters.master_caution_is_illuminated());
+        assert_eq!(attention_getters.aural_alert(), Some(AuralAlert::ContinuousRepetitiveChime));
+    }
+
+    fn attention_getters_after(updates: &[Vec<Alert>], lights_powered: bool) -> AttentionGetters {
+        let mut attention_getters = AttentionGetters::new();
+        for alerts in updates {
+            attention_getters.update(&fwc_with(alerts.clone()), lights_powered);
+        }
+
+        attention_getters
+    }
+
+    fn fwc_with(alerts: Vec<Alert>) -> FlightWarningComputer {
+        let mut fwc = FlightWarningComputer::new();
+        fwc.update(FlightPhase::AboveFifteenHundredFeet, alerts);
+
+        fwc
+    }
+}
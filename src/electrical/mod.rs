@@ -1,6 +1,8 @@
-use uom::si::{electric_charge::ampere_hour, electric_current::ampere, electric_potential::volt, f32::{Frequency, ElectricPotential, ElectricCurrent, Ratio, ElectricCharge}, frequency::hertz, ratio::percent};
+use uom::si::{electric_charge::ampere_hour, electric_current::ampere, electric_potential::volt, f32::{Frequency, ElectricPotential, ElectricCurrent, Ratio, ElectricCharge, ThermodynamicTemperature}, frequency::hertz, ratio::{percent, ratio}, thermodynamic_temperature::degree_celsius};
 
-use crate::{overhead::OnOffPushButton, shared::Engine};
+use std::{fmt, time::Duration};
+
+use crate::{overhead::OnOffPushButton, shared::{ConfirmationNode, DelayedTrueLogicGate, Engine, Failures, HysteresisComparator, UpdateContext}, simulator::{SimulatorElement, SimulatorReader, SimulatorWriter}, snapshot::{StateVisitor, Stateful, visit_collection, visit_duration, visit_element, visit_variant}};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum PowerSource {
@@ -12,6 +14,20 @@ pub enum PowerSource {
     Battery(u8)
 }
 
+/// Displays the source as named on the ECAM ELEC page, such as "GEN 1" or "EXT PWR".
+impl fmt::Display for PowerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PowerSource::None => write!(f, "NONE"),
+            PowerSource::EngineGenerator(number) => write!(f, "GEN {}", number),
+            PowerSource::ApuGenerator => write!(f, "APU GEN"),
+            PowerSource::External => write!(f, "EXT PWR"),
+            PowerSource::EmergencyGenerator => write!(f, "EMER GEN"),
+            PowerSource::Battery(number) => write!(f, "BAT {}", number)
+        }
+    }
+}
+
 /// Represents a type of electric current.
 #[derive(Clone, Copy, Debug)]
 pub enum Current {
@@ -22,15 +38,11 @@ pub enum Current {
 
 impl Current {
     pub fn is_powered(self) -> bool {
-        match self { 
-            Current::Alternating(..) => true,
-            Current::Direct(..) => true,
-            _ => false
-        }
+        matches!(self, Current::Alternating(..) | Current::Direct(..))
     }
 
     pub fn is_unpowered(self) -> bool {
-        if let Current::None = self { true } else { false }
+        matches!(self, Current::None)
     }
 
     pub fn source(self) -> PowerSource {
@@ -42,21 +54,189 @@ impl Current {
     }
 }
 
+/// Two distinct alternating current sources connected in parallel to the same bus.
+/// The A320's AC sources aren't synchronised, thus they must never be paralleled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Paralleling {
+    pub bus: String,
+    pub sources: (PowerSource, PowerSource)
+}
+
+impl Paralleling {
+    /// Detects paralleling among the outputs of the conductors connected to the given bus.
+    pub fn detect(bus: &str, outputs: &[Current]) -> Option<Paralleling> {
+        let mut sources = outputs.iter().filter_map(|output| match output {
+            Current::Alternating(source, ..) => Some(*source),
+            _ => None
+        });
+
+        let first = sources.next()?;
+        sources.find(|source| *source != first).map(|second| Paralleling {
+            bus: bus.to_string(),
+            sources: (first, second)
+        })
+    }
+}
+
+impl fmt::Display for Paralleling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} and {} are paralleled on {}", self.sources.0, self.sources.1, self.bus)
+    }
+}
+
+/// The name under which the element with the given id writes its simulator variables, e.g. "AC_BUS_1" for "AC BUS 1".
+fn variable_name(id: &str) -> String {
+    id.replace(' ', "_")
+}
+
+/// Writes the voltage and frequency of an AC output as "ELEC_{NAME}_POTENTIAL" and "ELEC_{NAME}_FREQUENCY".
+/// Both are 0 when there is no output.
+fn write_alternating_output(writer: &mut SimulatorWriter, id: &str, output: Current) {
+    let (frequency, voltage) = match output {
+        Current::Alternating(_, frequency, voltage, _) => (frequency.get::<hertz>(), voltage.get::<volt>()),
+        _ => (0., 0.)
+    };
+    writer.write_f64(&format!("ELEC_{}_POTENTIAL", variable_name(id)), voltage as f64);
+    writer.write_f64(&format!("ELEC_{}_FREQUENCY", variable_name(id)), frequency as f64);
+}
+
+impl Stateful for PowerSource {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        let (mut kind, number) = match *self {
+            PowerSource::None => (0, 0),
+            PowerSource::EngineGenerator(number) => (1, number),
+            PowerSource::ApuGenerator => (2, 0),
+            PowerSource::External => (3, 0),
+            PowerSource::EmergencyGenerator => (4, 0),
+            PowerSource::Battery(number) => (5, number)
+        };
+        visit_variant(state, "KIND", &mut kind, &[0, 1, 2, 3, 4, 5]);
+        let mut number = number as u64;
+        state.visit_u64("NUMBER", &mut number);
+        if number > u8::MAX as u64 {
+            state.reject("NUMBER");
+        }
+
+        *self = match kind {
+            1 => PowerSource::EngineGenerator(number as u8),
+            2 => PowerSource::ApuGenerator,
+            3 => PowerSource::External,
+            4 => PowerSource::EmergencyGenerator,
+            5 => PowerSource::Battery(number as u8),
+            _ => PowerSource::None
+        };
+    }
+}
+
+/// Visits the kind of current first, as it determines which quantities are visited.
+impl Stateful for Current {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        let mut kind = match self {
+            Current::Alternating(..) => 0,
+            Current::Direct(..) => 1,
+            Current::None => 2
+        };
+        visit_variant(state, "KIND", &mut kind, &[0, 1, 2]);
+
+        match (kind, *self) {
+            (0, Current::Alternating(..)) | (1, Current::Direct(..)) | (2, Current::None) => {},
+            (0, _) => *self = Current::Alternating(PowerSource::None, Frequency::new::<hertz>(0.), ElectricPotential::new::<volt>(0.), ElectricCurrent::new::<ampere>(0.)),
+            (1, _) => *self = Current::Direct(PowerSource::None, ElectricPotential::new::<volt>(0.), ElectricCurrent::new::<ampere>(0.)),
+            _ => *self = Current::None
+        }
+
+        match self {
+            Current::Alternating(source, frequency, potential, current) => {
+                visit_element(state, "SOURCE", source);
+                state.visit_f32("FREQUENCY", &mut frequency.value);
+                state.visit_f32("POTENTIAL", &mut potential.value);
+                state.visit_f32("CURRENT", &mut current.value);
+            },
+            Current::Direct(source, potential, current) => {
+                visit_element(state, "SOURCE", source);
+                state.visit_f32("POTENTIAL", &mut potential.value);
+                state.visit_f32("CURRENT", &mut current.value);
+            },
+            Current::None => {}
+        }
+    }
+}
+
+/// The conductors a powerable conductor is wired to and the one its input currently comes from,
+/// recorded by `powered_by` and `or_powered_by`. As a circuit offers each conductor all the sources
+/// it is wired to, the circuit's wiring and the path power takes through it follow from its update.
+#[derive(Debug)]
+pub struct Feeders {
+    wired: Vec<String>,
+    feeder: Option<String>
+}
+
+impl Feeders {
+    pub fn new() -> Feeders {
+        Feeders {
+            wired: vec![],
+            feeder: None
+        }
+    }
+
+    /// The ids of the conductors the conductor is wired to, in the order in which they were first offered.
+    pub fn wired(&self) -> impl Iterator<Item = &str> {
+        self.wired.iter().map(|id| id.as_str())
+    }
+
+    /// The id of the conductor the input comes from, or `None` when none of the offered conductors had output.
+    pub fn feeder(&self) -> Option<&str> {
+        self.feeder.as_deref()
+    }
+
+    fn wire(&mut self, id: &str) {
+        if !self.wired.iter().any(|wired| wired == id) {
+            self.wired.push(id.to_string());
+        }
+    }
+
+    fn feed_from(&mut self, id: Option<&str>) {
+        if self.feeder.as_deref() != id {
+            self.feeder = id.map(String::from);
+        }
+    }
+}
+
+impl Stateful for Feeders {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_collection(state, "WIRED", &mut self.wired, String::new);
+        state.enter("WIRED");
+        for (index, id) in self.wired.iter_mut().enumerate() {
+            state.visit_text(&index.to_string(), id);
+        }
+        state.leave();
+
+        let mut is_fed = self.feeder.is_some();
+        state.visit_bool("IS FED", &mut is_fed);
+        let mut feeder = self.feeder.take().unwrap_or_default();
+        state.visit_text("FEEDER", &mut feeder);
+        self.feeder = if is_fed { Some(feeder) } else { None };
+    }
+}
+
 pub trait PowerConductor {
     fn output(&self) -> Current;
+
+    /// Identifies the conductor within its circuit, such as "9XU1" or "AC BUS 1".
+    fn id(&self) -> &str;
 }
 
 pub trait Powerable {
     /// Provides input power from any of the given sources. When none of the sources
     /// has any output, no input is provided.
     fn powered_by<T: PowerConductor + ?Sized>(&mut self, sources: Vec<&T>) {
-        self.set_input(sources.iter().find_map(|x| {
-            let output = x.output();
-            match output {
-                Current::None => None,
-                _ => Some(output)
-            }
-        }).unwrap_or(Current::None));
+        for source in sources.iter() {
+            self.feeders_mut().wire(source.id());
+        }
+
+        let feeder = sources.iter().find(|source| !source.output().is_unpowered());
+        self.set_input(feeder.map_or(Current::None, |feeder| feeder.output()));
+        self.feeders_mut().feed_from(feeder.map(|feeder| feeder.id()));
     }
 
     /// Provides input power from any of the given sources. When none of the sources
@@ -64,11 +244,16 @@ pub trait Powerable {
     /// This function is useful for situations where power can flow bidirectionally between
     /// conductors, such as from ENG1 to AC BUS 2 and ENG2 to AC BUS 1.
     fn or_powered_by<T: PowerConductor + ?Sized>(&mut self, sources: Vec<&T>) {
+        for source in sources.iter() {
+            self.feeders_mut().wire(source.id());
+        }
+
         if let Current::None = self.get_input() {
             for source in sources {
                 let output = source.output();
                 if !output.is_unpowered() {
                     self.set_input(output);
+                    self.feeders_mut().feed_from(Some(source.id()));
                 }
             }
         }
@@ -76,50 +261,168 @@ pub trait Powerable {
 
     fn set_input(&mut self, current: Current);
     fn get_input(&self) -> Current;
+    fn feeders(&self) -> &Feeders;
+    fn feeders_mut(&mut self) -> &mut Feeders;
 }
 
 /// Represents the state of a contactor.
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum ContactorState {
     Open,
-    Closed
+    /// The contactor is commanded closed, but its contacts haven't closed yet.
+    Closing,
+    Closed,
+    /// The contactor is commanded open, but its contacts haven't opened yet.
+    Opening
+}
+
+/// Represents a failure of a contactor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContactorFailure {
+    /// The contactor remains open, regardless of how it is commanded.
+    StuckOpen,
+    /// The contactor remains closed, regardless of how it is commanded.
+    StuckClosed,
+    /// The main contacts are welded together and remain closed. The contactor's
+    /// armature still moves as commanded, thus the feedback reports the commanded state.
+    Welded
 }
 
 /// Represents a contactor in a electrical power circuit.
+/// A contactor doesn't switch instantly. After being commanded to close or open
+/// it takes some time before the contacts are closed or opened.
 #[derive(Debug)]
 pub struct Contactor {
     id: String,
     state: ContactorState,
+    failure: Option<ContactorFailure>,
+    closing_time: Duration,
+    opening_time: Duration,
+    transition_duration: Duration,
     input: Current,
+    feeders: Feeders
 }
 
 impl Contactor {
+    pub const DEFAULT_CLOSING_TIME: Duration = Duration::from_millis(30);
+    pub const DEFAULT_OPENING_TIME: Duration = Duration::from_millis(20);
+
     pub fn new(id: String) -> Contactor {
+        Contactor::with_transition_times(id, Contactor::DEFAULT_CLOSING_TIME, Contactor::DEFAULT_OPENING_TIME)
+    }
+
+    pub fn with_transition_times(id: String, closing_time: Duration, opening_time: Duration) -> Contactor {
         Contactor {
             id,
             state: ContactorState::Open,
+            failure: None,
+            closing_time,
+            opening_time,
+            transition_duration: Duration::from_millis(0),
             input: Current::None,
+            feeders: Feeders::new()
+        }
+    }
+
+    fn fail(&mut self, failure: ContactorFailure) {
+        self.failure = Some(failure);
+        match failure {
+            ContactorFailure::StuckOpen => self.state = ContactorState::Open,
+            ContactorFailure::StuckClosed => self.state = ContactorState::Closed,
+            ContactorFailure::Welded => {}
+        }
+    }
+
+    fn normal(&mut self) {
+        self.failure = None;
+    }
+
+    pub fn failure(&self) -> Option<ContactorFailure> {
+        self.failure
+    }
+
+    /// The ids of the failures this contactor responds to.
+    pub fn failure_ids(&self) -> Vec<String> {
+        vec![self.id.clone(), format!("{} STUCK OPEN", self.id), format!("{} STUCK CLOSED", self.id), format!("{} WELDED", self.id)]
+    }
+
+    /// Queries the failures registry for failures of this contactor. A failure registered
+    /// under only the contactor's id (e.g. "9XU1") makes the contactor stuck open. The other failures
+    /// are registered with their mode appended (e.g. "9XU1 STUCK CLOSED" or "9XU1 WELDED").
+    pub fn update(&mut self, failures: &Failures) {
+        let failure = if failures.is_active(&self.id) || failures.is_active(&format!("{} STUCK OPEN", self.id)) {
+            Some(ContactorFailure::StuckOpen)
+        } else if failures.is_active(&format!("{} STUCK CLOSED", self.id)) {
+            Some(ContactorFailure::StuckClosed)
+        } else if failures.is_active(&format!("{} WELDED", self.id)) {
+            Some(ContactorFailure::Welded)
+        } else {
+            None
+        };
+
+        if failure != self.failure {
+            match failure {
+                Some(failure) => self.fail(failure),
+                None => self.normal()
+            }
         }
     }
 
-    pub fn toggle(&mut self, should_be_closed: bool) {
+    pub fn toggle(&mut self, context: &UpdateContext, should_be_closed: bool) {
+        if let Some(ContactorFailure::StuckOpen) | Some(ContactorFailure::StuckClosed) = self.failure {
+            return;
+        }
+
         self.state = match self.state {
-            ContactorState::Open if should_be_closed => ContactorState::Closed,
-            ContactorState::Closed if !should_be_closed => ContactorState::Open,
+            ContactorState::Open if should_be_closed => self.start_transition(ContactorState::Closing),
+            ContactorState::Closed if !should_be_closed => self.start_transition(ContactorState::Opening),
+            // A transition which is reverted before it completes leaves the contacts as they were.
+            ContactorState::Closing if !should_be_closed => ContactorState::Open,
+            ContactorState::Opening if should_be_closed => ContactorState::Closed,
+            ContactorState::Closing | ContactorState::Opening => self.continue_transition(context),
             _ => self.state
         };
     }
 
-    pub fn is_open(&self) -> bool {
-        if let ContactorState::Open = self.state {
-            true
-        } else {
-            false
+    fn start_transition(&mut self, transition: ContactorState) -> ContactorState {
+        // We do not include the delta representing the moment the contactor was commanded.
+        self.transition_duration = Duration::from_millis(0);
+        self.complete_transition_when_elapsed(transition)
+    }
+
+    fn continue_transition(&mut self, context: &UpdateContext) -> ContactorState {
+        self.transition_duration += context.delta();
+        self.complete_transition_when_elapsed(self.state)
+    }
+
+    fn complete_transition_when_elapsed(&self, transition: ContactorState) -> ContactorState {
+        match transition {
+            ContactorState::Closing if self.closing_time <= self.transition_duration => ContactorState::Closed,
+            ContactorState::Opening if self.opening_time <= self.transition_duration => ContactorState::Open,
+            _ => transition
         }
     }
 
+    /// Indicates if the main contacts are open.
+    pub fn is_open(&self) -> bool {
+        !self.is_closed()
+    }
+
+    /// Indicates if the main contacts are closed.
     pub fn is_closed(&self) -> bool {
-        !self.is_open()
+        self.failure == Some(ContactorFailure::Welded) || self.reports_closed()
+    }
+
+    /// Indicates if the contactor's feedback reports it is open. Monitoring logic
+    /// can only observe the contactor through its feedback.
+    pub fn reports_open(&self) -> bool {
+        !self.reports_closed()
+    }
+
+    /// Indicates if the contactor's feedback reports it is closed. Monitoring logic
+    /// can only observe the contactor through its feedback.
+    pub fn reports_closed(&self) -> bool {
+        matches!(self.state, ContactorState::Closed | ContactorState::Opening)
     }
 }
 
@@ -131,47 +434,155 @@ impl Powerable for Contactor {
     fn get_input(&self) -> Current {
         self.input
     }
+
+    fn feeders(&self) -> &Feeders {
+        &self.feeders
+    }
+
+    fn feeders_mut(&mut self) -> &mut Feeders {
+        &mut self.feeders
+    }
 }
 
 impl PowerConductor for Contactor {
     fn output(&self) -> Current {
-        if let ContactorState::Closed = self.state {
+        if self.is_closed() {
             self.input
         } else {
             Current::None
         }
     }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl SimulatorElement for Contactor {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&format!("ELEC_CONTACTOR_{}_IS_CLOSED", self.id), self.is_closed());
+    }
 }
 
+impl Stateful for Contactor {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_variant(state, "STATE", &mut self.state,
+            &[ContactorState::Open, ContactorState::Closing, ContactorState::Closed, ContactorState::Opening]);
+        visit_variant(state, "FAILURE", &mut self.failure,
+            &[None, Some(ContactorFailure::StuckOpen), Some(ContactorFailure::StuckClosed), Some(ContactorFailure::Welded)]);
+        visit_duration(state, "TRANSITION DURATION", &mut self.transition_duration);
+        visit_element(state, "INPUT", &mut self.input);
+        visit_element(state, "FEEDERS", &mut self.feeders);
+    }
+}
+
+/// A generator whose voltage regulator failed outputs a voltage well above tolerance.
+const FAILED_VOLTAGE_REGULATOR_OUTPUT_VOLTAGE: f32 = 135.;
+
 pub struct EngineGenerator {
     number: u8,
+    id: String,
     output: Current,
+    engine_above_threshold: HysteresisComparator<Ratio>,
+    engine_above_threshold_confirmation: ConfirmationNode,
+    idg_driving: bool,
+    idg_overheating: bool,
+    idg_leaking: bool,
+    idg_oil_outlet_temperature: ThermodynamicTemperature
 }
 
 impl EngineGenerator {
     pub const ENGINE_N2_POWER_OUTPUT_THRESHOLD: f32 = 57.5;
+    /// Once providing output, the generator keeps doing so until N2 drops to this threshold.
+    pub const ENGINE_N2_POWER_OUTPUT_DISCONNECT_THRESHOLD: f32 = 55.;
+    /// N2 has to remain on one side of the thresholds this long before the output follows.
+    pub const POWER_OUTPUT_CONFIRMATION_TIME: Duration = Duration::from_millis(100);
+    /// Below this N2 the IDG cannot yet drive the generator at its nominal speed. The output
+    /// frequency and voltage are then proportionally lower than nominal.
+    pub const ENGINE_N2_NOMINAL_POWER_OUTPUT_THRESHOLD: f32 = 60.;
+    pub const IDG_AMBIENT_TEMPERATURE_IN_CELSIUS: f32 = 25.;
+    /// The oil outlet temperature at which a driving IDG settles.
+    pub const IDG_NOMINAL_OIL_OUTLET_TEMPERATURE_IN_CELSIUS: f32 = 85.;
+    /// The oil outlet temperature towards which an overheating IDG heats up.
+    pub const IDG_OVERHEATING_OIL_OUTLET_TEMPERATURE_IN_CELSIUS: f32 = 220.;
+    pub const IDG_THERMAL_TIME_CONSTANT_IN_SECONDS: f32 = 60.;
 
     pub fn new(number: u8) -> EngineGenerator {
         EngineGenerator {
             number,
+            id: format!("GEN {}", number),
             output: Current::None,
+            engine_above_threshold: HysteresisComparator::new(Ratio::new::<percent>(EngineGenerator::ENGINE_N2_POWER_OUTPUT_DISCONNECT_THRESHOLD),
+                Ratio::new::<percent>(EngineGenerator::ENGINE_N2_POWER_OUTPUT_THRESHOLD)),
+            engine_above_threshold_confirmation: ConfirmationNode::new(
+                EngineGenerator::POWER_OUTPUT_CONFIRMATION_TIME, false),
+            idg_driving: false,
+            idg_overheating: false,
+            idg_leaking: false,
+            idg_oil_outlet_temperature: ThermodynamicTemperature::new::<degree_celsius>(EngineGenerator::IDG_AMBIENT_TEMPERATURE_IN_CELSIUS)
         }
     }
 
-    pub fn update(&mut self, engine: &Engine, idg_push_button: &OnOffPushButton) {
+    /// The id of the IDG driving this generator. An overheating IDG is registered in the failures
+    /// registry as this id followed by "OVERHEAT" (e.g. "IDG 1 OVERHEAT"), a leaking IDG as this id
+    /// followed by "OIL LEAK".
+    pub fn idg_id(&self) -> String {
+        format!("IDG {}", self.number)
+    }
+
+    /// The ids of the failures this generator and its IDG respond to.
+    pub fn failure_ids(&self) -> Vec<String> {
+        vec![self.id.clone(), format!("{} OVERHEAT", self.idg_id()), format!("{} OIL LEAK", self.idg_id())]
+    }
+
+    pub fn update(&mut self, context: &UpdateContext, engine: &Engine, idg_push_button: &OnOffPushButton, failures: &Failures) {
+        self.engine_above_threshold.update(context, engine.n2);
+        self.engine_above_threshold_confirmation.update(context, self.engine_above_threshold.output());
+
         // TODO: The push button being on or off is still a simplification. Of course we should later simulate the
         // IDG itself. It would be disconnected the moment the push button is in the off state. Then the logic below would
         // consider the IDG state itself, instead of the button state.
-        if EngineGenerator::engine_above_threshold(engine) && idg_push_button.is_on() {
-            self.output = Current::Alternating(PowerSource::EngineGenerator(self.number), Frequency::new::<hertz>(400.), 
-                ElectricPotential::new::<volt>(115.), ElectricCurrent::new::<ampere>(782.60));
+        self.idg_driving = self.engine_above_threshold_confirmation.output() && idg_push_button.is_on();
+        self.idg_overheating = failures.is_active(&format!("{} OVERHEAT", self.idg_id()));
+        self.idg_leaking = failures.is_active(&format!("{} OIL LEAK", self.idg_id()));
+
+        if self.idg_driving {
+            let factor = EngineGenerator::nominal_output_factor(engine);
+            let voltage = if failures.is_active(&self.id) { FAILED_VOLTAGE_REGULATOR_OUTPUT_VOLTAGE } else { 115. * factor };
+            self.output = Current::Alternating(PowerSource::EngineGenerator(self.number), Frequency::new::<hertz>(400. * factor), 
+                ElectricPotential::new::<volt>(voltage), ElectricCurrent::new::<ampere>(782.60));
         } else {
             self.output = Current::None
         }
     }
 
-    fn engine_above_threshold(engine: &Engine) -> bool {
-        engine.n2 > Ratio::new::<percent>(EngineGenerator::ENGINE_N2_POWER_OUTPUT_THRESHOLD)
+    /// Updates the IDG's oil outlet temperature, which settles at its nominal temperature
+    /// while the IDG drives the generator and cools down to ambient otherwise.
+    pub fn update_idg_temperature(&mut self, context: &UpdateContext) {
+        let target = if self.idg_overheating {
+            EngineGenerator::IDG_OVERHEATING_OIL_OUTLET_TEMPERATURE_IN_CELSIUS
+        } else if self.idg_driving {
+            EngineGenerator::IDG_NOMINAL_OIL_OUTLET_TEMPERATURE_IN_CELSIUS
+        } else {
+            EngineGenerator::IDG_AMBIENT_TEMPERATURE_IN_CELSIUS
+        };
+
+        let temperature = self.idg_oil_outlet_temperature.get::<degree_celsius>();
+        let factor = (context.delta().as_secs_f32() / EngineGenerator::IDG_THERMAL_TIME_CONSTANT_IN_SECONDS).min(1.);
+        self.idg_oil_outlet_temperature = ThermodynamicTemperature::new::<degree_celsius>(temperature + (target - temperature) * factor);
+    }
+
+    pub fn idg_oil_outlet_temperature(&self) -> ThermodynamicTemperature {
+        self.idg_oil_outlet_temperature
+    }
+
+    /// A leaking IDG loses its oil pressure while driving the generator.
+    pub fn idg_oil_pressure_is_low(&self) -> bool {
+        self.idg_driving && self.idg_leaking
+    }
+
+    fn nominal_output_factor(engine: &Engine) -> f32 {
+        (engine.n2.get::<percent>() / EngineGenerator::ENGINE_N2_NOMINAL_POWER_OUTPUT_THRESHOLD).min(1.)
     }
 }
 
@@ -179,26 +590,160 @@ impl PowerConductor for EngineGenerator {
     fn output(&self) -> Current {
         self.output
     }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl SimulatorElement for EngineGenerator {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        write_alternating_output(writer, &self.id, self.output);
+        writer.write_f64(&format!("ELEC_{}_TEMPERATURE", variable_name(&self.idg_id())), self.idg_oil_outlet_temperature.get::<degree_celsius>() as f64);
+    }
+}
+
+impl Stateful for EngineGenerator {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_element(state, "OUTPUT", &mut self.output);
+        visit_element(state, "ENGINE ABOVE THRESHOLD", &mut self.engine_above_threshold);
+        visit_element(state, "ENGINE ABOVE THRESHOLD CONFIRMATION", &mut self.engine_above_threshold_confirmation);
+        state.visit_bool("IDG DRIVING", &mut self.idg_driving);
+        state.visit_bool("IDG OVERHEATING", &mut self.idg_overheating);
+        state.visit_bool("IDG LEAKING", &mut self.idg_leaking);
+        state.visit_f32("IDG OIL OUTLET TEMPERATURE", &mut self.idg_oil_outlet_temperature.value);
+    }
+}
+
+/// The generator control unit (GCU) monitors the voltage and frequency of a generator's
+/// output and commands the generator line contactor (GLC) accordingly.
+/// The line contactor is only closed while the output is within tolerance. Once closed, an output
+/// which leaves tolerance for longer than the protection's delay trips the line contactor.
+/// Over voltage and over frequency trips are latched as a fault, which can only be reset by cycling
+/// the GEN push button. Under voltage and under frequency trips reset once the output is within tolerance again.
+pub struct GeneratorControlUnit {
+    over_voltage: DelayedTrueLogicGate,
+    under_voltage: DelayedTrueLogicGate,
+    over_frequency: DelayedTrueLogicGate,
+    under_frequency: DelayedTrueLogicGate,
+    faulted: bool,
+    closes_line_contactor: bool
+}
+
+impl GeneratorControlUnit {
+    pub const MAX_VOLTAGE: f32 = 120.;
+    pub const MIN_VOLTAGE: f32 = 110.;
+    pub const MAX_FREQUENCY: f32 = 410.;
+    pub const MIN_FREQUENCY: f32 = 390.;
+    pub const OVER_VOLTAGE_TRIP_DELAY: Duration = Duration::from_millis(150);
+    pub const UNDER_VOLTAGE_TRIP_DELAY: Duration = Duration::from_secs(3);
+    pub const OVER_FREQUENCY_TRIP_DELAY: Duration = Duration::from_millis(150);
+    pub const UNDER_FREQUENCY_TRIP_DELAY: Duration = Duration::from_secs(3);
+
+    pub fn new() -> GeneratorControlUnit {
+        GeneratorControlUnit {
+            over_voltage: DelayedTrueLogicGate::new(GeneratorControlUnit::OVER_VOLTAGE_TRIP_DELAY),
+            under_voltage: DelayedTrueLogicGate::new(GeneratorControlUnit::UNDER_VOLTAGE_TRIP_DELAY),
+            over_frequency: DelayedTrueLogicGate::new(GeneratorControlUnit::OVER_FREQUENCY_TRIP_DELAY),
+            under_frequency: DelayedTrueLogicGate::new(GeneratorControlUnit::UNDER_FREQUENCY_TRIP_DELAY),
+            faulted: false,
+            closes_line_contactor: false
+        }
+    }
+
+    pub fn update<T: PowerConductor + ?Sized>(&mut self, context: &UpdateContext, generator: &T, gen_push_button: &OnOffPushButton) {
+        if gen_push_button.is_off() {
+            self.faulted = false;
+        }
+
+        let output = generator.output();
+        let (frequency, voltage) = match output {
+            Current::Alternating(_, frequency, voltage, _) => (frequency, voltage),
+            _ => (Frequency::new::<hertz>(0.), ElectricPotential::new::<volt>(0.))
+        };
+
+        // Protections are only armed while the generator is online. This avoids tripping
+        // while the generator is still coming up to speed.
+        let online = self.closes_line_contactor && output.is_powered();
+        self.over_voltage.update(context, online && voltage > ElectricPotential::new::<volt>(GeneratorControlUnit::MAX_VOLTAGE));
+        self.under_voltage.update(context, online && voltage < ElectricPotential::new::<volt>(GeneratorControlUnit::MIN_VOLTAGE));
+        self.over_frequency.update(context, online && frequency > Frequency::new::<hertz>(GeneratorControlUnit::MAX_FREQUENCY));
+        self.under_frequency.update(context, online && frequency < Frequency::new::<hertz>(GeneratorControlUnit::MIN_FREQUENCY));
+
+        if self.over_voltage.output() || self.over_frequency.output() {
+            self.faulted = true;
+        }
+
+        let tripped = self.faulted || self.under_voltage.output() || self.under_frequency.output();
+        self.closes_line_contactor = gen_push_button.is_on() && output.is_powered() && !tripped &&
+            (self.closes_line_contactor || GeneratorControlUnit::within_tolerance(frequency, voltage));
+    }
+
+    fn within_tolerance(frequency: Frequency, voltage: ElectricPotential) -> bool {
+        voltage >= ElectricPotential::new::<volt>(GeneratorControlUnit::MIN_VOLTAGE) &&
+        voltage <= ElectricPotential::new::<volt>(GeneratorControlUnit::MAX_VOLTAGE) &&
+        frequency >= Frequency::new::<hertz>(GeneratorControlUnit::MIN_FREQUENCY) &&
+        frequency <= Frequency::new::<hertz>(GeneratorControlUnit::MAX_FREQUENCY)
+    }
+
+    /// Indicates if the GCU commands the generator line contactor to close.
+    pub fn closes_line_contactor(&self) -> bool {
+        self.closes_line_contactor
+    }
+
+    /// Indicates if the GCU latched a fault which requires the GEN push button to be cycled.
+    pub fn has_fault(&self) -> bool {
+        self.faulted
+    }
+}
+
+impl Stateful for GeneratorControlUnit {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_element(state, "OVER VOLTAGE", &mut self.over_voltage);
+        visit_element(state, "UNDER VOLTAGE", &mut self.under_voltage);
+        visit_element(state, "OVER FREQUENCY", &mut self.over_frequency);
+        visit_element(state, "UNDER FREQUENCY", &mut self.under_frequency);
+        state.visit_bool("FAULTED", &mut self.faulted);
+        state.visit_bool("CLOSES LINE CONTACTOR", &mut self.closes_line_contactor);
+    }
 }
 
 pub struct ApuGenerator {
-    output: Current
+    output: Current,
+    apu_above_threshold: HysteresisComparator<Ratio>,
+    apu_above_threshold_confirmation: ConfirmationNode
 }
 
 impl ApuGenerator {
     pub const APU_SPEED_POWER_OUTPUT_THRESHOLD: f32 = 57.5;
+    /// Once providing output, the generator keeps doing so until the speed drops to this threshold.
+    pub const APU_SPEED_POWER_OUTPUT_DISCONNECT_THRESHOLD: f32 = 55.;
+    /// The speed has to remain on one side of the thresholds this long before the output follows.
+    pub const POWER_OUTPUT_CONFIRMATION_TIME: Duration = Duration::from_millis(100);
 
     pub fn new() -> ApuGenerator {
         ApuGenerator {
-            output: Current::None
+            output: Current::None,
+            apu_above_threshold: HysteresisComparator::new(Ratio::new::<percent>(ApuGenerator::APU_SPEED_POWER_OUTPUT_DISCONNECT_THRESHOLD),
+                Ratio::new::<percent>(ApuGenerator::APU_SPEED_POWER_OUTPUT_THRESHOLD)),
+            apu_above_threshold_confirmation: ConfirmationNode::new(
+                ApuGenerator::POWER_OUTPUT_CONFIRMATION_TIME, false)
         }
     }
 
-    pub fn update(&mut self, apu: &AuxiliaryPowerUnit) {
-        const POWER_OUTPUT_THRESHOLD: f32 = 57.5;
-        if apu.speed > Ratio::new::<percent>(ApuGenerator::APU_SPEED_POWER_OUTPUT_THRESHOLD) {
+    /// The ids of the failures this generator responds to.
+    pub fn failure_ids(&self) -> Vec<String> {
+        vec![self.id().to_string()]
+    }
+
+    pub fn update(&mut self, context: &UpdateContext, apu: &AuxiliaryPowerUnit, failures: &Failures) {
+        self.apu_above_threshold.update(context, apu.speed);
+        self.apu_above_threshold_confirmation.update(context, self.apu_above_threshold.output());
+
+        if self.apu_above_threshold_confirmation.output() {
+            let voltage = if failures.is_active(self.id()) { FAILED_VOLTAGE_REGULATOR_OUTPUT_VOLTAGE } else { 115. };
             self.output = Current::Alternating(PowerSource::ApuGenerator, Frequency::new::<hertz>(400.),
-                ElectricPotential::new::<volt>(115.), ElectricCurrent::new::<ampere>(782.60));
+                ElectricPotential::new::<volt>(voltage), ElectricCurrent::new::<ampere>(782.60));
         } else {
             self.output = Current::None
         }
@@ -209,6 +754,24 @@ impl PowerConductor for ApuGenerator {
     fn output(&self) -> Current {
         self.output
     }
+
+    fn id(&self) -> &str {
+        "APU GEN"
+    }
+}
+
+impl SimulatorElement for ApuGenerator {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        write_alternating_output(writer, self.id(), self.output);
+    }
+}
+
+impl Stateful for ApuGenerator {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_element(state, "OUTPUT", &mut self.output);
+        visit_element(state, "APU ABOVE THRESHOLD", &mut self.apu_above_threshold);
+        visit_element(state, "APU ABOVE THRESHOLD CONFIRMATION", &mut self.apu_above_threshold_confirmation);
+    }
 }
 
 pub struct AuxiliaryPowerUnit {
@@ -221,6 +784,24 @@ impl AuxiliaryPowerUnit {
             speed: Ratio::new::<percent>(0.)
         }
     }
+
+    pub fn update(&mut self, _context: &UpdateContext) {
+        // Until the APU is implemented, its speed is read from the simulator and there is nothing to update.
+    }
+}
+
+impl SimulatorElement for AuxiliaryPowerUnit {
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        if let Some(speed) = reader.read_f64("APU PCT RPM") {
+            self.speed = Ratio::new::<percent>(speed as f32);
+        }
+    }
+}
+
+impl Stateful for AuxiliaryPowerUnit {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_f32("SPEED", &mut self.speed.value);
+    }
 }
 
 pub struct ExternalPowerSource {
@@ -235,6 +816,14 @@ impl ExternalPowerSource {
     }
 }
 
+impl SimulatorElement for ExternalPowerSource {
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        if let Some(plugged_in) = reader.read_bool("EXTERNAL POWER AVAILABLE:1") {
+            self.plugged_in = plugged_in;
+        }
+    }
+}
+
 impl PowerConductor for ExternalPowerSource {
     fn output(&self) -> Current {
         if self.plugged_in { 
@@ -244,27 +833,42 @@ impl PowerConductor for ExternalPowerSource {
             Current::None
         }
     }
+
+    fn id(&self) -> &str {
+        "EXT PWR"
+    }
+}
+
+impl Stateful for ExternalPowerSource {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_bool("PLUGGED IN", &mut self.plugged_in);
+    }
 }
 
 pub struct ElectricalBus {
+    id: String,
     input: Current,
+    feeders: Feeders,
     failed: bool
 }
 
 impl ElectricalBus {
-    pub fn new() -> ElectricalBus {
+    pub fn new(id: String) -> ElectricalBus {
         ElectricalBus {
+            id,
             input: Current::None,
+            feeders: Feeders::new(),
             failed: false
         }
     }
 
-    pub fn fail(&mut self) {
-        self.failed = true;
+    /// The ids of the failures this bus responds to.
+    pub fn failure_ids(&self) -> Vec<String> {
+        vec![self.id.clone()]
     }
 
-    pub fn normal(&mut self) {
-        self.failed = false;
+    pub fn update(&mut self, failures: &Failures) {
+        self.failed = failures.is_active(&self.id);
     }
 }
 
@@ -276,6 +880,14 @@ impl Powerable for ElectricalBus {
     fn get_input(&self) -> Current {
         self.input
     }
+
+    fn feeders(&self) -> &Feeders {
+        &self.feeders
+    }
+
+    fn feeders_mut(&mut self) -> &mut Feeders {
+        &mut self.feeders
+    }
 }
 
 impl PowerConductor for ElectricalBus {
@@ -286,32 +898,132 @@ impl PowerConductor for ElectricalBus {
             Current::None
         }
     }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl SimulatorElement for ElectricalBus {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&format!("ELEC_{}_IS_POWERED", variable_name(&self.id)), self.output().is_powered());
+    }
+}
+
+impl Stateful for ElectricalBus {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_element(state, "INPUT", &mut self.input);
+        visit_element(state, "FEEDERS", &mut self.feeders);
+        state.visit_bool("FAILED", &mut self.failed);
+    }
 }
 
+/// Represents a transformer rectifier (TR), converting alternating current into direct current.
+/// The output voltage follows the input voltage and droops with the load drawn from the TR.
+/// A load exceeding the rating for too long results in an overload fault, after which the TR
+/// no longer provides output. The TR's temperature rises with the load it supplies.
 pub struct TransformerRectifier {
+    id: String,
     input: Current,
-    failed: bool
+    feeders: Feeders,
+    failed: bool,
+    overheating: bool,
+    load: ElectricCurrent,
+    overload: DelayedTrueLogicGate,
+    overload_fault: bool,
+    temperature: ThermodynamicTemperature
 }
 
 impl TransformerRectifier {
-    pub fn new() -> TransformerRectifier {
+    pub const RATED_CURRENT_IN_AMPERES: f32 = 200.;
+    pub const NOMINAL_INPUT_VOLTAGE: f32 = 115.;
+    pub const NO_LOAD_OUTPUT_VOLTAGE: f32 = 28.5;
+    /// The output voltage drops by this voltage per ampere of load.
+    pub const OUTPUT_RESISTANCE_IN_OHMS: f32 = 0.005;
+    pub const OVERLOAD_FAULT_DELAY: Duration = Duration::from_secs(5);
+    pub const AMBIENT_TEMPERATURE_IN_CELSIUS: f32 = 25.;
+    /// The temperature above ambient at which the TR settles per ampere of load.
+    pub const TEMPERATURE_RISE_PER_AMPERE_IN_CELSIUS: f32 = 0.3;
+    /// The temperature towards which an overheating TR heats up, regardless of its load.
+    pub const OVERHEATING_TEMPERATURE_IN_CELSIUS: f32 = 250.;
+    pub const THERMAL_TIME_CONSTANT_IN_SECONDS: f32 = 120.;
+
+    pub fn new(id: String) -> TransformerRectifier {
         TransformerRectifier {
+            id,
             input: Current::None,
-            failed: false
+            feeders: Feeders::new(),
+            failed: false,
+            overheating: false,
+            load: ElectricCurrent::new::<ampere>(0.),
+            overload: DelayedTrueLogicGate::new(TransformerRectifier::OVERLOAD_FAULT_DELAY),
+            overload_fault: false,
+            temperature: ThermodynamicTemperature::new::<degree_celsius>(TransformerRectifier::AMBIENT_TEMPERATURE_IN_CELSIUS)
         }
     }
 
-    pub fn fail(&mut self) {
-        self.failed = true;
+    /// The ids of the failures this TR responds to.
+    pub fn failure_ids(&self) -> Vec<String> {
+        vec![self.id.clone(), format!("{} OVERHEAT", self.id)]
     }
 
-    pub fn normal(&mut self) {
-        self.failed = false;
+    /// Queries the failures registry for a failure of the TR (e.g. "TR 1") and for
+    /// overheating, registered as the TR's id followed by "OVERHEAT" (e.g. "TR 1 OVERHEAT").
+    pub fn update(&mut self, failures: &Failures) {
+        self.failed = failures.is_active(&self.id);
+        self.overheating = failures.is_active(&format!("{} OVERHEAT", self.id));
+    }
+
+    /// Updates the load drawn from the TR's output by the consumers it supplies.
+    pub fn update_load(&mut self, context: &UpdateContext, load: ElectricCurrent) {
+        self.load = load;
+        self.overload.update(context, self.output().is_powered() &&
+            load > ElectricCurrent::new::<ampere>(TransformerRectifier::RATED_CURRENT_IN_AMPERES));
+
+        if self.overload.output() {
+            self.overload_fault = true;
+        }
+
+        self.update_temperature(context);
+    }
+
+    fn update_temperature(&mut self, context: &UpdateContext) {
+        let target = if self.overheating {
+            TransformerRectifier::OVERHEATING_TEMPERATURE_IN_CELSIUS
+        } else {
+            TransformerRectifier::AMBIENT_TEMPERATURE_IN_CELSIUS + TransformerRectifier::TEMPERATURE_RISE_PER_AMPERE_IN_CELSIUS * self.output_current().get::<ampere>()
+        };
+
+        let temperature = self.temperature.get::<degree_celsius>();
+        let factor = (context.delta().as_secs_f32() / TransformerRectifier::THERMAL_TIME_CONSTANT_IN_SECONDS).min(1.);
+        self.temperature = ThermodynamicTemperature::new::<degree_celsius>(temperature + (target - temperature) * factor);
+    }
+
+    pub fn temperature(&self) -> ThermodynamicTemperature {
+        self.temperature
     }
 
     pub fn has_failed(&self) -> bool {
         self.failed
     }
+
+    pub fn has_overload_fault(&self) -> bool {
+        self.overload_fault
+    }
+
+    pub fn output_voltage(&self) -> ElectricPotential {
+        match self.output() {
+            Current::Direct(_, voltage, _) => voltage,
+            _ => ElectricPotential::new::<volt>(0.)
+        }
+    }
+
+    pub fn output_current(&self) -> ElectricCurrent {
+        match self.output() {
+            Current::Direct(_, _, current) => current,
+            _ => ElectricCurrent::new::<ampere>(0.)
+        }
+    }
 }
 
 impl Powerable for TransformerRectifier {
@@ -322,46 +1034,365 @@ impl Powerable for TransformerRectifier {
     fn get_input(&self) -> Current {
         self.input
     }
+
+    fn feeders(&self) -> &Feeders {
+        &self.feeders
+    }
+
+    fn feeders_mut(&mut self) -> &mut Feeders {
+        &mut self.feeders
+    }
 }
 
 impl PowerConductor for TransformerRectifier {
     fn output(&self) -> Current {
-        if self.failed {
+        if self.failed || self.overload_fault {
             Current::None
         } else {
             match self.input {
-                Current::Alternating(source, ..) => Current::Direct(source, ElectricPotential::new::<volt>(28.5), ElectricCurrent::new::<ampere>(35.)),
+                Current::Alternating(source, _, input_voltage, _) => {
+                    let no_load_voltage = TransformerRectifier::NO_LOAD_OUTPUT_VOLTAGE * input_voltage.get::<volt>() / TransformerRectifier::NOMINAL_INPUT_VOLTAGE;
+                    let voltage = (no_load_voltage - TransformerRectifier::OUTPUT_RESISTANCE_IN_OHMS * self.load.get::<ampere>()).max(0.);
+
+                    Current::Direct(source, ElectricPotential::new::<volt>(voltage), self.load)
+                },
                 _ => Current::None
             }
         }
     }
-}
-
-pub struct EmergencyGenerator {
-    running: bool,
-    is_blue_pressurised: bool
-}
 
-impl EmergencyGenerator {
-    pub fn new() -> EmergencyGenerator {
-        EmergencyGenerator {
-            running: false,
-            is_blue_pressurised: false
-        }
+    fn id(&self) -> &str {
+        &self.id
     }
+}
 
-    pub fn update(&mut self, is_blue_pressurised: bool) {
-        // TODO: The emergency generator is driven by the blue hydraulic circuit. Still to be implemented.
-        self.is_blue_pressurised = is_blue_pressurised;
+impl SimulatorElement for TransformerRectifier {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_f64(&format!("ELEC_{}_POTENTIAL", variable_name(&self.id)), self.output_voltage().get::<volt>() as f64);
+        writer.write_f64(&format!("ELEC_{}_CURRENT", variable_name(&self.id)), self.output_current().get::<ampere>() as f64);
     }
+}
 
-    pub fn attempt_start(&mut self) {
-        self.running = true;
+impl Stateful for TransformerRectifier {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_element(state, "INPUT", &mut self.input);
+        visit_element(state, "FEEDERS", &mut self.feeders);
+        state.visit_bool("FAILED", &mut self.failed);
+        state.visit_bool("OVERHEATING", &mut self.overheating);
+        state.visit_f32("LOAD", &mut self.load.value);
+        visit_element(state, "OVERLOAD", &mut self.overload);
+        state.visit_bool("OVERLOAD FAULT", &mut self.overload_fault);
+        state.visit_f32("TEMPERATURE", &mut self.temperature.value);
     }
+}
 
-    pub fn is_running(&self) -> bool {
-        self.is_blue_pressurised && self.running
-    }
+/// Monitors a transformer rectifier through its observed output current and temperature.
+/// The aircraft cannot know whether a TR failed, it can only observe what the TR outputs.
+/// Undercurrent and overtemperature are only detected once confirmed for some time.
+/// An overtemperature is latched, as the TR contactor opens and the TR then cools down.
+pub struct TransformerRectifierMonitor {
+    undercurrent: DelayedTrueLogicGate,
+    overtemperature: DelayedTrueLogicGate,
+    overtemperature_detected: bool
+}
+
+impl TransformerRectifierMonitor {
+    pub const UNDERCURRENT_THRESHOLD_IN_AMPERES: f32 = 1.;
+    pub const UNDERCURRENT_CONFIRMATION_TIME: Duration = Duration::from_secs(1);
+    pub const OVERTEMPERATURE_THRESHOLD_IN_CELSIUS: f32 = 150.;
+    pub const OVERTEMPERATURE_CONFIRMATION_TIME: Duration = Duration::from_secs(2);
+
+    pub fn new() -> TransformerRectifierMonitor {
+        TransformerRectifierMonitor {
+            undercurrent: DelayedTrueLogicGate::new(TransformerRectifierMonitor::UNDERCURRENT_CONFIRMATION_TIME),
+            overtemperature: DelayedTrueLogicGate::new(TransformerRectifierMonitor::OVERTEMPERATURE_CONFIRMATION_TIME),
+            overtemperature_detected: false
+        }
+    }
+
+    pub fn update(&mut self, context: &UpdateContext, tr: &TransformerRectifier) {
+        self.undercurrent.update(context, tr.output_current() < ElectricCurrent::new::<ampere>(TransformerRectifierMonitor::UNDERCURRENT_THRESHOLD_IN_AMPERES));
+        self.overtemperature.update(context, tr.temperature() > ThermodynamicTemperature::new::<degree_celsius>(TransformerRectifierMonitor::OVERTEMPERATURE_THRESHOLD_IN_CELSIUS));
+
+        if self.overtemperature.output() {
+            self.overtemperature_detected = true;
+        }
+    }
+
+    pub fn has_undercurrent(&self) -> bool {
+        self.undercurrent.output()
+    }
+
+    pub fn has_overtemperature(&self) -> bool {
+        self.overtemperature_detected
+    }
+
+    pub fn has_fault(&self) -> bool {
+        self.has_undercurrent() || self.has_overtemperature()
+    }
+}
+
+impl Stateful for TransformerRectifierMonitor {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_element(state, "UNDERCURRENT", &mut self.undercurrent);
+        visit_element(state, "OVERTEMPERATURE", &mut self.overtemperature);
+        state.visit_bool("OVERTEMPERATURE DETECTED", &mut self.overtemperature_detected);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CircuitBreakerState {
+    Closed,
+    Tripped,
+    Pulled
+}
+
+/// Represents a thermal circuit breaker protecting the wiring between a bus and its consumers.
+/// The breaker trips following an inverse-time curve: the larger the overcurrent, the sooner it trips.
+/// A breaker can be pulled and reset by hand from its circuit breaker panel.
+pub struct CircuitBreaker {
+    id: String,
+    panel: String,
+    position: String,
+    rated_current: ElectricCurrent,
+    state: CircuitBreakerState,
+    /// The accumulated heat as a ratio of the heat at which the breaker trips.
+    heat: f32,
+    input: Current,
+    feeders: Feeders
+}
+
+impl CircuitBreaker {
+    /// The time it takes to trip at twice the rated current is a third of this constant.
+    pub const TRIP_CURVE_CONSTANT_IN_SECONDS: f32 = 10.;
+    /// The time it takes to cool down completely from the moment it would trip.
+    pub const COOLING_TIME_IN_SECONDS: f32 = 60.;
+
+    pub fn new(panel: String, position: String, rated_current: ElectricCurrent) -> CircuitBreaker {
+        CircuitBreaker {
+            id: format!("{} {}", panel, position),
+            panel,
+            position,
+            rated_current,
+            state: CircuitBreakerState::Closed,
+            heat: 0.,
+            input: Current::None,
+            feeders: Feeders::new()
+        }
+    }
+
+    pub fn panel(&self) -> &str {
+        &self.panel
+    }
+
+    pub fn position(&self) -> &str {
+        &self.position
+    }
+
+    pub fn rated_current(&self) -> ElectricCurrent {
+        self.rated_current
+    }
+
+    /// Returns the time it takes to trip when continuously conducting the given load,
+    /// or None when the load doesn't exceed the rated current.
+    pub fn trip_time(&self, load: ElectricCurrent) -> Option<Duration> {
+        let overload_ratio = (load / self.rated_current).get::<ratio>();
+        if overload_ratio > 1. {
+            Some(Duration::from_secs_f32(CircuitBreaker::TRIP_CURVE_CONSTANT_IN_SECONDS / (overload_ratio.powi(2) - 1.)))
+        } else {
+            None
+        }
+    }
+
+    pub fn update(&mut self, context: &UpdateContext, load: ElectricCurrent) {
+        let conducting = self.is_closed() && self.input.is_powered();
+        match self.trip_time(load) {
+            Some(trip_time) if conducting => self.heat += context.delta().as_secs_f32() / trip_time.as_secs_f32(),
+            _ => self.heat = (self.heat - context.delta().as_secs_f32() / CircuitBreaker::COOLING_TIME_IN_SECONDS).max(0.)
+        }
+
+        if self.heat >= 1. && self.is_closed() {
+            self.state = CircuitBreakerState::Tripped;
+        }
+    }
+
+    pub fn pull(&mut self) {
+        self.state = CircuitBreakerState::Pulled;
+    }
+
+    /// Pushes the breaker back in after it tripped or was pulled.
+    pub fn reset(&mut self) {
+        self.state = CircuitBreakerState::Closed;
+        self.heat = 0.;
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state == CircuitBreakerState::Closed
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.state == CircuitBreakerState::Tripped
+    }
+
+    pub fn is_pulled(&self) -> bool {
+        self.state == CircuitBreakerState::Pulled
+    }
+}
+
+impl Powerable for CircuitBreaker {
+    fn set_input(&mut self, current: Current) {
+        self.input = current;
+    }
+
+    fn get_input(&self) -> Current {
+        self.input
+    }
+
+    fn feeders(&self) -> &Feeders {
+        &self.feeders
+    }
+
+    fn feeders_mut(&mut self) -> &mut Feeders {
+        &mut self.feeders
+    }
+}
+
+impl PowerConductor for CircuitBreaker {
+    fn output(&self) -> Current {
+        if self.is_closed() {
+            self.input
+        } else {
+            Current::None
+        }
+    }
+
+    /// The panel and position of the breaker, such as "121VU M08".
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Stateful for CircuitBreaker {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_variant(state, "STATE", &mut self.state,
+            &[CircuitBreakerState::Closed, CircuitBreakerState::Tripped, CircuitBreakerState::Pulled]);
+        state.visit_f32("HEAT", &mut self.heat);
+        visit_element(state, "INPUT", &mut self.input);
+        visit_element(state, "FEEDERS", &mut self.feeders);
+    }
+}
+
+/// Represents an electrical consumer which draws its demand from its input while powered.
+pub struct PowerConsumer {
+    id: String,
+    demand: ElectricCurrent,
+    short_circuited: bool,
+    input: Current,
+    feeders: Feeders
+}
+
+impl PowerConsumer {
+    /// A short circuited consumer draws many times its normal demand.
+    pub const SHORT_CIRCUIT_DEMAND_FACTOR: f32 = 10.;
+
+    pub fn new(id: String, demand: ElectricCurrent) -> PowerConsumer {
+        PowerConsumer {
+            id,
+            demand,
+            short_circuited: false,
+            input: Current::None,
+            feeders: Feeders::new()
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The ids of the failures this consumer responds to.
+    pub fn failure_ids(&self) -> Vec<String> {
+        vec![format!("{} SHORT CIRCUIT", self.id)]
+    }
+
+    /// Queries the failures registry for a short circuit, registered as the consumer's id
+    /// followed by "SHORT CIRCUIT" (e.g. "FUEL PUMP 1 SHORT CIRCUIT").
+    pub fn update(&mut self, failures: &Failures) {
+        self.short_circuited = failures.is_active(&format!("{} SHORT CIRCUIT", self.id));
+    }
+
+    pub fn is_powered(&self) -> bool {
+        self.input.is_powered()
+    }
+
+    /// Returns the current the consumer draws when powered and not short circuited.
+    pub fn demand(&self) -> ElectricCurrent {
+        self.demand
+    }
+
+    /// Returns the current drawn from the consumer's input.
+    pub fn load(&self) -> ElectricCurrent {
+        if !self.is_powered() {
+            ElectricCurrent::new::<ampere>(0.)
+        } else if self.short_circuited {
+            self.demand * PowerConsumer::SHORT_CIRCUIT_DEMAND_FACTOR
+        } else {
+            self.demand
+        }
+    }
+}
+
+impl Powerable for PowerConsumer {
+    fn set_input(&mut self, current: Current) {
+        self.input = current;
+    }
+
+    fn get_input(&self) -> Current {
+        self.input
+    }
+
+    fn feeders(&self) -> &Feeders {
+        &self.feeders
+    }
+
+    fn feeders_mut(&mut self) -> &mut Feeders {
+        &mut self.feeders
+    }
+}
+
+impl Stateful for PowerConsumer {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_f32("DEMAND", &mut self.demand.value);
+        state.visit_bool("SHORT CIRCUITED", &mut self.short_circuited);
+        visit_element(state, "INPUT", &mut self.input);
+        visit_element(state, "FEEDERS", &mut self.feeders);
+    }
+}
+
+pub struct EmergencyGenerator {
+    running: bool,
+    is_blue_pressurised: bool
+}
+
+impl EmergencyGenerator {
+    pub fn new() -> EmergencyGenerator {
+        EmergencyGenerator {
+            running: false,
+            is_blue_pressurised: false
+        }
+    }
+
+    pub fn update(&mut self, is_blue_pressurised: bool) {
+        // TODO: The emergency generator is driven by the blue hydraulic circuit. Still to be implemented.
+        self.is_blue_pressurised = is_blue_pressurised;
+    }
+
+    pub fn attempt_start(&mut self) {
+        self.running = true;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.is_blue_pressurised && self.running
+    }
 }
 
 impl PowerConductor for EmergencyGenerator {
@@ -372,42 +1403,107 @@ impl PowerConductor for EmergencyGenerator {
             Current::None
         }
     }
+
+    fn id(&self) -> &str {
+        "EMER GEN"
+    }
+}
+
+impl Stateful for EmergencyGenerator {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_bool("RUNNING", &mut self.running);
+        state.visit_bool("IS BLUE PRESSURISED", &mut self.is_blue_pressurised);
+    }
 }
 
 pub struct Battery {
     number: u8,
+    id: String,
     input: Current,
-    charge: ElectricCharge
+    feeders: Feeders,
+    /// The charge drawn from the full battery. Storing it instead of the remaining charge keeps
+    /// the small charges of a single step from being rounded away while the battery is nearly full.
+    consumed_charge: ElectricCharge,
+    current: ElectricCurrent
 }
 
 impl Battery {
-    const MAX_ELECTRIC_CHARGE_AMPERE_HOURS: f32 = 23.0;
+    pub const MAX_ELECTRIC_CHARGE_AMPERE_HOURS: f32 = 23.0;
+    const OUTPUT_VOLTAGE: f32 = 28.5;
+    /// The charging current is proportional to the consumed charge, such that the
+    /// battery approaches its full charge exponentially with this time constant.
+    pub const CHARGING_TIME_CONSTANT_IN_SECONDS: f32 = 600.;
+    /// The last part of the charge is completed at this current.
+    pub const MIN_CHARGING_CURRENT_IN_AMPERES: f32 = 2.;
+    pub const MAX_CHARGING_CURRENT_IN_AMPERES: f32 = 60.;
 
     pub fn full(number: u8) -> Battery {
-        Battery::new(number, ElectricCharge::new::<ampere_hour>(Battery::MAX_ELECTRIC_CHARGE_AMPERE_HOURS))
+        Battery::new(number, ElectricCharge::new::<ampere_hour>(0.))
     }
 
     pub fn empty(number: u8) -> Battery {
-        Battery::new(number, ElectricCharge::new::<ampere_hour>(0.))
+        Battery::new(number, ElectricCharge::new::<ampere_hour>(Battery::MAX_ELECTRIC_CHARGE_AMPERE_HOURS))
     }
 
-    fn new(number: u8, charge: ElectricCharge) -> Battery {
+    fn new(number: u8, consumed_charge: ElectricCharge) -> Battery {
         Battery {
             number,
+            id: format!("BAT {}", number),
             input: Current::None,
-            charge
+            feeders: Feeders::new(),
+            consumed_charge,
+            current: ElectricCurrent::new::<ampere>(0.)
         }
     }
 
     pub fn is_full(&self) -> bool {
-        self.charge >= ElectricCharge::new::<ampere_hour>(Battery::MAX_ELECTRIC_CHARGE_AMPERE_HOURS)
+        self.consumed_charge == ElectricCharge::new::<ampere_hour>(0.)
     }
 
     pub fn is_empty(&self) -> bool {
-        self.charge == ElectricCharge::new::<ampere_hour>(0.)
+        self.consumed_charge >= ElectricCharge::new::<ampere_hour>(Battery::MAX_ELECTRIC_CHARGE_AMPERE_HOURS)
     }
 
-    // TODO: Charging and depleting battery when used.
+    pub fn charge(&self) -> ElectricCharge {
+        ElectricCharge::new::<ampere_hour>(Battery::MAX_ELECTRIC_CHARGE_AMPERE_HOURS) - self.consumed_charge
+    }
+
+    /// The voltage across the battery's terminals. While charging, this is the charging voltage.
+    pub fn potential(&self) -> ElectricPotential {
+        match (self.input, self.output()) {
+            (Current::Direct(_, voltage, _), _) => voltage,
+            (_, Current::Direct(_, voltage, _)) => voltage,
+            _ => ElectricPotential::new::<volt>(0.)
+        }
+    }
+
+    /// The current flowing into (positive) or out of (negative) the battery.
+    pub fn current(&self) -> ElectricCurrent {
+        self.current
+    }
+
+    /// Charges the battery while it is powered, or discharges it by the load it supplies.
+    pub fn update(&mut self, context: &UpdateContext, load: ElectricCurrent) {
+        self.current = if self.input.is_powered() {
+            self.charging_current()
+        } else if self.output().is_powered() {
+            -load
+        } else {
+            ElectricCurrent::new::<ampere>(0.)
+        };
+
+        let consumed_charge = self.consumed_charge.get::<ampere_hour>() - self.current.get::<ampere>() * context.delta().as_secs_f32() / 3600.;
+        self.consumed_charge = ElectricCharge::new::<ampere_hour>(consumed_charge.clamp(0., Battery::MAX_ELECTRIC_CHARGE_AMPERE_HOURS));
+    }
+
+    fn charging_current(&self) -> ElectricCurrent {
+        if self.is_full() {
+            return ElectricCurrent::new::<ampere>(0.);
+        }
+
+        ElectricCurrent::new::<ampere>((self.consumed_charge.get::<ampere_hour>() * 3600. / Battery::CHARGING_TIME_CONSTANT_IN_SECONDS)
+            .clamp(Battery::MIN_CHARGING_CURRENT_IN_AMPERES, Battery::MAX_CHARGING_CURRENT_IN_AMPERES))
+    }
 }
 
 impl Powerable for Battery {
@@ -418,18 +1514,46 @@ impl Powerable for Battery {
     fn get_input(&self) -> Current {
         self.input
     }
+
+    fn feeders(&self) -> &Feeders {
+        &self.feeders
+    }
+
+    fn feeders_mut(&mut self) -> &mut Feeders {
+        &mut self.feeders
+    }
 }
 
 impl PowerConductor for Battery {
     fn output(&self) -> Current {
         if let Current::None = self.input {
-            if self.charge > ElectricCharge::new::<ampere_hour>(0.) {
-                return Current::Direct(PowerSource::Battery(self.number), ElectricPotential::new::<volt>(28.5), ElectricCurrent::new::<ampere>(35.));
+            if !self.is_empty() {
+                return Current::Direct(PowerSource::Battery(self.number), ElectricPotential::new::<volt>(Battery::OUTPUT_VOLTAGE), -self.current.min(ElectricCurrent::new::<ampere>(0.)));
             }
         }
         
         Current::None
     }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl SimulatorElement for Battery {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_f64(&format!("ELEC_BAT_{}_POTENTIAL", self.number), self.potential().get::<volt>() as f64);
+        writer.write_f64(&format!("ELEC_BAT_{}_CURRENT", self.number), self.current().get::<ampere>() as f64);
+    }
+}
+
+impl Stateful for Battery {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_element(state, "INPUT", &mut self.input);
+        visit_element(state, "FEEDERS", &mut self.feeders);
+        state.visit_f32("CONSUMED CHARGE", &mut self.consumed_charge.value);
+        state.visit_f32("CURRENT", &mut self.current.value);
+    }
 }
 
 #[cfg(test)]
@@ -441,6 +1565,10 @@ mod tests {
         fn output(&self) -> Current {
             Current::None
         }
+
+        fn id(&self) -> &str {
+            "POWERLESS"
+        }
     }
 
     struct StubApuGenerator {}
@@ -450,6 +1578,10 @@ mod tests {
             Current::Alternating(PowerSource::ApuGenerator, Frequency::new::<hertz>(400.), 
                 ElectricPotential::new::<volt>(115.), ElectricCurrent::new::<ampere>(782.60))
         }
+
+        fn id(&self) -> &str {
+            "APU GEN"
+        }
     }
 
     fn apu_generator() -> StubApuGenerator {
@@ -464,337 +1596,1221 @@ mod tests {
 
         #[test]
         fn alternating_current_is_powered() {
-            assert_eq!(alternating_current().is_powered(), true);
+            assert!(alternating_current().is_powered());
         }
 
         #[test]
         fn alternating_current_is_not_unpowered() {
-            assert_eq!(alternating_current().is_unpowered(), false);
+            assert!(!alternating_current().is_unpowered());
+        }
+
+        #[test]
+        fn direct_current_is_powered() {
+            assert!(direct_current().is_powered());
+        }
+
+        #[test]
+        fn direct_current_is_not_unpowered() {
+            assert!(!direct_current().is_unpowered());
+        }
+        
+        #[test]
+        fn none_current_is_not_powered() {
+            assert!(!none_current().is_powered());
+        }
+
+        #[test]
+        fn none_current_is_unpowered() {
+            assert!(none_current().is_unpowered());
+        }
+
+        fn alternating_current() -> Current {
+            Current::Alternating(PowerSource::ApuGenerator, Frequency::new::<hertz>(0.), ElectricPotential::new::<volt>(0.), ElectricCurrent::new::<ampere>(0.))
+        }
+
+        fn direct_current() -> Current {
+            Current::Direct(PowerSource::ApuGenerator, ElectricPotential::new::<volt>(0.), ElectricCurrent::new::<ampere>(0.))
+        }
+
+        fn none_current() -> Current {
+            Current::None
+        }
+    }
+
+    #[cfg(test)]
+    mod paralleling_tests {
+        use uom::si::{electric_current::ampere, electric_potential::volt, frequency::hertz};
+
+        use super::*;
+
+        #[test]
+        fn distinct_alternating_sources_are_paralleled() {
+            let paralleling = Paralleling::detect("AC BUS 1", &[Current::None, alternating_current(PowerSource::EngineGenerator(1)),
+                alternating_current(PowerSource::ApuGenerator)]);
+
+            assert_eq!(paralleling, Some(Paralleling { bus: String::from("AC BUS 1"), sources: (PowerSource::EngineGenerator(1), PowerSource::ApuGenerator) }));
+            assert_eq!(paralleling.unwrap().to_string(), "GEN 1 and APU GEN are paralleled on AC BUS 1");
+        }
+
+        #[test]
+        fn same_source_through_multiple_conductors_is_not_paralleled() {
+            assert_eq!(Paralleling::detect("AC BUS 1", &[alternating_current(PowerSource::EngineGenerator(2)), alternating_current(PowerSource::EngineGenerator(2))]), None);
+        }
+
+        #[test]
+        fn direct_current_is_not_paralleled() {
+            let direct_current = Current::Direct(PowerSource::Battery(1), ElectricPotential::new::<volt>(28.), ElectricCurrent::new::<ampere>(0.));
+
+            assert_eq!(Paralleling::detect("AC BUS 1", &[alternating_current(PowerSource::EngineGenerator(1)), direct_current]), None);
+        }
+
+        fn alternating_current(source: PowerSource) -> Current {
+            Current::Alternating(source, Frequency::new::<hertz>(400.), ElectricPotential::new::<volt>(115.), ElectricCurrent::new::<ampere>(0.))
+        }
+    }
+
+    #[cfg(test)]
+    mod contactor_tests {
+        use std::time::Duration;
+        use super::*;
+
+        #[test]
+        fn contactor_starts_open() {
+            assert_eq!(contactor().state, ContactorState::Open);
+        }
+
+        #[test]
+        fn open_contactor_when_toggled_open_stays_open() {
+            let mut contactor = open_contactor();
+            toggle_for(&mut contactor, false, Contactor::DEFAULT_OPENING_TIME);
+
+            assert_eq!(contactor.state, ContactorState::Open);
+        }
+
+        #[test]
+        fn open_contactor_when_toggled_closed_closes() {
+            let mut contactor = open_contactor();
+            toggle_for(&mut contactor, true, Contactor::DEFAULT_CLOSING_TIME);
+
+            assert_eq!(contactor.state, ContactorState::Closed);
+        }
+
+        #[test]
+        fn closed_contactor_when_toggled_open_opens() {
+            let mut contactor = closed_contactor();
+            toggle_for(&mut contactor, false, Contactor::DEFAULT_OPENING_TIME);
+
+            assert_eq!(contactor.state, ContactorState::Open);
+        }
+
+        #[test]
+        fn closed_contactor_when_toggled_closed_stays_closed() {
+            let mut contactor = closed_contactor();
+            toggle_for(&mut contactor, true, Contactor::DEFAULT_CLOSING_TIME);
+
+            assert_eq!(contactor.state, ContactorState::Closed);
+        }
+
+        #[test]
+        fn open_contactor_when_toggled_closed_is_open_until_closing_time_passed() {
+            let mut contactor = open_contactor();
+            toggle_for(&mut contactor, true, Contactor::DEFAULT_CLOSING_TIME - Duration::from_millis(1));
+
+            assert!(contactor.is_open());
+        }
+
+        #[test]
+        fn closed_contactor_when_toggled_open_is_closed_until_opening_time_passed() {
+            let mut contactor = closed_contactor();
+            toggle_for(&mut contactor, false, Contactor::DEFAULT_OPENING_TIME - Duration::from_millis(1));
+
+            assert!(contactor.is_closed());
+        }
+
+        #[test]
+        fn closing_contactor_has_no_output_until_closing_time_passed() {
+            let mut contactor = open_contactor();
+            contactor.powered_by(vec!(&apu_generator()));
+            toggle_for(&mut contactor, true, Contactor::DEFAULT_CLOSING_TIME - Duration::from_millis(1));
+
+            assert!(contactor.output().is_unpowered());
+        }
+
+        #[test]
+        fn opening_contactor_has_output_until_opening_time_passed() {
+            let mut contactor = closed_contactor();
+            contactor.powered_by(vec!(&apu_generator()));
+            toggle_for(&mut contactor, false, Contactor::DEFAULT_OPENING_TIME - Duration::from_millis(1));
+
+            assert!(contactor.output().is_powered());
+        }
+
+        #[test]
+        fn closing_contactor_when_toggled_open_before_closing_time_passed_opens() {
+            let mut contactor = open_contactor();
+            contactor.toggle(&context(Duration::from_millis(0)), true);
+            contactor.toggle(&context(Duration::from_millis(1)), false);
+
+            assert_eq!(contactor.state, ContactorState::Open);
+        }
+
+        #[test]
+        fn stuck_open_contactor_when_toggled_closed_stays_open() {
+            let mut contactor = open_contactor();
+            contactor.fail(ContactorFailure::StuckOpen);
+            toggle_for(&mut contactor, true, Contactor::DEFAULT_CLOSING_TIME);
+
+            assert!(contactor.is_open());
+            assert!(contactor.reports_open());
+        }
+
+        #[test]
+        fn closed_contactor_which_becomes_stuck_open_opens() {
+            let mut contactor = closed_contactor();
+            contactor.fail(ContactorFailure::StuckOpen);
+
+            assert!(contactor.is_open());
+        }
+
+        #[test]
+        fn stuck_closed_contactor_when_toggled_open_stays_closed() {
+            let mut contactor = closed_contactor();
+            contactor.fail(ContactorFailure::StuckClosed);
+            toggle_for(&mut contactor, false, Contactor::DEFAULT_OPENING_TIME);
+
+            assert!(contactor.is_closed());
+            assert!(contactor.reports_closed());
+        }
+
+        #[test]
+        fn stuck_closed_contactor_has_output_when_powered() {
+            let mut contactor = open_contactor();
+            contactor.fail(ContactorFailure::StuckClosed);
+            contactor.powered_by(vec!(&apu_generator()));
+
+            assert!(contactor.output().is_powered());
+        }
+
+        #[test]
+        fn welded_contactor_when_toggled_open_stays_closed_but_reports_open() {
+            let mut contactor = closed_contactor();
+            contactor.fail(ContactorFailure::Welded);
+            toggle_for(&mut contactor, false, Contactor::DEFAULT_OPENING_TIME);
+
+            assert!(contactor.is_closed());
+            assert!(contactor.reports_open());
+        }
+
+        #[test]
+        fn welded_contactor_has_output_when_powered() {
+            let mut contactor = open_contactor();
+            contactor.fail(ContactorFailure::Welded);
+            contactor.powered_by(vec!(&apu_generator()));
+
+            assert!(contactor.output().is_powered());
+        }
+
+        #[test]
+        fn failed_contactor_when_returned_to_normal_can_be_toggled() {
+            let mut contactor = open_contactor();
+            contactor.fail(ContactorFailure::StuckOpen);
+            contactor.normal();
+            toggle_for(&mut contactor, true, Contactor::DEFAULT_CLOSING_TIME);
+
+            assert!(contactor.is_closed());
+        }
+
+        #[test]
+        fn contactor_failed_in_registry_by_id_is_stuck_open() {
+            let mut contactor = closed_contactor();
+            let mut failures = Failures::new();
+            failures.activate("TEST");
+            contactor.update(&failures);
+
+            assert_eq!(contactor.failure(), Some(ContactorFailure::StuckOpen));
+            assert!(contactor.is_open());
+        }
+
+        #[test]
+        fn contactor_failed_in_registry_by_id_and_mode_has_that_failure() {
+            let mut contactor = closed_contactor();
+            let mut failures = Failures::new();
+            failures.activate("TEST WELDED");
+            contactor.update(&failures);
+
+            assert_eq!(contactor.failure(), Some(ContactorFailure::Welded));
+        }
+
+        #[test]
+        fn contactor_returns_to_normal_when_failure_deactivated_in_registry() {
+            let mut contactor = closed_contactor();
+            let mut failures = Failures::new();
+            failures.activate("TEST STUCK CLOSED");
+            contactor.update(&failures);
+            failures.deactivate("TEST STUCK CLOSED");
+            contactor.update(&failures);
+
+            assert_eq!(contactor.failure(), None);
+        }
+
+        #[test]
+        fn contactor_without_transition_times_switches_instantly() {
+            let mut contactor = Contactor::with_transition_times(String::from("TEST"), Duration::from_millis(0), Duration::from_millis(0));
+            contactor.toggle(&context(Duration::from_millis(0)), true);
+            assert_eq!(contactor.state, ContactorState::Closed);
+
+            contactor.toggle(&context(Duration::from_millis(0)), false);
+            assert_eq!(contactor.state, ContactorState::Open);
+        }
+
+        #[test]
+        fn open_contactor_has_no_output_when_powered_by_nothing() {
+            contactor_has_no_output_when_powered_by_nothing(open_contactor());
+        }
+
+        #[test]
+        fn closed_contactor_has_no_output_when_powered_by_nothing() {
+            contactor_has_no_output_when_powered_by_nothing(closed_contactor());
+        }
+
+        fn contactor_has_no_output_when_powered_by_nothing(mut contactor: Contactor) {
+            let nothing: Vec<&dyn PowerConductor> = vec![];
+            contactor.powered_by(nothing);
+
+            assert!(contactor.output().is_unpowered());
+        }
+
+        #[test]
+        fn open_contactor_has_no_output_when_powered_by_nothing_which_is_powered() {
+            contactor_has_no_output_when_powered_by_nothing_which_is_powered(open_contactor());
+        }
+
+        #[test]
+        fn closed_contactor_has_no_output_when_powered_by_nothing_which_is_powered() {
+            contactor_has_no_output_when_powered_by_nothing_which_is_powered(closed_contactor());
+        }
+
+        fn contactor_has_no_output_when_powered_by_nothing_which_is_powered(mut contactor: Contactor) {
+            contactor.powered_by(vec![&Powerless{}]);
+
+            assert!(contactor.output().is_unpowered());
+        }
+
+        #[test]
+        fn open_contactor_has_no_output_when_powered_by_something() {
+            let mut contactor = open_contactor();
+            let conductors: Vec<&dyn PowerConductor> = vec![&Powerless{}, &StubApuGenerator{}];
+            contactor.powered_by(conductors);
+
+            assert!(contactor.output().is_unpowered());
+        }
+
+        #[test]
+        fn closed_contactor_has_output_when_powered_by_something_which_is_powered() {
+            let mut contactor = closed_contactor();
+            let conductors: Vec<&dyn PowerConductor> = vec![&Powerless{}, &StubApuGenerator{}];
+            contactor.powered_by(conductors);
+
+            assert!(contactor.output().is_powered());
+        }
+
+        fn toggle_for(contactor: &mut Contactor, should_be_closed: bool, delta: Duration) {
+            contactor.toggle(&context(Duration::from_millis(0)), should_be_closed);
+            contactor.toggle(&context(delta), should_be_closed);
+        }
+
+        fn context(delta: Duration) -> UpdateContext {
+            UpdateContext::new(delta)
+        }
+
+        fn contactor() -> Contactor {
+            Contactor::new(String::from("TEST"))
+        }
+
+        fn open_contactor() -> Contactor {
+            let mut contactor = contactor();
+            contactor.state = ContactorState::Open;
+
+            contactor
+        }
+
+        fn closed_contactor() -> Contactor {
+            let mut contactor = contactor();
+            contactor.state = ContactorState::Closed;
+
+            contactor
+        }
+    }
+
+    #[cfg(test)]
+    mod engine_generator_tests {
+        use std::time::Duration;
+        use uom::si::{ratio::percent};
+        use super::*;
+
+        #[test]
+        fn starts_without_output() {
+            assert!(engine_generator().output.is_unpowered());
+        }
+
+        #[test]
+        fn when_engine_n2_above_threshold_provides_output() {
+            let mut generator = engine_generator();
+            update_below_threshold(&mut generator);
+            update_above_threshold(&mut generator);
+
+            assert!(generator.output.is_powered());
+        }
+
+        #[test]
+        fn when_engine_n2_below_threshold_provides_no_output() {
+            let mut generator = engine_generator();
+            update_above_threshold(&mut generator);
+            update_below_threshold(&mut generator);
+
+            assert!(generator.output.is_unpowered());
+        }
+
+        #[test]
+        fn when_engine_n2_drops_between_thresholds_keeps_providing_output() {
+            let mut generator = engine_generator();
+            update_above_threshold(&mut generator);
+            update_between_thresholds(&mut generator);
+
+            assert!(generator.output.is_powered());
+        }
+
+        #[test]
+        fn when_engine_n2_rises_between_thresholds_provides_no_output() {
+            let mut generator = engine_generator();
+            update_below_threshold(&mut generator);
+            update_between_thresholds(&mut generator);
+
+            assert!(generator.output.is_unpowered());
+        }
+
+        #[test]
+        fn when_engine_n2_above_threshold_for_less_than_confirmation_time_provides_no_output() {
+            let mut generator = engine_generator();
+            let engine = engine_above_threshold();
+            generator.update(&UpdateContext::new(Duration::from_millis(0)), &engine, &OnOffPushButton::new_on(), &Failures::new());
+            generator.update(&UpdateContext::new(confirmation_time() - Duration::from_millis(1)), &engine, &OnOffPushButton::new_on(), &Failures::new());
+
+            assert!(generator.output.is_unpowered());
+        }
+
+        #[test]
+        fn when_engine_n2_briefly_drops_below_threshold_keeps_providing_output() {
+            let mut generator = engine_generator();
+            update_above_threshold(&mut generator);
+            generator.update(&UpdateContext::new(Duration::from_millis(100)), &engine_below_threshold(), &OnOffPushButton::new_on(), &Failures::new());
+            generator.update(&UpdateContext::new(Duration::from_millis(100)), &engine_above_threshold(), &OnOffPushButton::new_on(), &Failures::new());
+
+            assert!(generator.output.is_powered());
+        }
+
+        #[test]
+        fn when_idg_disconnected_provides_no_output() {
+            let mut generator = engine_generator();
+            update(&mut generator, &engine_above_threshold(), &OnOffPushButton::new_off(), &Failures::new());
+
+            assert!(generator.output.is_unpowered());
+        }
+
+        #[test]
+        fn when_engine_n2_below_nominal_threshold_provides_output_below_nominal_frequency() {
+            let mut generator = engine_generator();
+            update_above_threshold(&mut generator);
+
+            assert!(if let Current::Alternating(_, frequency, ..) = generator.output { frequency < Frequency::new::<hertz>(400.) } else { false });
+        }
+
+        #[test]
+        fn when_engine_n2_above_nominal_threshold_provides_output_at_nominal_frequency() {
+            let mut generator = engine_generator();
+            update(&mut generator, &engine(Ratio::new::<percent>(EngineGenerator::ENGINE_N2_NOMINAL_POWER_OUTPUT_THRESHOLD + 1.)), &OnOffPushButton::new_on(), &Failures::new());
+
+            assert!(if let Current::Alternating(_, frequency, ..) = generator.output { frequency == Frequency::new::<hertz>(400.) } else { false });
+        }
+
+        #[test]
+        fn when_failed_provides_output_above_voltage_tolerance() {
+            let mut generator = engine_generator();
+            let mut failures = Failures::new();
+            failures.activate("GEN 1");
+            update(&mut generator, &engine(Ratio::new::<percent>(EngineGenerator::ENGINE_N2_NOMINAL_POWER_OUTPUT_THRESHOLD + 1.)), &OnOffPushButton::new_on(), &failures);
+
+            assert!(if let Current::Alternating(_, _, voltage, _) = generator.output { voltage > ElectricPotential::new::<volt>(GeneratorControlUnit::MAX_VOLTAGE) } else { false });
+        }
+
+        #[test]
+        fn idg_starts_at_ambient_temperature() {
+            assert_eq!(engine_generator().idg_oil_outlet_temperature(), ThermodynamicTemperature::new::<degree_celsius>(EngineGenerator::IDG_AMBIENT_TEMPERATURE_IN_CELSIUS));
+        }
+
+        #[test]
+        fn when_driving_idg_settles_at_nominal_temperature() {
+            let mut generator = engine_generator();
+            update_above_threshold(&mut generator);
+            generator.update_idg_temperature(&UpdateContext::new(Duration::from_secs(3_600)));
+
+            assert_eq!(generator.idg_oil_outlet_temperature(), ThermodynamicTemperature::new::<degree_celsius>(EngineGenerator::IDG_NOMINAL_OIL_OUTLET_TEMPERATURE_IN_CELSIUS));
+        }
+
+        #[test]
+        fn when_idg_overheating_heats_up_beyond_nominal_temperature() {
+            let mut generator = engine_generator();
+            let mut failures = Failures::new();
+            failures.activate("IDG 1 OVERHEAT");
+            update(&mut generator, &engine_above_threshold(), &OnOffPushButton::new_on(), &failures);
+            generator.update_idg_temperature(&UpdateContext::new(Duration::from_secs(3_600)));
+
+            assert!(generator.idg_oil_outlet_temperature() > ThermodynamicTemperature::new::<degree_celsius>(EngineGenerator::IDG_NOMINAL_OIL_OUTLET_TEMPERATURE_IN_CELSIUS));
+        }
+
+        #[test]
+        fn when_idg_leaking_while_driving_idg_oil_pressure_is_low() {
+            let mut generator = engine_generator();
+            let mut failures = Failures::new();
+            failures.activate("IDG 1 OIL LEAK");
+            update(&mut generator, &engine_above_threshold(), &OnOffPushButton::new_on(), &failures);
+
+            assert!(generator.idg_oil_pressure_is_low());
+        }
+
+        #[test]
+        fn when_leaking_idg_disconnected_idg_oil_pressure_is_not_low() {
+            let mut generator = engine_generator();
+            let mut failures = Failures::new();
+            failures.activate("IDG 1 OIL LEAK");
+            update(&mut generator, &engine_above_threshold(), &OnOffPushButton::new_off(), &failures);
+
+            assert!(!generator.idg_oil_pressure_is_low());
+        }
+
+        fn engine_generator() -> EngineGenerator {
+            EngineGenerator::new(1)
+        }
+
+        fn engine(n2: Ratio) -> Engine {
+            let mut engine = Engine::new(1);
+            engine.n2 = n2;
+
+            engine
+        }
+
+        /// Updates the generator for long enough for the engine's N2 to be confirmed.
+        fn update(generator: &mut EngineGenerator, engine: &Engine, idg_push_button: &OnOffPushButton, failures: &Failures) {
+            generator.update(&UpdateContext::new(Duration::from_millis(0)), engine, idg_push_button, failures);
+            generator.update(&UpdateContext::new(confirmation_time()), engine, idg_push_button, failures);
+        }
+
+        fn confirmation_time() -> Duration {
+            EngineGenerator::POWER_OUTPUT_CONFIRMATION_TIME
+        }
+
+        fn update_above_threshold(generator: &mut EngineGenerator) {
+            update(generator, &engine_above_threshold(), &OnOffPushButton::new_on(), &Failures::new());
+        }
+
+        fn update_between_thresholds(generator: &mut EngineGenerator) {
+            update(generator, &engine(Ratio::new::<percent>((EngineGenerator::ENGINE_N2_POWER_OUTPUT_THRESHOLD
+                + EngineGenerator::ENGINE_N2_POWER_OUTPUT_DISCONNECT_THRESHOLD) / 2.)), &OnOffPushButton::new_on(), &Failures::new());
+        }
+
+        fn update_below_threshold(generator: &mut EngineGenerator) {
+            update(generator, &engine_below_threshold(), &OnOffPushButton::new_on(), &Failures::new());
+        }
+
+        fn engine_above_threshold() -> Engine {
+            engine(Ratio::new::<percent>(EngineGenerator::ENGINE_N2_POWER_OUTPUT_THRESHOLD + 1.))
+        }
+
+        fn engine_below_threshold() -> Engine {
+            engine(Ratio::new::<percent>(EngineGenerator::ENGINE_N2_POWER_OUTPUT_DISCONNECT_THRESHOLD - 1.))
+        }
+    }
+
+    #[cfg(test)]
+    mod generator_control_unit_tests {
+        use std::time::Duration;
+        use super::*;
+
+        struct StubGenerator {
+            output: Current
+        }
+
+        impl StubGenerator {
+            fn new(frequency: f32, voltage: f32) -> StubGenerator {
+                StubGenerator {
+                    output: Current::Alternating(PowerSource::EngineGenerator(1), Frequency::new::<hertz>(frequency),
+                        ElectricPotential::new::<volt>(voltage), ElectricCurrent::new::<ampere>(782.60))
+                }
+            }
+
+            fn nominal() -> StubGenerator {
+                StubGenerator::new(400., 115.)
+            }
+        }
+
+        impl PowerConductor for StubGenerator {
+            fn output(&self) -> Current {
+                self.output
+            }
+
+            fn id(&self) -> &str {
+                "GEN 1"
+            }
+        }
+
+        #[test]
+        fn starts_with_line_contactor_open() {
+            assert!(!gcu().closes_line_contactor());
+        }
+
+        #[test]
+        fn closes_line_contactor_when_output_within_tolerance() {
+            let mut gcu = gcu();
+            gcu.update(&context(Duration::from_millis(1)), &StubGenerator::nominal(), &OnOffPushButton::new_on());
+
+            assert!(gcu.closes_line_contactor());
+        }
+
+        #[test]
+        fn does_not_close_line_contactor_when_gen_push_button_off() {
+            let mut gcu = gcu();
+            gcu.update(&context(Duration::from_millis(1)), &StubGenerator::nominal(), &OnOffPushButton::new_off());
+
+            assert!(!gcu.closes_line_contactor());
+        }
+
+        #[test]
+        fn does_not_close_line_contactor_when_generator_unpowered() {
+            let mut gcu = gcu();
+            gcu.update(&context(Duration::from_millis(1)), &Powerless {}, &OnOffPushButton::new_on());
+
+            assert!(!gcu.closes_line_contactor());
+        }
+
+        #[test]
+        fn does_not_close_line_contactor_when_frequency_below_tolerance() {
+            let mut gcu = gcu();
+            gcu.update(&context(Duration::from_millis(1)), &StubGenerator::new(GeneratorControlUnit::MIN_FREQUENCY - 10., 115.), &OnOffPushButton::new_on());
+
+            assert!(!gcu.closes_line_contactor());
+        }
+
+        #[test]
+        fn does_not_close_line_contactor_when_voltage_below_tolerance() {
+            let mut gcu = gcu();
+            gcu.update(&context(Duration::from_millis(1)), &StubGenerator::new(400., GeneratorControlUnit::MIN_VOLTAGE - 10.), &OnOffPushButton::new_on());
+
+            assert!(!gcu.closes_line_contactor());
+        }
+
+        #[test]
+        fn keeps_line_contactor_closed_when_over_voltage_shorter_than_trip_delay() {
+            let mut gcu = online_gcu();
+            over_voltage_for(&mut gcu, GeneratorControlUnit::OVER_VOLTAGE_TRIP_DELAY - Duration::from_millis(1));
+
+            assert!(gcu.closes_line_contactor());
+            assert!(!gcu.has_fault());
+        }
+
+        #[test]
+        fn trips_line_contactor_and_latches_fault_when_over_voltage_longer_than_trip_delay() {
+            let mut gcu = online_gcu();
+            over_voltage_for(&mut gcu, GeneratorControlUnit::OVER_VOLTAGE_TRIP_DELAY);
+
+            assert!(!gcu.closes_line_contactor());
+            assert!(gcu.has_fault());
+        }
+
+        #[test]
+        fn trips_line_contactor_and_latches_fault_when_over_frequency_longer_than_trip_delay() {
+            let mut gcu = online_gcu();
+            let generator = StubGenerator::new(GeneratorControlUnit::MAX_FREQUENCY + 10., 115.);
+            gcu.update(&context(Duration::from_millis(0)), &generator, &OnOffPushButton::new_on());
+            gcu.update(&context(GeneratorControlUnit::OVER_FREQUENCY_TRIP_DELAY), &generator, &OnOffPushButton::new_on());
+
+            assert!(!gcu.closes_line_contactor());
+            assert!(gcu.has_fault());
+        }
+
+        #[test]
+        fn latched_fault_keeps_line_contactor_open_when_output_returns_within_tolerance() {
+            let mut gcu = online_gcu();
+            over_voltage_for(&mut gcu, GeneratorControlUnit::OVER_VOLTAGE_TRIP_DELAY);
+            gcu.update(&context(Duration::from_millis(1)), &StubGenerator::nominal(), &OnOffPushButton::new_on());
+
+            assert!(!gcu.closes_line_contactor());
+            assert!(gcu.has_fault());
+        }
+
+        #[test]
+        fn cycling_gen_push_button_resets_latched_fault() {
+            let mut gcu = online_gcu();
+            over_voltage_for(&mut gcu, GeneratorControlUnit::OVER_VOLTAGE_TRIP_DELAY);
+            gcu.update(&context(Duration::from_millis(1)), &StubGenerator::nominal(), &OnOffPushButton::new_off());
+            gcu.update(&context(Duration::from_millis(1)), &StubGenerator::nominal(), &OnOffPushButton::new_on());
+
+            assert!(gcu.closes_line_contactor());
+            assert!(!gcu.has_fault());
+        }
+
+        #[test]
+        fn trips_line_contactor_without_latching_fault_when_under_frequency_longer_than_trip_delay() {
+            let mut gcu = online_gcu();
+            under_frequency_for(&mut gcu, GeneratorControlUnit::UNDER_FREQUENCY_TRIP_DELAY);
+
+            assert!(!gcu.closes_line_contactor());
+            assert!(!gcu.has_fault());
+        }
+
+        #[test]
+        fn closes_line_contactor_again_once_under_frequency_returns_within_tolerance() {
+            let mut gcu = online_gcu();
+            under_frequency_for(&mut gcu, GeneratorControlUnit::UNDER_FREQUENCY_TRIP_DELAY);
+            gcu.update(&context(Duration::from_millis(1)), &StubGenerator::nominal(), &OnOffPushButton::new_on());
+            gcu.update(&context(Duration::from_millis(1)), &StubGenerator::nominal(), &OnOffPushButton::new_on());
+
+            assert!(gcu.closes_line_contactor());
+        }
+
+        fn gcu() -> GeneratorControlUnit {
+            GeneratorControlUnit::new()
+        }
+
+        fn online_gcu() -> GeneratorControlUnit {
+            let mut gcu = gcu();
+            gcu.update(&context(Duration::from_millis(1)), &StubGenerator::nominal(), &OnOffPushButton::new_on());
+
+            gcu
+        }
+
+        fn over_voltage_for(gcu: &mut GeneratorControlUnit, delta: Duration) {
+            let generator = StubGenerator::new(400., GeneratorControlUnit::MAX_VOLTAGE + 10.);
+            gcu.update(&context(Duration::from_millis(0)), &generator, &OnOffPushButton::new_on());
+            gcu.update(&context(delta), &generator, &OnOffPushButton::new_on());
+        }
+
+        fn under_frequency_for(gcu: &mut GeneratorControlUnit, delta: Duration) {
+            let generator = StubGenerator::new(GeneratorControlUnit::MIN_FREQUENCY - 10., 115.);
+            gcu.update(&context(Duration::from_millis(0)), &generator, &OnOffPushButton::new_on());
+            gcu.update(&context(delta), &generator, &OnOffPushButton::new_on());
+        }
+
+        fn context(delta: Duration) -> UpdateContext {
+            UpdateContext::new(delta)
+        }
+    }
+
+    #[cfg(test)]
+    mod apu_generator_tests {
+        use std::time::Duration;
+        use uom::si::{ratio::percent};
+        use super::*;
+
+        #[test]
+        fn starts_without_output() {
+            assert!(apu_generator().output.is_unpowered());
+        }
+
+        #[test]
+        fn when_apu_speed_above_threshold_provides_output() {
+            let mut generator = apu_generator();
+            update_below_threshold(&mut generator);
+            update_above_threshold(&mut generator);
+
+            assert!(generator.output.is_powered());
+        }
+
+        #[test]
+        fn when_apu_speed_below_threshold_provides_no_output() {
+            let mut generator = apu_generator();
+            update_above_threshold(&mut generator);
+            update_below_threshold(&mut generator);
+
+            assert!(generator.output.is_unpowered());
+        }
+
+        #[test]
+        fn when_apu_speed_drops_between_thresholds_keeps_providing_output() {
+            let mut generator = apu_generator();
+            update_above_threshold(&mut generator);
+            update(&mut generator, Ratio::new::<percent>((ApuGenerator::APU_SPEED_POWER_OUTPUT_THRESHOLD
+                + ApuGenerator::APU_SPEED_POWER_OUTPUT_DISCONNECT_THRESHOLD) / 2.));
+
+            assert!(generator.output.is_powered());
+        }
+
+        #[test]
+        fn when_apu_speed_above_threshold_for_less_than_confirmation_time_provides_no_output() {
+            let mut generator = apu_generator();
+            let apu = apu(Ratio::new::<percent>(ApuGenerator::APU_SPEED_POWER_OUTPUT_THRESHOLD + 1.));
+            generator.update(&UpdateContext::new(Duration::from_millis(0)), &apu, &Failures::new());
+            generator.update(&UpdateContext::new(ApuGenerator::POWER_OUTPUT_CONFIRMATION_TIME - Duration::from_millis(1)), &apu, &Failures::new());
+
+            assert!(generator.output.is_unpowered());
+        }
+
+        fn apu_generator() -> ApuGenerator {
+            ApuGenerator::new()
+        }
+
+        fn apu(speed: Ratio) -> AuxiliaryPowerUnit {
+            let mut apu = AuxiliaryPowerUnit::new();
+            apu.speed = speed;
+
+            apu
+        }
+
+        /// Updates the generator for long enough for the APU's speed to be confirmed.
+        fn update(generator: &mut ApuGenerator, speed: Ratio) {
+            generator.update(&UpdateContext::new(Duration::from_millis(0)), &apu(speed), &Failures::new());
+            generator.update(&UpdateContext::new(ApuGenerator::POWER_OUTPUT_CONFIRMATION_TIME), &apu(speed), &Failures::new());
+        }
+
+        fn update_above_threshold(generator: &mut ApuGenerator) {
+            update(generator, Ratio::new::<percent>(ApuGenerator::APU_SPEED_POWER_OUTPUT_THRESHOLD + 1.));
+        }
+
+        fn update_below_threshold(generator: &mut ApuGenerator) {
+            update(generator, Ratio::new::<percent>(ApuGenerator::APU_SPEED_POWER_OUTPUT_DISCONNECT_THRESHOLD - 1.));
+        }
+    }
+
+    #[cfg(test)]
+    mod external_power_source_tests {
+        use super::*;
+
+        #[test]
+        fn starts_without_output() {
+            assert!(external_power_source().output().is_unpowered());
+        }
+
+        #[test]
+        fn when_plugged_in_provides_output() {
+            let mut ext_pwr = external_power_source();
+            ext_pwr.plugged_in = true;
+
+            assert!(ext_pwr.output().is_powered());
+        }
+
+        #[test]
+        fn when_not_plugged_in_provides_no_output() {
+            let mut ext_pwr = external_power_source();
+            ext_pwr.plugged_in = false;
+
+            assert!(ext_pwr.output().is_unpowered());
+        }
+
+        fn external_power_source() -> ExternalPowerSource {
+            ExternalPowerSource::new()
+        }
+    }
+
+    #[cfg(test)]
+    mod transformer_rectifier_tests {
+        use std::time::Duration;
+        use super::*;
+
+        #[test]
+        fn starts_without_output() {
+            assert!(transformer_rectifier().output().is_unpowered());
         }
 
         #[test]
-        fn direct_current_is_powered() {
-            assert_eq!(direct_current().is_powered(), true);
+        fn when_powered_with_alternating_current_outputs_direct_current() {
+            let mut tr = transformer_rectifier();
+            tr.powered_by(vec!(&apu_generator()));
+
+            assert!(tr.output().is_powered());
+            assert!(matches!(tr.output(), Current::Direct(PowerSource::ApuGenerator, ..)));
         }
 
         #[test]
-        fn direct_current_is_not_unpowered() {
-            assert_eq!(direct_current().is_unpowered(), false);
+        fn when_failure_deactivated_in_registry_has_output_again() {
+            let mut tr = transformer_rectifier();
+            tr.powered_by(vec!(&apu_generator()));
+            let mut failures = Failures::new();
+            failures.activate("TR 1");
+            tr.update(&failures);
+            failures.deactivate("TR 1");
+            tr.update(&failures);
+
+            assert!(tr.output().is_powered());
         }
-        
+
         #[test]
-        fn none_current_is_not_powered() {
-            assert_eq!(none_current().is_powered(), false);
+        fn when_failure_active_in_registry_has_no_output() {
+            let mut tr = transformer_rectifier();
+            tr.powered_by(vec!(&apu_generator()));
+            let mut failures = Failures::new();
+            failures.activate("TR 1");
+            tr.update(&failures);
+
+            assert!(tr.output().is_unpowered());
         }
 
         #[test]
-        fn none_current_is_unpowered() {
-            assert_eq!(none_current().is_unpowered(), true);
-        }
+        fn when_unpowered_has_no_output() {
+            let mut tr = transformer_rectifier();
+            tr.powered_by(vec!(&Powerless {}));
 
-        fn alternating_current() -> Current {
-            Current::Alternating(PowerSource::ApuGenerator, Frequency::new::<hertz>(0.), ElectricPotential::new::<volt>(0.), ElectricCurrent::new::<ampere>(0.))
+            tr.output().is_unpowered();
         }
 
-        fn direct_current() -> Current {
-            Current::Direct(PowerSource::ApuGenerator, ElectricPotential::new::<volt>(0.), ElectricCurrent::new::<ampere>(0.))
-        }
+        #[test]
+        fn without_load_outputs_no_load_voltage() {
+            let mut tr = transformer_rectifier();
+            tr.powered_by(vec!(&apu_generator()));
 
-        fn none_current() -> Current {
-            Current::None
+            assert_eq!(tr.output_voltage(), ElectricPotential::new::<volt>(TransformerRectifier::NO_LOAD_OUTPUT_VOLTAGE));
         }
-    }
-
-    #[cfg(test)]
-    mod contactor_tests {
-        use super::*;
 
         #[test]
-        fn contactor_starts_open() {
-            assert_eq!(contactor().state, ContactorState::Open);
+        fn output_voltage_follows_input_voltage() {
+            let mut tr = transformer_rectifier();
+            tr.powered_by(vec!(&StubAlternatingCurrentSource { voltage: TransformerRectifier::NOMINAL_INPUT_VOLTAGE / 2. }));
+
+            assert_eq!(tr.output_voltage(), ElectricPotential::new::<volt>(TransformerRectifier::NO_LOAD_OUTPUT_VOLTAGE / 2.));
         }
 
         #[test]
-        fn open_contactor_when_toggled_open_stays_open() {
-            let mut contactor = open_contactor();
-            contactor.toggle(false);
+        fn output_voltage_droops_with_load() {
+            let mut tr = transformer_rectifier();
+            tr.powered_by(vec!(&apu_generator()));
+            tr.update_load(&context(Duration::from_millis(1)), amperes(100.));
 
-            assert_eq!(contactor.state, ContactorState::Open);
+            assert!(tr.output_voltage() < ElectricPotential::new::<volt>(TransformerRectifier::NO_LOAD_OUTPUT_VOLTAGE));
         }
 
         #[test]
-        fn open_contactor_when_toggled_closed_closes() {
-            let mut contactor = open_contactor();
-            contactor.toggle(true);
+        fn output_current_equals_load() {
+            let mut tr = transformer_rectifier();
+            tr.powered_by(vec!(&apu_generator()));
+            tr.update_load(&context(Duration::from_millis(1)), amperes(100.));
 
-            assert_eq!(contactor.state, ContactorState::Closed);
+            assert_eq!(tr.output_current(), amperes(100.));
         }
 
         #[test]
-        fn closed_contactor_when_toggled_open_opens() {
-            let mut contactor = closed_contactor();
-            contactor.toggle(false);
+        fn when_unpowered_outputs_no_current() {
+            let mut tr = transformer_rectifier();
+            tr.update_load(&context(Duration::from_millis(1)), amperes(100.));
 
-            assert_eq!(contactor.state, ContactorState::Open);
+            assert_eq!(tr.output_current(), amperes(0.));
         }
 
         #[test]
-        fn closed_contactor_when_toggled_closed_stays_closed() {
-            let mut contactor = closed_contactor();
-            contactor.toggle(true);
+        fn when_overloaded_shorter_than_delay_has_no_overload_fault() {
+            let mut tr = overloaded_for(TransformerRectifier::OVERLOAD_FAULT_DELAY - Duration::from_millis(1));
 
-            assert_eq!(contactor.state, ContactorState::Closed);
+            assert!(!tr.has_overload_fault());
+            tr.update_load(&context(Duration::from_millis(0)), amperes(100.));
+            assert!(tr.output().is_powered());
         }
 
         #[test]
-        fn open_contactor_has_no_output_when_powered_by_nothing() {
-            contactor_has_no_output_when_powered_by_nothing(open_contactor());
+        fn when_overloaded_longer_than_delay_has_overload_fault_and_no_output() {
+            let tr = overloaded_for(TransformerRectifier::OVERLOAD_FAULT_DELAY);
+
+            assert!(tr.has_overload_fault());
+            assert!(tr.output().is_unpowered());
         }
 
         #[test]
-        fn closed_contactor_has_no_output_when_powered_by_nothing() {
-            contactor_has_no_output_when_powered_by_nothing(closed_contactor());
+        fn starts_at_ambient_temperature() {
+            assert_eq!(transformer_rectifier().temperature(), ThermodynamicTemperature::new::<degree_celsius>(TransformerRectifier::AMBIENT_TEMPERATURE_IN_CELSIUS));
         }
 
-        fn contactor_has_no_output_when_powered_by_nothing(mut contactor: Contactor) {
-            let nothing: Vec<&dyn PowerConductor> = vec![];
-            contactor.powered_by(nothing);
+        #[test]
+        fn heats_up_with_load() {
+            let mut tr = transformer_rectifier();
+            tr.powered_by(vec!(&apu_generator()));
+            tr.update_load(&context(Duration::from_secs(60)), amperes(100.));
 
-            assert!(contactor.output().is_unpowered());
+            assert!(tr.temperature() > ThermodynamicTemperature::new::<degree_celsius>(TransformerRectifier::AMBIENT_TEMPERATURE_IN_CELSIUS));
         }
 
         #[test]
-        fn open_contactor_has_no_output_when_powered_by_nothing_which_is_powered() {
-            contactor_has_no_output_when_powered_by_nothing_which_is_powered(open_contactor());
-        }
+        fn when_overheating_heats_up_beyond_rated_load_temperature() {
+            let mut tr = transformer_rectifier();
+            tr.powered_by(vec!(&apu_generator()));
+            let mut failures = Failures::new();
+            failures.activate("TR 1 OVERHEAT");
+            tr.update(&failures);
+            tr.update_load(&context(Duration::from_secs(600)), amperes(0.));
 
-        #[test]
-        fn closed_contactor_has_no_output_when_powered_by_nothing_which_is_powered() {
-            contactor_has_no_output_when_powered_by_nothing_which_is_powered(closed_contactor());
+            assert!(tr.temperature() > ThermodynamicTemperature::new::<degree_celsius>(TransformerRectifier::AMBIENT_TEMPERATURE_IN_CELSIUS +
+                TransformerRectifier::TEMPERATURE_RISE_PER_AMPERE_IN_CELSIUS * TransformerRectifier::RATED_CURRENT_IN_AMPERES));
         }
 
-        fn contactor_has_no_output_when_powered_by_nothing_which_is_powered(mut contactor: Contactor) {
-            contactor.powered_by(vec![&Powerless{}]);
-
-            assert!(contactor.output().is_unpowered());
+        struct StubAlternatingCurrentSource {
+            voltage: f32
         }
 
-        #[test]
-        fn open_contactor_has_no_output_when_powered_by_something() {
-            let mut contactor = open_contactor();
-            let conductors: Vec<&dyn PowerConductor> = vec![&Powerless{}, &StubApuGenerator{}];
-            contactor.powered_by(conductors);
+        impl PowerConductor for StubAlternatingCurrentSource {
+            fn output(&self) -> Current {
+                Current::Alternating(PowerSource::ApuGenerator, Frequency::new::<hertz>(400.),
+                    ElectricPotential::new::<volt>(self.voltage), ElectricCurrent::new::<ampere>(782.60))
+            }
 
-            assert!(contactor.output().is_unpowered());
+            fn id(&self) -> &str {
+                "APU GEN"
+            }
         }
 
-        #[test]
-        fn closed_contactor_has_output_when_powered_by_something_which_is_powered() {
-            let mut contactor = closed_contactor();
-            let conductors: Vec<&dyn PowerConductor> = vec![&Powerless{}, &StubApuGenerator{}];
-            contactor.powered_by(conductors);
+        fn overloaded_for(delta: Duration) -> TransformerRectifier {
+            let mut tr = transformer_rectifier();
+            tr.powered_by(vec!(&apu_generator()));
+            let overload = amperes(TransformerRectifier::RATED_CURRENT_IN_AMPERES + 50.);
+            tr.update_load(&context(Duration::from_millis(0)), overload);
+            tr.update_load(&context(delta), overload);
 
-            assert!(contactor.output().is_powered());
+            tr
         }
 
-        fn contactor() -> Contactor {
-            Contactor::new(String::from("TEST"))
+        fn amperes(value: f32) -> ElectricCurrent {
+            ElectricCurrent::new::<ampere>(value)
         }
 
-        fn open_contactor() -> Contactor {
-            let mut contactor = contactor();
-            contactor.state = ContactorState::Open;
-
-            contactor
+        fn context(delta: Duration) -> UpdateContext {
+            UpdateContext::new(delta)
         }
 
-        fn closed_contactor() -> Contactor {
-            let mut contactor = contactor();
-            contactor.state = ContactorState::Closed;
-
-            contactor
+        fn transformer_rectifier() -> TransformerRectifier {
+            TransformerRectifier::new(String::from("TR 1"))
         }
     }
 
     #[cfg(test)]
-    mod engine_generator_tests {
-        use uom::si::{ratio::percent};
+    mod transformer_rectifier_monitor_tests {
+        use std::time::Duration;
         use super::*;
 
         #[test]
-        fn starts_without_output() {
-            assert!(engine_generator().output.is_unpowered());
+        fn starts_without_fault() {
+            assert!(!TransformerRectifierMonitor::new().has_fault());
         }
 
         #[test]
-        fn when_engine_n2_above_threshold_provides_output() {
-            let mut generator = engine_generator();
-            update_below_threshold(&mut generator);
-            update_above_threshold(&mut generator);
+        fn when_tr_supplies_current_has_no_fault() {
+            let monitor = monitor_for(&loaded_tr(), TransformerRectifierMonitor::UNDERCURRENT_CONFIRMATION_TIME);
 
-            assert!(generator.output.is_powered());
+            assert!(!monitor.has_fault());
         }
 
         #[test]
-        fn when_engine_n2_below_threshold_provides_no_output() {
-            let mut generator = engine_generator();
-            update_above_threshold(&mut generator);
-            update_below_threshold(&mut generator);
+        fn when_tr_undercurrent_shorter_than_confirmation_time_has_no_undercurrent() {
+            let monitor = monitor_for(&failed_tr(), TransformerRectifierMonitor::UNDERCURRENT_CONFIRMATION_TIME - Duration::from_millis(1));
 
-            assert!(generator.output.is_unpowered());
+            assert!(!monitor.has_undercurrent());
         }
 
         #[test]
-        fn when_idg_disconnected_provides_no_output() {
-            let mut generator = engine_generator();
-            generator.update(&engine_above_threshold(), &OnOffPushButton::new_off());
+        fn when_tr_undercurrent_confirmed_has_undercurrent() {
+            let monitor = monitor_for(&failed_tr(), TransformerRectifierMonitor::UNDERCURRENT_CONFIRMATION_TIME);
 
-            assert!(generator.output.is_unpowered());
+            assert!(monitor.has_undercurrent());
+            assert!(monitor.has_fault());
         }
 
-        fn engine_generator() -> EngineGenerator {
-            EngineGenerator::new(1)
+        #[test]
+        fn when_tr_overtemperature_confirmed_has_overtemperature() {
+            let monitor = monitor_for(&overheated_tr(), TransformerRectifierMonitor::OVERTEMPERATURE_CONFIRMATION_TIME);
+
+            assert!(monitor.has_overtemperature());
         }
 
-        fn engine(n2: Ratio) -> Engine {
-            let mut engine = Engine::new();
-            engine.n2 = n2;
+        #[test]
+        fn overtemperature_remains_detected_after_tr_cooled_down() {
+            let mut monitor = monitor_for(&overheated_tr(), TransformerRectifierMonitor::OVERTEMPERATURE_CONFIRMATION_TIME);
+            monitor.update(&context(Duration::from_millis(1)), &loaded_tr());
 
-            engine
+            assert!(monitor.has_overtemperature());
         }
 
-        fn update_above_threshold(generator: &mut EngineGenerator) {
-            generator.update(&engine_above_threshold(), &OnOffPushButton::new_on());
+        fn monitor_for(tr: &TransformerRectifier, delta: Duration) -> TransformerRectifierMonitor {
+            let mut monitor = TransformerRectifierMonitor::new();
+            monitor.update(&context(Duration::from_millis(0)), tr);
+            monitor.update(&context(delta), tr);
+
+            monitor
         }
 
-        fn update_below_threshold(generator: &mut EngineGenerator) {
-            generator.update(&engine_below_threshold(), &OnOffPushButton::new_on());
+        fn loaded_tr() -> TransformerRectifier {
+            let mut tr = TransformerRectifier::new(String::from("TR 1"));
+            tr.powered_by(vec!(&apu_generator()));
+            tr.update_load(&context(Duration::from_millis(1)), ElectricCurrent::new::<ampere>(50.));
+
+            tr
         }
 
-        fn engine_above_threshold() -> Engine {
-            engine(Ratio::new::<percent>(EngineGenerator::ENGINE_N2_POWER_OUTPUT_THRESHOLD + 1.))
+        fn failed_tr() -> TransformerRectifier {
+            let mut tr = loaded_tr();
+            let mut failures = Failures::new();
+            failures.activate("TR 1");
+            tr.update(&failures);
+
+            tr
         }
 
-        fn engine_below_threshold() -> Engine {
-            engine(Ratio::new::<percent>(EngineGenerator::ENGINE_N2_POWER_OUTPUT_THRESHOLD - 1.))
+        fn overheated_tr() -> TransformerRectifier {
+            let mut tr = loaded_tr();
+            let mut failures = Failures::new();
+            failures.activate("TR 1 OVERHEAT");
+            tr.update(&failures);
+            tr.update_load(&context(Duration::from_secs(3_600)), ElectricCurrent::new::<ampere>(50.));
+
+            tr
+        }
+
+        fn context(delta: Duration) -> UpdateContext {
+            UpdateContext::new(delta)
         }
     }
 
     #[cfg(test)]
-    mod apu_generator_tests {
-        use uom::si::{ratio::percent};
+    mod circuit_breaker_tests {
+        use std::time::Duration;
         use super::*;
 
         #[test]
-        fn starts_without_output() {
-            assert!(apu_generator().output.is_unpowered());
+        fn starts_closed() {
+            assert!(circuit_breaker().is_closed());
         }
 
         #[test]
-        fn when_apu_speed_above_threshold_provides_output() {
-            let mut generator = apu_generator();
-            update_below_threshold(&mut generator);
-            update_above_threshold(&mut generator);
+        fn when_closed_has_output_when_powered() {
+            let mut breaker = circuit_breaker();
+            breaker.powered_by(vec!(&apu_generator()));
 
-            assert!(generator.output.is_powered());
+            assert!(breaker.output().is_powered());
         }
 
         #[test]
-        fn when_apu_speed_below_threshold_provides_no_output() {
-            let mut generator = apu_generator();
-            update_above_threshold(&mut generator);
-            update_below_threshold(&mut generator);
+        fn when_pulled_has_no_output() {
+            let mut breaker = circuit_breaker();
+            breaker.powered_by(vec!(&apu_generator()));
+            breaker.pull();
 
-            assert!(generator.output.is_unpowered());
+            assert!(breaker.is_pulled());
+            assert!(breaker.output().is_unpowered());
         }
 
-        fn apu_generator() -> ApuGenerator {
-            ApuGenerator::new()
+        #[test]
+        fn has_no_trip_time_at_rated_current() {
+            assert_eq!(circuit_breaker().trip_time(amperes(5.)), None);
         }
 
-        fn apu(speed: Ratio) -> AuxiliaryPowerUnit {
-            let mut apu = AuxiliaryPowerUnit::new();
-            apu.speed = speed;
+        #[test]
+        fn trips_sooner_with_larger_overcurrent() {
+            let breaker = circuit_breaker();
 
-            apu
+            assert!(breaker.trip_time(amperes(20.)).unwrap() < breaker.trip_time(amperes(10.)).unwrap());
         }
 
-        fn update_above_threshold(generator: &mut ApuGenerator) {
-            generator.update(&apu(Ratio::new::<percent>(ApuGenerator::APU_SPEED_POWER_OUTPUT_THRESHOLD + 1.)));
-        }
+        #[test]
+        fn does_not_trip_at_rated_current() {
+            let mut breaker = powered_circuit_breaker();
+            breaker.update(&context(Duration::from_secs(3_600)), amperes(5.));
 
-        fn update_below_threshold(generator: &mut ApuGenerator) {
-            generator.update(&apu(Ratio::new::<percent>(ApuGenerator::APU_SPEED_POWER_OUTPUT_THRESHOLD - 1.)));
+            assert!(breaker.is_closed());
         }
-    }
 
-    #[cfg(test)]
-    mod external_power_source_tests {
-        use super::*;
+        #[test]
+        fn does_not_trip_with_overcurrent_before_trip_time() {
+            let mut breaker = powered_circuit_breaker();
+            let trip_time = breaker.trip_time(amperes(10.)).unwrap();
+            breaker.update(&context(trip_time.mul_f32(0.9)), amperes(10.));
+
+            assert!(breaker.is_closed());
+        }
 
         #[test]
-        fn starts_without_output() {
-            assert!(external_power_source().output().is_unpowered());
+        fn trips_with_overcurrent_after_trip_time() {
+            let mut breaker = powered_circuit_breaker();
+            let trip_time = breaker.trip_time(amperes(10.)).unwrap();
+            breaker.update(&context(trip_time.mul_f32(1.01)), amperes(10.));
+
+            assert!(breaker.is_tripped());
+            assert!(breaker.output().is_unpowered());
         }
 
         #[test]
-        fn when_plugged_in_provides_output() {
-            let mut ext_pwr = external_power_source();
-            ext_pwr.plugged_in = true;
+        fn cools_down_when_overcurrent_stops() {
+            let mut breaker = powered_circuit_breaker();
+            let trip_time = breaker.trip_time(amperes(10.)).unwrap();
+            breaker.update(&context(trip_time.mul_f32(0.9)), amperes(10.));
+            breaker.update(&context(Duration::from_secs_f32(CircuitBreaker::COOLING_TIME_IN_SECONDS)), amperes(1.));
+            breaker.update(&context(trip_time.mul_f32(0.9)), amperes(10.));
 
-            assert!(ext_pwr.output().is_powered());
+            assert!(breaker.is_closed());
         }
 
         #[test]
-        fn when_not_plugged_in_provides_no_output() {
-            let mut ext_pwr = external_power_source();
-            ext_pwr.plugged_in = false;
+        fn when_reset_after_tripping_closes() {
+            let mut breaker = powered_circuit_breaker();
+            breaker.update(&context(Duration::from_secs(60)), amperes(10.));
+            breaker.reset();
 
-            assert!(ext_pwr.output().is_unpowered());
+            assert!(breaker.is_closed());
+            assert!(breaker.output().is_powered());
         }
 
-        fn external_power_source() -> ExternalPowerSource {
-            ExternalPowerSource::new()
+        fn circuit_breaker() -> CircuitBreaker {
+            CircuitBreaker::new(String::from("49VU"), String::from("A01"), amperes(5.))
+        }
+
+        fn powered_circuit_breaker() -> CircuitBreaker {
+            let mut breaker = circuit_breaker();
+            breaker.powered_by(vec!(&apu_generator()));
+
+            breaker
+        }
+
+        fn amperes(value: f32) -> ElectricCurrent {
+            ElectricCurrent::new::<ampere>(value)
+        }
+
+        fn context(delta: Duration) -> UpdateContext {
+            UpdateContext::new(delta)
         }
     }
 
     #[cfg(test)]
-    mod transformer_rectifier_tests {
+    mod power_consumer_tests {
         use super::*;
 
         #[test]
-        fn starts_without_output() {
-            assert!(transformer_rectifier().output().is_unpowered());
-        }
-
-        #[test]
-        fn when_powered_with_alternating_current_outputs_direct_current() {
-            let mut tr = transformer_rectifier();
-            tr.powered_by(vec!(&apu_generator()));
-
-            assert!(tr.output().is_powered());
-            assert!(if let Current::Direct(PowerSource::ApuGenerator, ..) = tr.output() { true } else { false });
+        fn when_unpowered_draws_no_load() {
+            assert_eq!(power_consumer().load(), ElectricCurrent::new::<ampere>(0.));
         }
 
         #[test]
-        fn when_powered_with_alternating_current_but_failed_has_no_output() {
-            let mut tr = transformer_rectifier();
-            tr.powered_by(vec!(&apu_generator()));
-            tr.fail();
+        fn when_powered_draws_its_demand() {
+            let mut consumer = power_consumer();
+            consumer.powered_by(vec!(&apu_generator()));
 
-            assert!(tr.output().is_unpowered());
+            assert!(consumer.is_powered());
+            assert_eq!(consumer.load(), ElectricCurrent::new::<ampere>(2.));
         }
 
         #[test]
-        fn when_unpowered_has_no_output() {
-            let mut tr = transformer_rectifier();
-            tr.powered_by(vec!(&Powerless {}));
+        fn when_short_circuited_draws_a_multiple_of_its_demand() {
+            let mut consumer = power_consumer();
+            consumer.powered_by(vec!(&apu_generator()));
+            let mut failures = Failures::new();
+            failures.activate("TEST SHORT CIRCUIT");
+            consumer.update(&failures);
 
-            tr.output().is_unpowered();
+            assert_eq!(consumer.load(), ElectricCurrent::new::<ampere>(2. * PowerConsumer::SHORT_CIRCUIT_DEMAND_FACTOR));
         }
 
-        fn transformer_rectifier() -> TransformerRectifier {
-            TransformerRectifier::new()
+        fn power_consumer() -> PowerConsumer {
+            PowerConsumer::new(String::from("TEST"), ElectricCurrent::new::<ampere>(2.))
         }
     }
 
@@ -865,6 +2881,16 @@ mod tests {
             assert!(battery.output().is_unpowered());
         }
 
+        #[test]
+        fn charged_battery_without_input_has_its_own_potential() {
+            assert_eq!(full_battery().potential(), ElectricPotential::new::<volt>(28.5));
+        }
+
+        #[test]
+        fn empty_battery_without_input_has_no_potential() {
+            assert_eq!(empty_battery().potential(), ElectricPotential::new::<volt>(0.));
+        }
+
         #[test]
         fn charged_battery_without_input_has_output() {
             let mut battery = full_battery();
@@ -881,6 +2907,45 @@ mod tests {
             assert!(battery.output().is_unpowered());
         }
 
+        #[test]
+        fn battery_supplying_a_load_discharges() {
+            let mut battery = full_battery();
+            battery.update(&context(Duration::from_secs(360)), amperes(10.));
+
+            assert_eq!(battery.current(), amperes(-10.));
+            assert!(matches!(battery.output(), Current::Direct(PowerSource::Battery(1), _, current) if current == amperes(10.)));
+            assert!((battery.charge().get::<ampere_hour>() - 22.).abs() < 0.001);
+        }
+
+        #[test]
+        fn powered_empty_battery_charges_at_the_maximum_current() {
+            let mut battery = empty_battery();
+            battery.powered_by(vec!(&apu_generator()));
+            battery.update(&context(Duration::from_secs(60)), amperes(0.));
+
+            assert_eq!(battery.current(), amperes(Battery::MAX_CHARGING_CURRENT_IN_AMPERES));
+            assert!((battery.charge().get::<ampere_hour>() - 1.).abs() < 0.001);
+        }
+
+        #[test]
+        fn powered_full_battery_has_no_current() {
+            let mut battery = full_battery();
+            battery.powered_by(vec!(&apu_generator()));
+            battery.update(&context(Duration::from_secs(60)), amperes(0.));
+
+            assert_eq!(battery.current(), amperes(0.));
+            assert!(battery.is_full());
+        }
+
+        #[test]
+        fn empty_battery_cannot_discharge() {
+            let mut battery = empty_battery();
+            battery.update(&context(Duration::from_secs(60)), amperes(10.));
+
+            assert_eq!(battery.current(), amperes(0.));
+            assert!(battery.is_empty());
+        }
+
         fn full_battery() -> Battery {
             Battery::full(1)
         }
@@ -888,5 +2953,13 @@ mod tests {
         fn empty_battery() -> Battery {
             Battery::empty(1)
         }
+
+        fn amperes(value: f32) -> ElectricCurrent {
+            ElectricCurrent::new::<ampere>(value)
+        }
+
+        fn context(delta: Duration) -> UpdateContext {
+            UpdateContext::new(delta)
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,14 @@
+#![allow(clippy::new_without_default)]
+
+pub mod shared;
+pub mod a320;
+pub mod a321;
+pub mod console;
+pub mod ecam;
+pub mod electrical;
+pub mod overhead;
+pub mod replay;
+pub mod scenario;
+pub mod simulator;
+pub mod snapshot;
+pub mod timeseries;
@@ -1,15 +1,195 @@
-use electrical::{AuxiliaryPowerUnit, ExternalPowerSource};
-use a320::{A320ElectricalCircuit, A320ElectricalOverheadPanel, A320HydraulicCircuit};
-use shared::{Engine, UpdateContext};
-use std::time::Duration;
+use airbus_systems::{
+    a320::A320,
+    a321::A321,
+    console::{Command, Console, HELP, Synoptic},
+    scenario::{Scenario, ScenarioAircraft, ScenarioError},
+    timeseries::TimeSeries
+};
+use std::{env, fs, io::{self, BufRead, Write}, process, time::Duration};
 
-mod shared;
-mod a320;
-mod electrical;
-mod overhead;
+const USAGE: &str = "Usage: airbus-systems <SCENARIO FILE> [--aircraft a320|a321] [--duration 10s] [--step 1ms] \
+[--format csv|json] [--columns NAME,NAME] [--output FILE]
+       airbus-systems --interactive [--aircraft a320|a321]
+
+Runs the scenario and continues until the duration passed, writing the selected outputs after every step.
+Outputs: {BUS} SOURCE, {BUS} PATH, {GEN} LOAD, BAT {n} CHARGE, BAT {n} POTENTIAL, {TR} CURRENT and {CONTACTOR} CLOSED.
+All outputs are written when no columns are given.
+In interactive mode commands are read from the terminal, type help for a list of commands.";
+
+enum Format {
+    Csv,
+    Json
+}
+
+struct Options {
+    interactive: bool,
+    scenario_file: String,
+    aircraft: String,
+    duration: Duration,
+    step: Duration,
+    format: Format,
+    columns: Option<Vec<String>>,
+    output: Option<String>
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut scenario_file = None;
+        let mut options = Options {
+            interactive: false,
+            scenario_file: String::new(),
+            aircraft: String::from("a320"),
+            duration: Duration::from_secs(0),
+            step: Scenario::TICK,
+            format: Format::Csv,
+            columns: None,
+            output: None
+        };
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                if scenario_file.replace(arg).is_some() {
+                    return Err(String::from("Only one scenario file can be given."));
+                }
+                continue;
+            }
+            if arg == "--interactive" {
+                options.interactive = true;
+                continue;
+            }
+
+            let value = args.next().ok_or_else(|| format!("{} requires a value.", arg))?;
+            match arg.as_str() {
+                "--aircraft" => options.aircraft = value.to_lowercase(),
+                "--duration" => options.duration = Scenario::parse_duration(&value)?,
+                "--step" => {
+                    options.step = Scenario::parse_duration(&value)?;
+                    if options.step == Duration::from_secs(0) {
+                        return Err(String::from("The step must be longer than zero."));
+                    }
+                },
+                "--format" => options.format = match value.to_lowercase().as_str() {
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    _ => return Err(format!("'{}' is not a format, use csv or json.", value))
+                },
+                "--columns" => options.columns = Some(value.split(',').map(|column| String::from(column.trim())).collect()),
+                "--output" => options.output = Some(value),
+                _ => return Err(format!("'{}' is not an option.", arg))
+            }
+        }
+
+        match scenario_file {
+            Some(_) if options.interactive => return Err(String::from("A scenario file cannot be run interactively.")),
+            Some(scenario_file) => options.scenario_file = scenario_file,
+            None if options.interactive => {},
+            None => return Err(String::from("A scenario file is required."))
+        }
+        Ok(options)
+    }
+}
+
+fn run<T: ScenarioAircraft>(aircraft: T, scenario: &Scenario, options: &Options) -> Result<(TimeSeries, Option<ScenarioError>), String> {
+    let available: Vec<String> = aircraft.sample().into_iter().map(|(name, _)| name).collect();
+    let columns = match &options.columns {
+        Some(columns) => {
+            if let Some(unknown) = columns.iter().find(|column| !available.contains(column)) {
+                return Err(format!("'{}' is not an output.", unknown));
+            }
+            columns.clone()
+        },
+        None => available
+    };
+
+    let mut time_series = TimeSeries::new(columns);
+    let result = scenario.run_with(aircraft, options.step, options.duration, &mut |simulation| {
+        time_series.record(simulation.simulation_time(), simulation.aircraft().sample());
+    });
+
+    Ok((time_series, result.err()))
+}
+
+fn interact<T: ScenarioAircraft>(aircraft: T) {
+    let mut console = Console::new(aircraft);
+    println!("{}\n\n{}", HELP, console.synoptic(Synoptic::All));
+
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().ok();
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match Command::parse(&line) {
+            Ok(Command::Quit) => break,
+            Ok(Command::Help) => println!("{}", HELP),
+            Ok(Command::Show(synoptic)) => println!("{}", console.synoptic(synoptic)),
+            Ok(command) => match console.execute(&command) {
+                Ok(()) => println!("{}", console.synoptic(Synoptic::All)),
+                Err(message) => println!("{}", message)
+            },
+            Err(message) => println!("{}", message)
+        }
+    }
+}
 
 fn main() {
-    let mut circuit = A320ElectricalCircuit::new();
-    circuit.update(&UpdateContext::new(Duration::new(1, 0)), &Engine::new(), &Engine::new(), &AuxiliaryPowerUnit::new(), &ExternalPowerSource::new(),
-        &A320HydraulicCircuit::new(), &A320ElectricalOverheadPanel::new());
+    let options = Options::parse(env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("{}\n\n{}", message, USAGE);
+        process::exit(2);
+    });
+
+    if options.interactive {
+        match options.aircraft.as_str() {
+            "a320" => interact(A320::new()),
+            "a321" => interact(A321::new()),
+            _ => {
+                eprintln!("'{}' is not an aircraft, use a320 or a321.\n\n{}", options.aircraft, USAGE);
+                process::exit(2);
+            }
+        }
+        return;
+    }
+
+    let text = fs::read_to_string(&options.scenario_file).unwrap_or_else(|error| {
+        eprintln!("Cannot read {}: {}", options.scenario_file, error);
+        process::exit(2);
+    });
+    let scenario = Scenario::parse(&text).unwrap_or_else(|error| {
+        eprintln!("{}: {}", options.scenario_file, error);
+        process::exit(2);
+    });
+
+    let result = match options.aircraft.as_str() {
+        "a320" => run(A320::new(), &scenario, &options),
+        "a321" => run(A321::new(), &scenario, &options),
+        _ => Err(format!("'{}' is not an aircraft, use a320 or a321.", options.aircraft))
+    };
+    let (time_series, scenario_error) = result.unwrap_or_else(|message| {
+        eprintln!("{}\n\n{}", message, USAGE);
+        process::exit(2);
+    });
+
+    let output = match options.format {
+        Format::Csv => time_series.to_csv(),
+        Format::Json => time_series.to_json()
+    };
+    match &options.output {
+        Some(file) => fs::write(file, output).unwrap_or_else(|error| {
+            eprintln!("Cannot write {}: {}", file, error);
+            process::exit(2);
+        }),
+        None => print!("{}", output)
+    }
+
+    if let Some(error) = scenario_error {
+        eprintln!("{}: {}", options.scenario_file, error);
+        process::exit(1);
+    }
 }
@@ -1,3 +1,6 @@
+use crate::snapshot::{StateVisitor, Stateful, visit_variant};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum OnOffPushButtonState {
     On,
     Off
@@ -35,14 +38,39 @@ impl OnOffPushButton {
     }
 
     pub fn is_on(&self) -> bool {
-        if let OnOffPushButtonState::On = self.state { true } else { false }
+        matches!(self.state, OnOffPushButtonState::On)
     }
 
     pub fn is_off(&self) -> bool {
-        if let OnOffPushButtonState::Off = self.state { true } else { false }
+        matches!(self.state, OnOffPushButtonState::Off)
+    }
+
+    pub fn has_fault(&self) -> bool {
+        self.fault
+    }
+
+    pub fn set_fault(&mut self, fault: bool) {
+        self.fault = fault;
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.available
+    }
+
+    pub fn set_available(&mut self, available: bool) {
+        self.available = available;
     }
 }
 
+impl Stateful for OnOffPushButton {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_variant(state, "STATE", &mut self.state, &[OnOffPushButtonState::On, OnOffPushButtonState::Off]);
+        state.visit_bool("FAULT", &mut self.fault);
+        state.visit_bool("AVAILABLE", &mut self.available);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum NormalAltnPushButtonState {
     Normal,
     Altn
@@ -77,11 +105,26 @@ impl NormalAltnPushButton {
     }
 
     pub fn is_normal(&self) -> bool {
-        if let NormalAltnPushButtonState::Normal = self.state { true } else { false }
+        matches!(self.state, NormalAltnPushButtonState::Normal)
     }
 
     pub fn is_altn(&self) -> bool {
-        if let NormalAltnPushButtonState::Altn = self.state { true } else { false }
+        matches!(self.state, NormalAltnPushButtonState::Altn)
+    }
+
+    pub fn has_fault(&self) -> bool {
+        self.fault
+    }
+
+    pub fn set_fault(&mut self, fault: bool) {
+        self.fault = fault;
+    }
+}
+
+impl Stateful for NormalAltnPushButton {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_variant(state, "STATE", &mut self.state, &[NormalAltnPushButtonState::Normal, NormalAltnPushButtonState::Altn]);
+        state.visit_bool("FAULT", &mut self.fault);
     }
 }
 
@@ -0,0 +1,534 @@
+use std::{collections::BTreeMap, error::Error, fmt, time::Duration};
+
+use crate::{shared::{Aircraft, Failures, Simulation}, simulator::SimulatorReaderWriter, snapshot::{Snapshot, SnapshotError, escape, unescape}};
+
+/// A failure injected into the aircraft's systems before a tick.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FailureInjection {
+    Activate(String),
+    ActivateAt(String, Duration),
+    ActivateRandomly(String, Duration),
+    Deactivate(String)
+}
+
+impl FailureInjection {
+    fn apply(&self, failures: &mut Failures) {
+        match self {
+            FailureInjection::Activate(id) => failures.activate(id),
+            FailureInjection::ActivateAt(id, time) => failures.activate_at(id, *time),
+            FailureInjection::ActivateRandomly(id, mean_time_between_failures) => failures.activate_randomly(id, *mean_time_between_failures),
+            FailureInjection::Deactivate(id) => failures.deactivate(id)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct RecordedTick {
+    delta: Duration,
+    /// The state the simulation was changed to since the previous tick, if it was changed.
+    state: Option<Snapshot>,
+    failures: Vec<FailureInjection>,
+    /// The inputs read during the tick, `None` when the simulator did not have the variable.
+    inputs: BTreeMap<String, Option<f64>>,
+    outputs: BTreeMap<String, f64>
+}
+
+/// Records the ticks of a simulation: the delta of each tick, the failures injected before it,
+/// the simulation variables read as inputs and those written as outputs. The simulation can
+/// also be changed directly between ticks, e.g. by pulling a circuit breaker, acknowledging the
+/// master warning or activating a failure through the aircraft's failures. The recorder detects
+/// such changes before the next tick and records the state they resulted in.
+pub struct Recorder {
+    recording: Recording,
+    pending_failures: Vec<FailureInjection>,
+    /// The state of the simulation after the last tick.
+    state: Snapshot
+}
+
+impl Recorder {
+    /// Starts recording the given simulation from its current state.
+    pub fn start<T: Aircraft>(simulation: &mut Simulation<T>) -> Recorder {
+        let state = Snapshot::save(simulation);
+        Recorder {
+            recording: Recording {
+                max_step: simulation.max_step(),
+                initial_state: state.clone(),
+                ticks: vec![]
+            },
+            pending_failures: vec![],
+            state
+        }
+    }
+
+    /// Injects the failure into the aircraft's systems before the next tick.
+    pub fn inject(&mut self, failure: FailureInjection) {
+        self.pending_failures.push(failure);
+    }
+
+    pub fn tick<T: Aircraft>(&mut self, simulation: &mut Simulation<T>, delta: Duration, reader_writer: &mut dyn SimulatorReaderWriter) {
+        let state = Snapshot::save(simulation);
+        let changed_state = if state != self.state { Some(state) } else { None };
+
+        let failures = std::mem::take(&mut self.pending_failures);
+        for failure in failures.iter() {
+            failure.apply(simulation.aircraft_mut().failures_mut());
+        }
+
+        let mut recording_reader_writer = RecordingReaderWriter { reader_writer, inputs: BTreeMap::new(), outputs: BTreeMap::new() };
+        simulation.tick(delta, &mut recording_reader_writer);
+
+        self.state = Snapshot::save(simulation);
+        self.recording.ticks.push(RecordedTick {
+            delta,
+            state: changed_state,
+            failures,
+            inputs: recording_reader_writer.inputs,
+            outputs: recording_reader_writer.outputs
+        });
+    }
+
+    pub fn finish(self) -> Recording {
+        self.recording
+    }
+}
+
+struct RecordingReaderWriter<'a> {
+    reader_writer: &'a mut dyn SimulatorReaderWriter,
+    inputs: BTreeMap<String, Option<f64>>,
+    outputs: BTreeMap<String, f64>
+}
+
+impl<'a> SimulatorReaderWriter for RecordingReaderWriter<'a> {
+    fn read(&mut self, name: &str) -> Option<f64> {
+        let value = self.reader_writer.read(name);
+        self.inputs.insert(String::from(name), value);
+
+        value
+    }
+
+    fn write(&mut self, name: &str, value: f64) {
+        self.reader_writer.write(name, value);
+        self.outputs.insert(String::from(name), value);
+    }
+}
+
+/// Serves the recorded inputs of a tick and collects the outputs written during the replay.
+struct ReplayReaderWriter<'a> {
+    inputs: &'a BTreeMap<String, Option<f64>>,
+    unrecorded_input: Option<String>,
+    outputs: BTreeMap<String, f64>
+}
+
+impl<'a> SimulatorReaderWriter for ReplayReaderWriter<'a> {
+    fn read(&mut self, name: &str) -> Option<f64> {
+        match self.inputs.get(name) {
+            Some(value) => *value,
+            None => {
+                self.unrecorded_input.get_or_insert_with(|| String::from(name));
+                None
+            }
+        }
+    }
+
+    fn write(&mut self, name: &str, value: f64) {
+        self.outputs.insert(String::from(name), value);
+    }
+}
+
+/// An output which differs between a replay and its recording. A missing value means
+/// the output was not written.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    /// The index of the tick, starting at 0.
+    pub tick: usize,
+    /// The simulation time at the end of the tick.
+    pub time: Duration,
+    pub name: String,
+    pub recorded: Option<f64>,
+    pub replayed: Option<f64>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplayError {
+    /// The recording's initial state cannot be restored into the simulation.
+    Snapshot(SnapshotError),
+    /// The simulation read an input during the given tick which was not recorded.
+    UnrecordedInput { tick: usize, name: String },
+    Diverged(Divergence)
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Snapshot(error) => write!(f, "The initial state cannot be restored: {}", error),
+            ReplayError::UnrecordedInput { tick, name } => write!(f, "Tick {} read input {} which was not recorded.", tick, name),
+            ReplayError::Diverged(divergence) => write!(f, "Tick {} at {:?} diverged on {}: recorded {:?}, replayed {:?}.",
+                divergence.tick, divergence.time, divergence.name, divergence.recorded, divergence.replayed)
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+/// A recording of a simulation, starting with the simulation's state when recording started.
+/// Recordings are stored as versioned text, with values stored as their bits such that
+/// a replay is exact.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recording {
+    /// The step by which the recorded simulation advanced its aircraft.
+    max_step: Duration,
+    initial_state: Snapshot,
+    ticks: Vec<RecordedTick>
+}
+
+impl Recording {
+    pub const VERSION: u32 = 5;
+    const HEADER: &'static str = "AIRBUS SYSTEMS RECORDING";
+
+    pub fn ticks(&self) -> usize {
+        self.ticks.len()
+    }
+
+    pub fn max_step(&self) -> Duration {
+        self.max_step
+    }
+
+    /// Replays the recording in the given simulation, after restoring the recording's initial state
+    /// and max step. The replay stops at the first tick whose outputs diverge from the recorded outputs.
+    pub fn replay<T: Aircraft>(&self, simulation: &mut Simulation<T>) -> Result<(), ReplayError> {
+        self.initial_state.restore(simulation).map_err(ReplayError::Snapshot)?;
+        simulation.set_max_step(self.max_step);
+
+        for (index, tick) in self.ticks.iter().enumerate() {
+            if let Some(state) = &tick.state {
+                state.restore(simulation).map_err(ReplayError::Snapshot)?;
+            }
+
+            for failure in tick.failures.iter() {
+                failure.apply(simulation.aircraft_mut().failures_mut());
+            }
+
+            let mut replay_reader_writer = ReplayReaderWriter { inputs: &tick.inputs, unrecorded_input: None, outputs: BTreeMap::new() };
+            simulation.tick(tick.delta, &mut replay_reader_writer);
+
+            if let Some(name) = replay_reader_writer.unrecorded_input {
+                return Err(ReplayError::UnrecordedInput { tick: index, name });
+            }
+
+            if let Some(name) = Recording::first_diverging_output(&tick.outputs, &replay_reader_writer.outputs) {
+                return Err(ReplayError::Diverged(Divergence {
+                    tick: index,
+                    time: simulation.simulation_time(),
+                    recorded: tick.outputs.get(&name).copied(),
+                    replayed: replay_reader_writer.outputs.get(&name).copied(),
+                    name
+                }));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Outputs are compared by their bits, such that the slightest difference is detected.
+    fn first_diverging_output(recorded: &BTreeMap<String, f64>, replayed: &BTreeMap<String, f64>) -> Option<String> {
+        recorded.keys().chain(replayed.keys()).filter(|name| {
+            recorded.get(*name).map(|value| value.to_bits()) != replayed.get(*name).map(|value| value.to_bits())
+        }).min().cloned()
+    }
+
+    pub fn to_text(&self) -> String {
+        let snapshot = self.initial_state.to_text();
+        let mut text = format!("{} {}\nMAX STEP\t{}\nSNAPSHOT\t{}\n{}", Recording::HEADER, Recording::VERSION, self.max_step.as_nanos(),
+            snapshot.lines().count(), snapshot);
+
+        for tick in self.ticks.iter() {
+            text.push_str(&format!("TICK\t{}\n", tick.delta.as_nanos()));
+            if let Some(state) = &tick.state {
+                let state = state.to_text();
+                text.push_str(&format!("STATE\t{}\n{}", state.lines().count(), state));
+            }
+            for failure in tick.failures.iter() {
+                text.push_str(&match failure {
+                    FailureInjection::Activate(id) => format!("ACTIVATE\t{}\n", escape(id)),
+                    FailureInjection::ActivateAt(id, time) => format!("ACTIVATE AT\t{}\t{}\n", escape(id), time.as_nanos()),
+                    FailureInjection::ActivateRandomly(id, mean_time_between_failures) =>
+                        format!("ACTIVATE RANDOMLY\t{}\t{}\n", escape(id), mean_time_between_failures.as_nanos()),
+                    FailureInjection::Deactivate(id) => format!("DEACTIVATE\t{}\n", escape(id))
+                });
+            }
+            for (name, value) in tick.inputs.iter() {
+                text.push_str(&match value {
+                    Some(value) => format!("IN\t{}\t{:016x}\n", escape(name), value.to_bits()),
+                    None => format!("MISSING\t{}\n", escape(name))
+                });
+            }
+            for (name, value) in tick.outputs.iter() {
+                text.push_str(&format!("OUT\t{}\t{:016x}\n", escape(name), value.to_bits()));
+            }
+        }
+
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Recording, RecordingError> {
+        let mut lines = text.lines().enumerate().map(|(index, line)| (index + 1, line));
+        let version = lines.next().and_then(|(_, header)| header.strip_prefix(Recording::HEADER))
+            .and_then(|version| version.trim().parse::<u32>().ok())
+            .ok_or(RecordingError::NotARecording)?;
+        if version != Recording::VERSION {
+            return Err(RecordingError::UnsupportedVersion(version));
+        }
+
+        let (line_number, max_step) = lines.next().ok_or(RecordingError::Malformed(2))?;
+        let max_step = max_step.strip_prefix("MAX STEP\t").and_then(Recording::parse_duration).filter(|max_step| *max_step > Duration::from_secs(0))
+            .ok_or(RecordingError::Malformed(line_number))?;
+
+        let (line_number, snapshot_header) = lines.next().ok_or(RecordingError::Malformed(3))?;
+        let snapshot_lines = snapshot_header.strip_prefix("SNAPSHOT\t").and_then(|count| count.parse::<usize>().ok())
+            .ok_or(RecordingError::Malformed(line_number))?;
+        let initial_state = Recording::parse_snapshot(&mut lines, snapshot_lines)?;
+
+        let mut ticks: Vec<RecordedTick> = vec![];
+        while let Some((line_number, line)) = lines.next() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let malformed = RecordingError::Malformed(line_number);
+            if let ["TICK", delta] = fields[..] {
+                ticks.push(RecordedTick {
+                    delta: Recording::parse_duration(delta).ok_or(malformed)?,
+                    state: None,
+                    failures: vec![],
+                    inputs: BTreeMap::new(),
+                    outputs: BTreeMap::new()
+                });
+                continue;
+            }
+
+            let tick = ticks.last_mut().ok_or_else(|| malformed.clone())?;
+            match fields[..] {
+                ["STATE", count] if tick.state.is_none() => {
+                    let state_lines = count.parse::<usize>().map_err(|_| malformed)?;
+                    tick.state = Some(Recording::parse_snapshot(&mut lines, state_lines)?);
+                },
+                ["ACTIVATE", id] => tick.failures.push(FailureInjection::Activate(unescape(id).ok_or(malformed)?)),
+                ["ACTIVATE AT", id, time] => tick.failures.push(FailureInjection::ActivateAt(unescape(id).ok_or_else(|| malformed.clone())?,
+                    Recording::parse_duration(time).ok_or(malformed)?)),
+                ["ACTIVATE RANDOMLY", id, time] => tick.failures.push(FailureInjection::ActivateRandomly(unescape(id).ok_or_else(|| malformed.clone())?,
+                    Recording::parse_duration(time).ok_or(malformed)?)),
+                ["DEACTIVATE", id] => tick.failures.push(FailureInjection::Deactivate(unescape(id).ok_or(malformed)?)),
+                ["IN", name, bits] => {
+                    tick.inputs.insert(unescape(name).ok_or_else(|| malformed.clone())?, Some(Recording::parse_value(bits).ok_or(malformed)?));
+                },
+                ["MISSING", name] => {
+                    tick.inputs.insert(unescape(name).ok_or(malformed)?, None);
+                },
+                ["OUT", name, bits] => {
+                    tick.outputs.insert(unescape(name).ok_or_else(|| malformed.clone())?, Recording::parse_value(bits).ok_or(malformed)?);
+                },
+                _ => return Err(malformed)
+            }
+        }
+
+        Ok(Recording {
+            max_step,
+            initial_state,
+            ticks
+        })
+    }
+
+    fn parse_snapshot<'a>(lines: &mut impl Iterator<Item = (usize, &'a str)>, count: usize) -> Result<Snapshot, RecordingError> {
+        let snapshot: Vec<&str> = lines.take(count).map(|(_, line)| line).collect();
+        Snapshot::from_text(&snapshot.join("\n")).map_err(RecordingError::Snapshot)
+    }
+
+    fn parse_duration(nanos: &str) -> Option<Duration> {
+        nanos.parse::<u64>().ok().map(Duration::from_nanos)
+    }
+
+    fn parse_value(bits: &str) -> Option<f64> {
+        u64::from_str_radix(bits, 16).ok().map(f64::from_bits)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordingError {
+    /// The text does not start with the recording header.
+    NotARecording,
+    UnsupportedVersion(u32),
+    /// The line with the given number cannot be parsed.
+    Malformed(usize),
+    Snapshot(SnapshotError)
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordingError::NotARecording => write!(f, "The text is not a recording."),
+            RecordingError::UnsupportedVersion(version) => write!(f, "Recording version {} is not supported.", version),
+            RecordingError::Malformed(line) => write!(f, "Line {} of the recording is malformed.", line),
+            RecordingError::Snapshot(error) => write!(f, "The recording's initial state is invalid: {}", error)
+        }
+    }
+}
+
+impl Error for RecordingError {}
+
+#[cfg(test)]
+mod replay_tests {
+    use crate::{a320::A320, a321::A321, ecam::FlightPhase, simulator::InMemorySimulatorReaderWriter};
+
+    use super::*;
+
+    fn record() -> Recording {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        reader_writer.write("TURB ENG CORRECTED N2:1", 80.);
+
+        let mut simulation = Simulation::with_max_step(A320::new(), Duration::from_millis(1));
+        let mut recorder = Recorder::start(&mut simulation);
+        for tick in 0..300 {
+            match tick {
+                100 => reader_writer.write("TURB ENG CORRECTED N2:2", 80.),
+                150 => recorder.inject(FailureInjection::Activate(String::from("TR 1"))),
+                200 => recorder.inject(FailureInjection::ActivateRandomly(String::from("AC BUS 2"), Duration::from_millis(50))),
+                _ => {}
+            }
+            recorder.tick(&mut simulation, Duration::from_millis(1), &mut reader_writer);
+        }
+
+        recorder.finish()
+    }
+
+    /// Records changes made directly to the aircraft between ticks, rather than through the recorder.
+    fn record_with_changes_between_ticks() -> (Recording, Simulation<A320>) {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        reader_writer.write("TURB ENG CORRECTED N2:1", 80.);
+        reader_writer.write("TURB ENG CORRECTED N2:2", 80.);
+
+        let mut simulation = Simulation::new(A320::new());
+        let mut recorder = Recorder::start(&mut simulation);
+        for tick in 0..300 {
+            let a320 = simulation.aircraft_mut();
+            match tick {
+                20 => a320.set_flight_phase(FlightPhase::AboveFifteenHundredFeet),
+                100 => a320.failures_mut().activate("IDG 1 OIL LEAK"),
+                150 => a320.attention_getters_mut().acknowledge_master_caution(),
+                180 => {
+                    a320.failures_mut().activate("AC BUS 1");
+                    a320.failures_mut().activate("AC BUS 2");
+                },
+                220 => a320.attention_getters_mut().acknowledge_master_warning(),
+                240 => a320.electrical_mut().pull_circuit_breaker("121VU", "M07"),
+                270 => a320.electrical_mut().reset_circuit_breaker("121VU", "M07"),
+                _ => {}
+            }
+            recorder.tick(&mut simulation, Duration::from_millis(1), &mut reader_writer);
+        }
+
+        (recorder.finish(), simulation)
+    }
+
+    #[test]
+    fn replay_reproduces_changes_made_between_ticks() {
+        let (recording, mut recorded) = record_with_changes_between_ticks();
+        let mut replayed = Simulation::new(A320::new());
+
+        assert_eq!(recording.ticks.iter().filter(|tick| tick.state.is_some()).count(), 7);
+        assert_eq!(recording.replay(&mut replayed), Ok(()));
+        assert_eq!(Snapshot::save(&mut replayed), Snapshot::save(&mut recorded));
+    }
+
+    #[test]
+    fn text_with_changes_between_ticks_round_trips() {
+        let (recording, _) = record_with_changes_between_ticks();
+
+        assert_eq!(Recording::from_text(&recording.to_text()), Ok(recording));
+    }
+
+    #[test]
+    fn replay_of_recording_does_not_diverge() {
+        let recording = record();
+
+        assert_eq!(recording.ticks(), 300);
+        assert_eq!(recording.replay(&mut Simulation::new(A320::new())), Ok(()));
+    }
+
+    #[test]
+    fn replay_advances_the_aircraft_by_the_recorded_max_step() {
+        let recording = record();
+        let mut simulation = Simulation::new(A320::new());
+
+        assert_eq!(recording.max_step(), Duration::from_millis(1));
+        assert_eq!(recording.replay(&mut simulation), Ok(()));
+        assert_eq!(simulation.max_step(), Duration::from_millis(1));
+    }
+
+    #[test]
+    fn replay_restores_initial_state_of_recording() {
+        let mut simulation = Simulation::new(A320::new());
+        simulation.aircraft_mut().failures_mut().activate("AC BUS 1");
+
+        assert_eq!(record().replay(&mut simulation), Ok(()));
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let recording = record();
+
+        assert_eq!(Recording::from_text(&recording.to_text()), Ok(recording));
+    }
+
+    #[test]
+    fn replay_reports_first_diverging_tick() {
+        let mut recording = record();
+        recording.ticks[160].outputs.insert(String::from("ELEC_AC_BUS_1_IS_POWERED"), 0.);
+        recording.ticks[180].outputs.insert(String::from("ELEC_AC_BUS_2_IS_POWERED"), 0.);
+
+        assert_eq!(recording.replay(&mut Simulation::new(A320::new())), Err(ReplayError::Diverged(Divergence {
+            tick: 160,
+            time: Duration::from_millis(161),
+            name: String::from("ELEC_AC_BUS_1_IS_POWERED"),
+            recorded: Some(0.),
+            replayed: Some(1.)
+        })));
+    }
+
+    #[test]
+    fn replay_without_recorded_failure_diverges() {
+        let mut recording = record();
+        recording.ticks[150].failures.clear();
+
+        assert!(matches!(recording.replay(&mut Simulation::new(A320::new())), Err(ReplayError::Diverged(Divergence { tick: 150, .. }))));
+    }
+
+    #[test]
+    fn replay_reports_unrecorded_input() {
+        let mut recording = record();
+        recording.ticks[10].inputs.remove("TURB ENG CORRECTED N2:1");
+
+        assert_eq!(recording.replay(&mut Simulation::new(A320::new())),
+            Err(ReplayError::UnrecordedInput { tick: 10, name: String::from("TURB ENG CORRECTED N2:1") }));
+    }
+
+    #[test]
+    fn replay_in_other_aircraft_type_cannot_restore_initial_state() {
+        assert!(matches!(record().replay(&mut Simulation::new(A321::new())), Err(ReplayError::Snapshot(_))));
+    }
+
+    #[test]
+    fn text_without_header_is_not_a_recording() {
+        assert_eq!(Recording::from_text("TICK\t1000000\n"), Err(RecordingError::NotARecording));
+    }
+
+    #[test]
+    fn text_without_max_step_is_malformed() {
+        let text = record().to_text().lines().filter(|line| !line.starts_with("MAX STEP\t")).map(|line| format!("{}\n", line)).collect::<String>();
+
+        assert_eq!(Recording::from_text(&text), Err(RecordingError::Malformed(2)));
+    }
+
+    #[test]
+    fn recording_of_previous_version_is_unsupported() {
+        let text = record().to_text().replacen(&format!("RECORDING {}", Recording::VERSION), &format!("RECORDING {}", Recording::VERSION - 1), 1);
+
+        assert_eq!(Recording::from_text(&text), Err(RecordingError::UnsupportedVersion(Recording::VERSION - 1)));
+    }
+}
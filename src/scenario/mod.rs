@@ -0,0 +1,556 @@
+use std::{error::Error, fmt, time::Duration};
+
+use crate::{a320::ELECTRICAL_ALERTS, ecam::FlightPhase, electrical::{Current, Paralleling, PowerSource}, shared::{Aircraft, Simulation}, simulator::{InMemorySimulatorReaderWriter, SimulatorReaderWriter, input_names}, timeseries::Sample};
+
+/// An aircraft which scenarios can drive and make assertions about.
+pub trait ScenarioAircraft: Aircraft {
+    /// Returns the output of the bus with the given identifier, or `None` when there is no such bus.
+    fn bus_output(&self, id: &str) -> Option<Current>;
+
+    fn alert_is_active(&self, title: &str) -> bool;
+    fn set_flight_phase(&mut self, flight_phase: FlightPhase);
+
+    /// The named outputs of the aircraft, for recording them in a time series.
+    fn sample(&self) -> Sample;
+
+    /// Returns the buses on which distinct AC sources are paralleled, which must never happen.
+    fn paralleled_sources(&self) -> Vec<Paralleling>;
+
+    /// The ids of all failures the aircraft's systems respond to.
+    fn failure_ids(&self) -> Vec<String>;
+
+    /// Returns an error when the aircraft's systems do not respond to the failure with the given id.
+    fn check_failure_id(&self, id: &str) -> Result<(), String> {
+        if self.failure_ids().iter().any(|known| known == id) {
+            Ok(())
+        } else {
+            Err(format!("There is no failure '{}'.", id))
+        }
+    }
+
+    /// Returns the simulator variable which presses the named push button into the given position,
+    /// and whether that position sets it. Returns an error for unknown push buttons and positions.
+    fn push_button_input(&self, push_button: &str, position: &str) -> Result<(String, bool), String>;
+
+    /// Returns an error when the aircraft does not read the simulator variable with the given name.
+    fn check_input(&mut self, name: &str) -> Result<(), String> where Self: Sized {
+        if input_names(self).iter().any(|known| known == name) {
+            Ok(())
+        } else {
+            Err(format!("There is no input '{}'.", name))
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum BusExpectation {
+    Powered,
+    PoweredBy(PowerSource),
+    Unpowered
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Step {
+    Set(String, f64),
+    Press(String, String),
+    Fail(String),
+    Repair(String),
+    Phase(FlightPhase),
+    AllowParalleling,
+    Wait(Duration),
+    At(Duration),
+    ExpectBus(String, BusExpectation),
+    ExpectAlert(String, bool),
+    ExpectOutput { name: String, value: f64, tolerance: f64 }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScenarioError {
+    /// The line with the given number cannot be parsed.
+    Syntax { line: usize, message: String },
+    /// The step on the line with the given number failed.
+    Failed { line: usize, message: String }
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Syntax { line, message } => write!(f, "Line {}: {}", line, message),
+            ScenarioError::Failed { line, message } => write!(f, "Line {} failed: {}", line, message)
+        }
+    }
+}
+
+impl Error for ScenarioError {}
+
+/// A scenario of timed steps which set inputs, press buttons, inject failures, advance time and
+/// assert the resulting state, one step per line. Anything following a `#` is a comment.
+///
+/// ```text
+/// set TURB ENG CORRECTED N2:1 = 80      # Sets a simulator input.
+/// press GEN 1 off                       # Presses an ELEC overhead push button: on, off, normal or altn.
+/// fail TR 1                             # Activates a failure.
+/// repair TR 1                           # Deactivates a failure.
+/// phase 6                               # Sets the flight phase, from 1 to 10.
+/// allow paralleling                     # Stops failing when AC sources are paralleled, e.g. for welded contactors.
+/// wait 500ms                            # Advances time by ms, s or min.
+/// at 3s                                 # Advances time until the given simulation time.
+/// expect AC BUS 1 powered by GEN 2      # Sources: GEN 1, GEN 2, APU GEN, EXT PWR, EMER GEN, BAT 1, BAT 2.
+/// expect DC BUS 2 unpowered
+/// expect DC ESS BUS powered
+/// expect alert ELEC TR 1 FAULT
+/// expect no alert ELEC EMER CONFIG
+/// expect ELEC_TR_1_POTENTIAL = 28 within 0.5  # Asserts a simulator output.
+/// ```
+pub struct Scenario {
+    steps: Vec<(usize, Step)>
+}
+
+impl Scenario {
+    /// The time by which the aircraft is advanced in each tick.
+    pub const TICK: Duration = Duration::from_millis(1);
+
+    pub fn parse(text: &str) -> Result<Scenario, ScenarioError> {
+        let steps = text.lines().enumerate().filter_map(|(index, line)| {
+            let line_number = index + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                None
+            } else {
+                Some(Scenario::parse_step(line)
+                    .map(|step| (line_number, step))
+                    .map_err(|message| ScenarioError::Syntax { line: line_number, message }))
+            }
+        }).collect::<Result<Vec<(usize, Step)>, ScenarioError>>()?;
+
+        Ok(Scenario {
+            steps
+        })
+    }
+
+    fn parse_step(line: &str) -> Result<Step, String> {
+        let (keyword, arguments) = line.split_once(' ').map(|(keyword, arguments)| (keyword, arguments.trim())).unwrap_or((line, ""));
+        match keyword {
+            "set" => {
+                let (name, value) = arguments.split_once('=').ok_or("Expected: set <NAME> = <VALUE>")?;
+                Ok(Step::Set(name.trim().to_string(), Scenario::parse_number(value)?))
+            },
+            "press" => {
+                let (push_button, position) = arguments.rsplit_once(' ').ok_or("Expected: press <PUSH BUTTON> on|off|normal|altn")?;
+                Ok(Step::Press(push_button.trim().to_string(), position.to_string()))
+            },
+            "fail" if !arguments.is_empty() => Ok(Step::Fail(arguments.to_string())),
+            "repair" if !arguments.is_empty() => Ok(Step::Repair(arguments.to_string())),
+            "phase" => Scenario::parse_flight_phase(arguments).map(Step::Phase),
+            "allow" if arguments == "paralleling" => Ok(Step::AllowParalleling),
+            "wait" => Scenario::parse_duration(arguments).map(Step::Wait),
+            "at" => Scenario::parse_duration(arguments).map(Step::At),
+            "expect" => Scenario::parse_expectation(arguments),
+            _ => Err(format!("Unknown step '{}'.", line))
+        }
+    }
+
+    fn parse_expectation(arguments: &str) -> Result<Step, String> {
+        if let Some(title) = arguments.strip_prefix("no alert ") {
+            Ok(Step::ExpectAlert(Scenario::parse_alert_title(title)?, false))
+        } else if let Some(title) = arguments.strip_prefix("alert ") {
+            Ok(Step::ExpectAlert(Scenario::parse_alert_title(title)?, true))
+        } else if let Some((bus, source)) = arguments.split_once(" powered by ") {
+            Ok(Step::ExpectBus(bus.trim().to_string(), BusExpectation::PoweredBy(Scenario::parse_power_source(source.trim())?)))
+        } else if let Some(bus) = arguments.strip_suffix(" unpowered") {
+            Ok(Step::ExpectBus(bus.trim().to_string(), BusExpectation::Unpowered))
+        } else if let Some(bus) = arguments.strip_suffix(" powered") {
+            Ok(Step::ExpectBus(bus.trim().to_string(), BusExpectation::Powered))
+        } else if let Some((name, value)) = arguments.split_once('=') {
+            let (value, tolerance) = match value.split_once(" within ") {
+                Some((value, tolerance)) => (Scenario::parse_number(value)?, Scenario::parse_number(tolerance)?),
+                None => (Scenario::parse_number(value)?, 0.)
+            };
+            Ok(Step::ExpectOutput { name: name.trim().to_string(), value, tolerance })
+        } else {
+            Err(format!("Unknown expectation '{}'.", arguments))
+        }
+    }
+
+    fn parse_alert_title(text: &str) -> Result<String, String> {
+        ELECTRICAL_ALERTS.iter().find(|alert| alert.title() == text.trim())
+            .map(|alert| alert.title().to_string())
+            .ok_or_else(|| format!("Unknown alert '{}'.", text.trim()))
+    }
+
+    fn parse_number(text: &str) -> Result<f64, String> {
+        text.trim().parse::<f64>().map_err(|_| format!("'{}' is not a number.", text.trim()))
+    }
+
+    /// Parses a duration in ms, s or min, such as `500ms`.
+    pub fn parse_duration(text: &str) -> Result<Duration, String> {
+        let (value, seconds_per_unit) = if let Some(value) = text.strip_suffix("ms") {
+            (value, 0.001)
+        } else if let Some(value) = text.strip_suffix("min") {
+            (value, 60.)
+        } else if let Some(value) = text.strip_suffix('s') {
+            (value, 1.)
+        } else {
+            return Err(format!("'{}' is not a duration in ms, s or min.", text));
+        };
+
+        Duration::try_from_secs_f64(Scenario::parse_number(value)? * seconds_per_unit)
+            .map_err(|_| format!("'{}' is not a duration in ms, s or min.", text))
+    }
+
+    /// Parses a flight phase by its number, from 1 to 10.
+    pub fn parse_flight_phase(text: &str) -> Result<FlightPhase, String> {
+        [FlightPhase::ElectricalPower, FlightPhase::FirstEngineStarted, FlightPhase::FirstEngineTakeoffPower, FlightPhase::EightyKnots,
+            FlightPhase::LiftOff, FlightPhase::AboveFifteenHundredFeet, FlightPhase::BelowEightHundredFeet, FlightPhase::Touchdown,
+            FlightPhase::BelowEightyKnots, FlightPhase::SecondEngineShutdown].iter()
+            .find(|flight_phase| text.parse::<u8>() == Ok(flight_phase.number()))
+            .copied()
+            .ok_or_else(|| format!("'{}' is not a flight phase from 1 to 10.", text))
+    }
+
+    fn parse_power_source(text: &str) -> Result<PowerSource, String> {
+        match text {
+            "GEN 1" => Ok(PowerSource::EngineGenerator(1)),
+            "GEN 2" => Ok(PowerSource::EngineGenerator(2)),
+            "APU GEN" => Ok(PowerSource::ApuGenerator),
+            "EXT PWR" => Ok(PowerSource::External),
+            "EMER GEN" => Ok(PowerSource::EmergencyGenerator),
+            "BAT 1" => Ok(PowerSource::Battery(1)),
+            "BAT 2" => Ok(PowerSource::Battery(2)),
+            _ => Err(format!("Unknown power source '{}'.", text))
+        }
+    }
+
+    /// Runs the scenario against the given aircraft, stopping at the first failed step.
+    /// Returns the simulation, such that its final state can be inspected.
+    pub fn run<T: ScenarioAircraft>(&self, aircraft: T) -> Result<Simulation<T>, ScenarioError> {
+        self.run_with(aircraft, Scenario::TICK, Duration::from_secs(0), &mut |_| {})
+    }
+
+    /// Runs the scenario against the given aircraft, advancing it by the given tick and calling
+    /// the observer after every tick. Once all steps ran, the aircraft is advanced until the given time.
+    /// Ticks shorter than the simulation's default max step also serve as the max step, such that
+    /// every tick advances the aircraft.
+    pub fn run_with<T: ScenarioAircraft>(&self, aircraft: T, tick: Duration, until: Duration, observer: &mut dyn FnMut(&Simulation<T>))
+        -> Result<Simulation<T>, ScenarioError> {
+        assert!(tick > Duration::from_secs(0), "The tick must be longer than zero.");
+
+        let mut run = ScenarioRun {
+            simulation: Simulation::with_max_step(aircraft, tick.min(Simulation::<T>::DEFAULT_MAX_STEP)),
+            reader_writer: InMemorySimulatorReaderWriter::new(),
+            tick,
+            observer,
+            allow_paralleling: false
+        };
+        for (line, step) in self.steps.iter() {
+            run.run_step(step).map_err(|message| ScenarioError::Failed { line: *line, message })?;
+        }
+        let last_line = self.steps.last().map_or(0, |(line, _)| *line);
+        run.advance_until(until).map_err(|message| ScenarioError::Failed { line: last_line, message })?;
+
+        Ok(run.simulation)
+    }
+}
+
+struct ScenarioRun<'a, T: ScenarioAircraft> {
+    simulation: Simulation<T>,
+    reader_writer: InMemorySimulatorReaderWriter,
+    tick: Duration,
+    observer: &'a mut dyn FnMut(&Simulation<T>),
+    allow_paralleling: bool
+}
+
+impl<'a, T: ScenarioAircraft> ScenarioRun<'a, T> {
+    fn run_step(&mut self, step: &Step) -> Result<(), String> {
+        match step {
+            Step::Set(name, value) => {
+                self.simulation.aircraft_mut().check_input(name)?;
+                self.reader_writer.write(name, *value);
+            },
+            Step::Press(push_button, position) => {
+                let (name, is_on) = self.simulation.aircraft().push_button_input(push_button, position)?;
+                self.simulation.aircraft_mut().check_input(&name)?;
+                self.reader_writer.write(&name, if is_on { 1. } else { 0. });
+            },
+            Step::Fail(id) => {
+                self.simulation.aircraft().check_failure_id(id)?;
+                self.simulation.aircraft_mut().failures_mut().activate(id);
+            },
+            Step::Repair(id) => {
+                self.simulation.aircraft().check_failure_id(id)?;
+                self.simulation.aircraft_mut().failures_mut().deactivate(id);
+            },
+            Step::Phase(flight_phase) => self.simulation.aircraft_mut().set_flight_phase(*flight_phase),
+            Step::AllowParalleling => self.allow_paralleling = true,
+            Step::Wait(duration) => self.advance_until(self.simulation.simulation_time() + *duration)?,
+            Step::At(time) => {
+                if *time < self.simulation.simulation_time() {
+                    return Err(format!("The simulation time is already {:?}.", self.simulation.simulation_time()));
+                }
+                self.advance_until(*time)?;
+            },
+            Step::ExpectBus(id, expectation) => {
+                let output = self.simulation.aircraft().bus_output(id).ok_or_else(|| format!("There is no bus '{}'.", id))?;
+                let met = match expectation {
+                    BusExpectation::Powered => output.is_powered(),
+                    BusExpectation::PoweredBy(source) => output.is_powered() && output.source() == *source,
+                    BusExpectation::Unpowered => output.is_unpowered()
+                };
+                if !met {
+                    return Err(format!("Expected {} to be {}, but it is {}.", id, ScenarioRun::<T>::describe_expectation(expectation),
+                        ScenarioRun::<T>::describe_output(output)));
+                }
+            },
+            Step::ExpectAlert(title, active) => {
+                if self.simulation.aircraft().alert_is_active(title) != *active {
+                    return Err(format!("Expected alert {} to be {}.", title, if *active { "active" } else { "inactive" }));
+                }
+            },
+            Step::ExpectOutput { name, value, tolerance } => {
+                let actual = self.reader_writer.read(name).ok_or_else(|| format!("There is no output '{}'.", name))?;
+                if (actual - value).abs() > *tolerance {
+                    return Err(format!("Expected {} to be {} within {}, but it is {}.", name, value, tolerance, actual));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fails as soon as AC sources are paralleled, unless the scenario allows it.
+    fn advance_until(&mut self, time: Duration) -> Result<(), String> {
+        while self.simulation.simulation_time() < time {
+            self.simulation.tick(self.tick.min(time - self.simulation.simulation_time()), &mut self.reader_writer);
+            (self.observer)(&self.simulation);
+
+            if !self.allow_paralleling {
+                if let Some(paralleling) = self.simulation.aircraft().paralleled_sources().first() {
+                    return Err(format!("{} at {:?}.", paralleling, self.simulation.simulation_time()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn describe_expectation(expectation: &BusExpectation) -> String {
+        match expectation {
+            BusExpectation::Powered => String::from("powered"),
+            BusExpectation::PoweredBy(source) => format!("powered by {}", source),
+            BusExpectation::Unpowered => String::from("unpowered")
+        }
+    }
+
+    fn describe_output(output: Current) -> String {
+        if output.is_powered() {
+            format!("powered by {}", output.source())
+        } else {
+            String::from("unpowered")
+        }
+    }
+}
+
+#[cfg(test)]
+mod scenario_tests {
+    use std::{fs, path::Path};
+
+    use crate::{a320::A320, a321::A321};
+
+    use super::*;
+
+    fn run(text: &str) -> Result<Simulation<A320>, ScenarioError> {
+        Scenario::parse(text)?.run(A320::new())
+    }
+
+    #[test]
+    fn running_engines_power_their_ac_buses() {
+        let result = run("
+            set TURB ENG CORRECTED N2:1 = 80
+            set TURB ENG CORRECTED N2:2 = 80
+            wait 500ms
+            expect AC BUS 1 powered by GEN 1
+            expect AC BUS 2 powered by GEN 2
+            expect ELEC_AC_BUS_1_IS_POWERED = 1
+        ");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn failed_expectation_reports_line_and_actual_state() {
+        let result = run("
+            set TURB ENG CORRECTED N2:1 = 80
+            wait 500ms
+            expect AC BUS 2 powered by GEN 2
+        ");
+
+        assert_eq!(result.err(), Some(ScenarioError::Failed {
+            line: 4,
+            message: String::from("Expected AC BUS 2 to be powered by GEN 2, but it is powered by GEN 1.")
+        }));
+    }
+
+    #[test]
+    fn pressing_push_buttons_changes_the_circuit() {
+        let result = run("
+            set TURB ENG CORRECTED N2:2 = 80
+            press GEN 2 off
+            wait 500ms
+            expect AC BUS 1 unpowered
+            expect AC BUS 2 unpowered
+        ");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn failures_and_alerts() {
+        let result = run("
+            set TURB ENG CORRECTED N2:1 = 80
+            set TURB ENG CORRECTED N2:2 = 80
+            phase 6
+            wait 500ms
+            fail TR 1
+            at 2s
+            expect alert ELEC TR 1 FAULT
+            expect no alert ELEC EMER CONFIG
+            repair TR 1
+        ");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn paralleled_sources_fail_the_scenario() {
+        let result = run("
+            set TURB ENG CORRECTED N2:1 = 80
+            set APU PCT RPM = 100
+            fail 11XU1 WELDED
+            wait 500ms
+        ");
+
+        assert!(matches!(result, Err(ScenarioError::Failed { line: 5, message }) if message.starts_with("GEN 1 and APU GEN are paralleled on AC BUS 1 at ")));
+    }
+
+    #[test]
+    fn allowed_paralleled_sources_do_not_fail_the_scenario() {
+        let result = run("
+            allow paralleling
+            set TURB ENG CORRECTED N2:1 = 80
+            set APU PCT RPM = 100
+            fail 11XU1 WELDED
+            wait 500ms
+        ");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn at_advances_to_the_given_time() {
+        let simulation = run("at 1.5s").unwrap();
+
+        assert_eq!(simulation.simulation_time(), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn at_a_past_time_fails() {
+        assert!(matches!(run("wait 2s\nat 1s"), Err(ScenarioError::Failed { line: 2, .. })));
+    }
+
+    #[test]
+    fn unknown_bus_fails() {
+        assert!(matches!(run("expect AC BUS 3 powered"), Err(ScenarioError::Failed { line: 1, .. })));
+    }
+
+    #[test]
+    fn output_within_tolerance() {
+        let result = run("
+            set TURB ENG CORRECTED N2:1 = 80
+            wait 500ms
+            expect ELEC_TR_1_POTENTIAL = 28 within 0.5
+        ");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn unknown_step_is_a_syntax_error() {
+        assert!(matches!(Scenario::parse("# Comment\njump 3s"), Err(ScenarioError::Syntax { line: 2, .. })));
+    }
+
+    #[test]
+    fn invalid_duration_is_a_syntax_error() {
+        assert!(matches!(Scenario::parse("wait 3 hours"), Err(ScenarioError::Syntax { line: 1, .. })));
+    }
+
+    #[test]
+    fn out_of_range_duration_is_a_syntax_error() {
+        assert!(matches!(Scenario::parse("wait 1e30s"), Err(ScenarioError::Syntax { line: 1, .. })));
+    }
+
+    #[test]
+    fn unknown_push_button_fails() {
+        assert!(matches!(run("press GEN1 off"), Err(ScenarioError::Failed { line: 1, message }) if message == "Unknown push button 'GEN1'."));
+        assert!(matches!(run("press GEN 1 altn"), Err(ScenarioError::Failed { line: 1, .. })));
+    }
+
+    #[test]
+    fn unknown_input_fails() {
+        assert!(matches!(run("set TURB ENG CORRECTED N:1 = 80"), Err(ScenarioError::Failed { line: 1, message })
+            if message == "There is no input 'TURB ENG CORRECTED N:1'."));
+    }
+
+    #[test]
+    fn unknown_alert_is_a_syntax_error() {
+        assert!(matches!(Scenario::parse("expect no alert ELEC EMR CONFIG"), Err(ScenarioError::Syntax { line: 1, .. })));
+    }
+
+    #[test]
+    fn unknown_failure_fails() {
+        assert!(matches!(run("fail TR1"), Err(ScenarioError::Failed { line: 1, message }) if message == "There is no failure 'TR1'."));
+    }
+
+    #[test]
+    fn output_which_was_never_written_fails() {
+        assert!(matches!(run("wait 1ms\nexpect ELEC_AC_BUS_1_IS_POWERD = 0"), Err(ScenarioError::Failed { line: 2, .. })));
+    }
+
+    #[test]
+    fn unknown_power_source_is_a_syntax_error() {
+        assert!(matches!(Scenario::parse("expect AC BUS 1 powered by RAT"), Err(ScenarioError::Syntax { line: 1, .. })));
+    }
+
+    #[test]
+    fn runs_against_a321() {
+        let scenario = Scenario::parse("
+            set TURB ENG CORRECTED N2:1 = 80
+            wait 500ms
+            expect AC BUS 2 powered by GEN 1
+        ").unwrap();
+
+        assert!(scenario.run(A321::new()).is_ok());
+    }
+
+    #[test]
+    fn run_with_observes_every_tick_until_the_given_time() {
+        let mut times = vec![];
+        let simulation = Scenario::parse("wait 30ms").unwrap()
+            .run_with(A320::new(), Duration::from_millis(10), Duration::from_millis(55), &mut |simulation| times.push(simulation.simulation_time()))
+            .unwrap();
+
+        assert_eq!(simulation.simulation_time(), Duration::from_millis(55));
+        assert_eq!(times, [10, 20, 30, 40, 50, 55].iter().map(|millis| Duration::from_millis(*millis)).collect::<Vec<Duration>>());
+    }
+
+    #[test]
+    fn scenario_files_pass() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+        for entry in fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            let text = fs::read_to_string(&path).unwrap();
+            if let Err(error) = Scenario::parse(&text).and_then(|scenario| scenario.run(A320::new())) {
+                panic!("{}: {}", path.display(), error);
+            }
+        }
+    }
+}
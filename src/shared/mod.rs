@@ -1,5 +1,7 @@
 use uom::si::{f32::{Ratio}, ratio::percent};
-use std::time::Duration;
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::{simulator::{SimulatorElement, SimulatorReader, SimulatorReaderWriter, read_from_simulator, write_to_simulator}, snapshot::{StateVisitor, Stateful, visit_collection, visit_duration, visit_element, visit_variant}};
 
 pub struct UpdateContext {
     delta: Duration
@@ -11,6 +13,10 @@ impl UpdateContext {
             delta
         }
     }
+
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
 }
 
 /// The delay logic gate delays the true result of a given expression by the given amount of time.
@@ -42,24 +48,558 @@ impl DelayedTrueLogicGate {
     }
 
     pub fn output(&self) -> bool {
-        if self.expression_result && self.delay <= self.true_duration {
-            true
+        self.expression_result && self.delay <= self.true_duration
+    }
+}
+
+impl Stateful for DelayedTrueLogicGate {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_bool("EXPRESSION RESULT", &mut self.expression_result);
+        visit_duration(state, "TRUE DURATION", &mut self.true_duration);
+    }
+}
+
+/// The delay logic gate delays the false result of a given expression by the given amount of time.
+/// True results are output immediately. Also known as a confirmation node on the falling edge.
+pub struct DelayedFalseLogicGate {
+    delay: Duration,
+    expression_result: bool,
+    false_duration: Duration
+}
+
+impl DelayedFalseLogicGate {
+    pub fn new(delay: Duration) -> DelayedFalseLogicGate {
+        DelayedFalseLogicGate {
+            delay,
+            expression_result: false,
+            // Starts as if the expression has been false for longer than the delay, such that the output is false.
+            false_duration: delay
+        }
+    }
+
+    pub fn update(&mut self, context: &UpdateContext, expression_result: bool) {
+        // We do not include the delta representing the moment before the expression_result became false.
+        if !self.expression_result && !expression_result {
+            self.false_duration += context.delta;
+        } else {
+            self.false_duration = Duration::from_millis(0);
+        }
+
+        self.expression_result = expression_result;
+    }
+
+    pub fn output(&self) -> bool {
+        self.expression_result || self.false_duration < self.delay
+    }
+}
+
+impl Stateful for DelayedFalseLogicGate {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_bool("EXPRESSION RESULT", &mut self.expression_result);
+        visit_duration(state, "FALSE DURATION", &mut self.false_duration);
+    }
+}
+
+/// The confirmation node outputs a change of its input only once the input kept its new value
+/// for the given amount of time, on both the rising and the falling edge. Inputs toggling faster
+/// than the confirmation time therefore do not change the output.
+pub struct ConfirmationNode {
+    time: Duration,
+    input: bool,
+    input_duration: Duration,
+    output: bool
+}
+
+impl ConfirmationNode {
+    pub fn new(time: Duration, initial_output: bool) -> ConfirmationNode {
+        ConfirmationNode {
+            time,
+            input: initial_output,
+            input_duration: Duration::from_millis(0),
+            output: initial_output
+        }
+    }
+
+    pub fn update(&mut self, context: &UpdateContext, input: bool) {
+        // We do not include the delta representing the moment before the input changed.
+        if self.input == input {
+            self.input_duration += context.delta;
+        } else {
+            self.input_duration = Duration::from_millis(0);
+        }
+        self.input = input;
+
+        if self.time <= self.input_duration {
+            self.output = input;
+        }
+    }
+
+    pub fn output(&self) -> bool {
+        self.output
+    }
+}
+
+impl Stateful for ConfirmationNode {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_bool("INPUT", &mut self.input);
+        visit_duration(state, "INPUT DURATION", &mut self.input_duration);
+        state.visit_bool("OUTPUT", &mut self.output);
+    }
+}
+
+/// Which of the inputs of a latch wins when both are true.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LatchPriority {
+    /// A set-reset (SR) flip-flop, which sets when both inputs are true.
+    Set,
+    /// A reset-set (RS) flip-flop, which resets when both inputs are true.
+    Reset
+}
+
+/// The latch (flip-flop) memorises that it was set until it is reset.
+pub struct Latch {
+    priority: LatchPriority,
+    output: bool
+}
+
+impl Latch {
+    pub fn new(priority: LatchPriority) -> Latch {
+        Latch {
+            priority,
+            output: false
+        }
+    }
+
+    pub fn update(&mut self, _: &UpdateContext, set: bool, reset: bool) {
+        self.output = match (set, reset) {
+            (true, true) => self.priority == LatchPriority::Set,
+            (true, false) => true,
+            (false, true) => false,
+            (false, false) => self.output
+        };
+    }
+
+    pub fn output(&self) -> bool {
+        self.output
+    }
+}
+
+impl Stateful for Latch {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_bool("OUTPUT", &mut self.output);
+    }
+}
+
+/// The edge of a signal at which a gate triggers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Edge {
+    /// The input changes from false to true.
+    Rising,
+    /// The input changes from true to false.
+    Falling
+}
+
+impl Edge {
+    fn detect(self, previous: bool, current: bool) -> bool {
+        match self {
+            Edge::Rising => !previous && current,
+            Edge::Falling => previous && !current
+        }
+    }
+}
+
+/// The edge detector outputs a pulse lasting a single update whenever its input changes at the given edge.
+/// The input is considered false before the first update.
+pub struct EdgeDetector {
+    edge: Edge,
+    previous_input: bool,
+    output: bool
+}
+
+impl EdgeDetector {
+    pub fn new(edge: Edge) -> EdgeDetector {
+        EdgeDetector {
+            edge,
+            previous_input: false,
+            output: false
+        }
+    }
+
+    pub fn update(&mut self, _: &UpdateContext, input: bool) {
+        self.output = self.edge.detect(self.previous_input, input);
+        self.previous_input = input;
+    }
+
+    pub fn output(&self) -> bool {
+        self.output
+    }
+}
+
+impl Stateful for EdgeDetector {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_bool("PREVIOUS INPUT", &mut self.previous_input);
+        state.visit_bool("OUTPUT", &mut self.output);
+    }
+}
+
+/// The monostable gate outputs a pulse of the given duration whenever its input changes at the given edge.
+/// The pulse restarts when the edge occurs again before the pulse ended.
+pub struct MonostableGate {
+    edge: Edge,
+    duration: Duration,
+    previous_input: bool,
+    remaining: Duration
+}
+
+impl MonostableGate {
+    pub fn new(edge: Edge, duration: Duration) -> MonostableGate {
+        MonostableGate {
+            edge,
+            duration,
+            previous_input: false,
+            remaining: Duration::from_millis(0)
+        }
+    }
+
+    pub fn update(&mut self, context: &UpdateContext, input: bool) {
+        // We do not include the delta representing the moment before the edge.
+        if self.edge.detect(self.previous_input, input) {
+            self.remaining = self.duration;
         } else {
-            false
+            self.remaining = self.remaining.saturating_sub(context.delta);
         }
+
+        self.previous_input = input;
+    }
+
+    pub fn output(&self) -> bool {
+        self.remaining > Duration::from_millis(0)
+    }
+}
+
+impl Stateful for MonostableGate {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_bool("PREVIOUS INPUT", &mut self.previous_input);
+        visit_duration(state, "REMAINING", &mut self.remaining);
+    }
+}
+
+/// The hysteresis comparator outputs true once its input reaches the upper threshold, and
+/// keeps doing so until its input drops to the lower threshold. Inputs hovering around
+/// a single threshold therefore do not toggle the output.
+pub struct HysteresisComparator<T: PartialOrd + Copy> {
+    lower_threshold: T,
+    upper_threshold: T,
+    output: bool
+}
+
+impl<T: PartialOrd + Copy> HysteresisComparator<T> {
+    pub fn new(lower_threshold: T, upper_threshold: T) -> HysteresisComparator<T> {
+        assert!(lower_threshold <= upper_threshold, "The lower threshold must not exceed the upper threshold.");
+
+        HysteresisComparator {
+            lower_threshold,
+            upper_threshold,
+            output: false
+        }
+    }
+
+    pub fn update(&mut self, _: &UpdateContext, input: T) {
+        if input >= self.upper_threshold {
+            self.output = true;
+        } else if input <= self.lower_threshold {
+            self.output = false;
+        }
+    }
+
+    pub fn output(&self) -> bool {
+        self.output
+    }
+}
+
+impl<T: PartialOrd + Copy> Stateful for HysteresisComparator<T> {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_bool("OUTPUT", &mut self.output);
+    }
+}
+
+/// An aircraft whose systems can be driven by a `Simulation`.
+pub trait Aircraft: SimulatorElement + Stateful {
+    /// Advances all of the aircraft's systems by the context's delta.
+    fn tick(&mut self, context: &UpdateContext);
+
+    /// The failure registry through which failures are injected into the aircraft's systems.
+    fn failures_mut(&mut self) -> &mut Failures;
+}
+
+/// Runs an aircraft against a host simulator. Each tick reads the aircraft's inputs
+/// from the simulator, advances the aircraft and writes its outputs back.
+/// The aircraft is always advanced in fixed steps of the maximum step. The time of a tick which
+/// does not fill a whole step is carried into the next tick, thus the aircraft lags behind the
+/// simulation time by less than one step. Results therefore do not depend on the host's frame
+/// rate, and converge as the maximum step shrinks.
+pub struct Simulation<T: Aircraft> {
+    aircraft: T,
+    max_step: Duration,
+    /// The time passed by the host which has not yet filled a whole step.
+    unspent_time: Duration,
+    simulation_time: Duration
+}
+
+impl<T: Aircraft> Simulation<T> {
+    pub const DEFAULT_MAX_STEP: Duration = Duration::from_millis(50);
+
+    pub fn new(aircraft: T) -> Simulation<T> {
+        Simulation::with_max_step(aircraft, Simulation::<T>::DEFAULT_MAX_STEP)
+    }
+
+    pub fn with_max_step(aircraft: T, max_step: Duration) -> Simulation<T> {
+        Simulation::<T>::assert_max_step(max_step);
+
+        Simulation {
+            aircraft,
+            max_step,
+            unspent_time: Duration::from_millis(0),
+            simulation_time: Duration::from_millis(0)
+        }
+    }
+
+    fn assert_max_step(max_step: Duration) {
+        assert!(max_step > Duration::from_secs(0), "The max step must be longer than zero.");
+    }
+
+    /// Advances the aircraft by as many whole steps as fit in the delta and the time left over
+    /// from previous ticks. Inputs are read once before and outputs written once after all steps,
+    /// even when no step was taken.
+    pub fn tick(&mut self, delta: Duration, reader_writer: &mut dyn SimulatorReaderWriter) {
+        read_from_simulator(&mut self.aircraft, reader_writer);
+
+        self.unspent_time += delta;
+        while self.unspent_time >= self.max_step {
+            self.aircraft.tick(&UpdateContext::new(self.max_step));
+            self.unspent_time -= self.max_step;
+        }
+
+        write_to_simulator(&mut self.aircraft, reader_writer);
+
+        self.simulation_time += delta;
+    }
+
+    pub fn aircraft(&self) -> &T {
+        &self.aircraft
+    }
+
+    pub fn aircraft_mut(&mut self) -> &mut T {
+        &mut self.aircraft
+    }
+
+    pub fn max_step(&self) -> Duration {
+        self.max_step
+    }
+
+    /// Changes the step by which the aircraft is advanced from the next tick on.
+    pub fn set_max_step(&mut self, max_step: Duration) {
+        Simulation::<T>::assert_max_step(max_step);
+
+        self.max_step = max_step;
+    }
+
+    pub fn simulation_time(&self) -> Duration {
+        self.simulation_time
+    }
+}
+
+impl<T: Aircraft> Stateful for Simulation<T> {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_duration(state, "SIMULATION TIME", &mut self.simulation_time);
+        visit_duration(state, "UNSPENT TIME", &mut self.unspent_time);
+        visit_element(state, "AIRCRAFT", &mut self.aircraft);
     }
 }
 
 pub struct Engine {
+    number: u8,
     pub n2: Ratio
 }
 
 impl Engine {
-    pub fn new() -> Engine {
+    pub fn new(number: u8) -> Engine {
         Engine {
+            number,
             n2: Ratio::new::<percent>(0.)
         }
     }
+
+    pub fn update(&mut self, _context: &UpdateContext) {
+        // Until the engine is implemented, its N2 is read from the simulator and there is nothing to update.
+    }
+}
+
+impl SimulatorElement for Engine {
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        if let Some(n2) = reader.read_f64(&format!("TURB ENG CORRECTED N2:{}", self.number)) {
+            self.n2 = Ratio::new::<percent>(n2 as f32);
+        }
+    }
+}
+
+impl Stateful for Engine {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        state.visit_f32("N2", &mut self.n2.value);
+    }
+}
+
+/// Describes when a registered failure becomes active.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FailureActivation {
+    Immediate,
+    /// Activates once the simulation time reaches the given time.
+    At(Duration),
+    /// Activates randomly with the given mean time between failures.
+    Random(Duration)
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Failure {
+    activation: FailureActivation,
+    active: bool
+}
+
+/// The failure registry through which instructors and test harnesses fail components.
+/// Components are addressed by their identifier, such as "TR 1", "AC BUS 2", "GEN 1" or "9XU1".
+/// Components query the registry for their failures during update.
+pub struct Failures {
+    time: Duration,
+    failures: BTreeMap<String, Failure>,
+    random: Random
+}
+
+impl Failures {
+    const DEFAULT_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+    pub fn new() -> Failures {
+        Failures::with_seed(Failures::DEFAULT_SEED)
+    }
+
+    /// Creates a registry whose random failures are drawn from the given seed.
+    pub fn with_seed(seed: u64) -> Failures {
+        Failures {
+            time: Duration::from_secs(0),
+            failures: BTreeMap::new(),
+            random: Random::new(seed)
+        }
+    }
+
+    pub fn activate(&mut self, id: &str) {
+        self.register(id, FailureActivation::Immediate, true);
+    }
+
+    /// Activates the failure once the simulation time reaches the given time.
+    pub fn activate_at(&mut self, id: &str, time: Duration) {
+        self.register(id, FailureActivation::At(time), time <= self.time);
+    }
+
+    /// Activates the failure at a random moment, with the given mean time between failures.
+    pub fn activate_randomly(&mut self, id: &str, mean_time_between_failures: Duration) {
+        self.register(id, FailureActivation::Random(mean_time_between_failures), false);
+    }
+
+    /// Returns the component to normal and removes any scheduled or random activation.
+    pub fn deactivate(&mut self, id: &str) {
+        self.failures.remove(id);
+    }
+
+    fn register(&mut self, id: &str, activation: FailureActivation, active: bool) {
+        self.failures.insert(String::from(id), Failure { activation, active });
+    }
+
+    pub fn update(&mut self, context: &UpdateContext) {
+        self.time += context.delta;
+
+        let time = self.time;
+        let random = &mut self.random;
+        for failure in self.failures.values_mut().filter(|failure| !failure.active) {
+            failure.active = match failure.activation {
+                FailureActivation::Immediate => true,
+                FailureActivation::At(activation_time) => activation_time <= time,
+                FailureActivation::Random(mean_time_between_failures) => {
+                    let probability = 1. - (-context.delta.as_secs_f64() / mean_time_between_failures.as_secs_f64()).exp();
+                    random.next_f64() < probability
+                }
+            };
+        }
+    }
+
+    pub fn is_active(&self, id: &str) -> bool {
+        self.failures.get(id).is_some_and(|failure| failure.active)
+    }
+
+    /// Returns the identifiers of all active failures in alphabetical order.
+    pub fn active(&self) -> Vec<&str> {
+        self.failures.iter().filter(|(_, failure)| failure.active).map(|(id, _)| id.as_str()).collect()
+    }
+}
+
+impl Stateful for Failures {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        visit_duration(state, "TIME", &mut self.time);
+        state.visit_u64("RANDOM STATE", &mut self.random.state);
+
+        let mut failures: Vec<(String, Failure)> = std::mem::take(&mut self.failures).into_iter().collect();
+        visit_collection(state, "FAILURES", &mut failures, || (String::new(), Failure { activation: FailureActivation::Immediate, active: false }));
+        for (index, (id, failure)) in failures.iter_mut().enumerate() {
+            state.enter(&format!("FAILURE {}", index));
+            state.visit_text("ID", id);
+            failure.visit_state(state);
+            state.leave();
+        }
+
+        self.failures = failures.into_iter().collect();
+    }
+}
+
+impl Stateful for Failure {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+        let (mut kind, mut time) = match self.activation {
+            FailureActivation::Immediate => (0, Duration::from_secs(0)),
+            FailureActivation::At(time) => (1, time),
+            FailureActivation::Random(mean_time_between_failures) => (2, mean_time_between_failures)
+        };
+        visit_variant(state, "ACTIVATION", &mut kind, &[0, 1, 2]);
+        visit_duration(state, "ACTIVATION TIME", &mut time);
+        self.activation = match kind {
+            1 => FailureActivation::At(time),
+            2 => FailureActivation::Random(time),
+            _ => FailureActivation::Immediate
+        };
+
+        state.visit_bool("ACTIVE", &mut self.active);
+    }
+}
+
+/// A small deterministic pseudo random number generator (xorshift64*), such that
+/// a simulation given the same seed always produces the same failures.
+pub(crate) struct Random {
+    state: u64
+}
+
+impl Random {
+    pub(crate) fn new(seed: u64) -> Random {
+        Random {
+            // The xorshift state must never be zero.
+            state: if seed == 0 { Failures::DEFAULT_SEED } else { seed }
+        }
+    }
+
+    /// Returns a number in the range [0, 1).
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let value = self.state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+
+        (value >> 11) as f64 / (1u64 << 53) as f64
+    }
 }
 
 #[cfg(test)]
@@ -72,7 +612,7 @@ mod delayed_true_logic_gate_tests {
         gate.update(&update_context(Duration::from_millis(0)), false);
         gate.update(&update_context(Duration::from_millis(1_000)), false);
 
-        assert_eq!(gate.output(), false);
+        assert!(!gate.output());
     }
 
     #[test]
@@ -81,7 +621,7 @@ mod delayed_true_logic_gate_tests {
         gate.update(&update_context(Duration::from_millis(0)), false);
         gate.update(&update_context(Duration::from_millis(1_000)), false);
 
-        assert_eq!(gate.output(), false);
+        assert!(!gate.output());
     }
 
     #[test]
@@ -90,7 +630,7 @@ mod delayed_true_logic_gate_tests {
         gate.update(&update_context(Duration::from_millis(0)), true);
         gate.update(&update_context(Duration::from_millis(1_000)), true);
 
-        assert_eq!(gate.output(), true);
+        assert!(gate.output());
     }
 
     #[test]
@@ -101,7 +641,7 @@ mod delayed_true_logic_gate_tests {
         gate.update(&update_context(Duration::from_millis(100)), false);
         gate.update(&update_context(Duration::from_millis(200)), false);
 
-        assert_eq!(gate.output(), false);
+        assert!(!gate.output());
     }
 
     #[test]
@@ -110,7 +650,7 @@ mod delayed_true_logic_gate_tests {
         gate.update(&update_context(Duration::from_millis(900)), true);
         gate.update(&update_context(Duration::from_millis(200)), true);
 
-        assert_eq!(gate.output(), false);
+        assert!(!gate.output());
     }
 
     fn update_context(delta: Duration) -> UpdateContext {
@@ -120,4 +660,513 @@ mod delayed_true_logic_gate_tests {
     fn delay_logic_gate(delay: Duration) -> DelayedTrueLogicGate {
         DelayedTrueLogicGate::new(delay)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod delayed_false_logic_gate_tests {
+    use super::*;
+
+    #[test]
+    fn initially_returns_false() {
+        let mut gate = DelayedFalseLogicGate::new(Duration::from_millis(100));
+        gate.update(&update_context(Duration::from_millis(1_000)), false);
+
+        assert!(!gate.output());
+    }
+
+    #[test]
+    fn when_the_expression_is_true_returns_true() {
+        let mut gate = DelayedFalseLogicGate::new(Duration::from_millis(100));
+        gate.update(&update_context(Duration::from_millis(0)), true);
+
+        assert!(gate.output());
+    }
+
+    #[test]
+    fn when_the_expression_becomes_false_and_delay_hasnt_passed_returns_true() {
+        let mut gate = DelayedFalseLogicGate::new(Duration::from_millis(1_000));
+        gate.update(&update_context(Duration::from_millis(0)), true);
+        gate.update(&update_context(Duration::from_millis(500)), false);
+        gate.update(&update_context(Duration::from_millis(800)), false);
+
+        assert!(gate.output());
+    }
+
+    #[test]
+    fn when_the_expression_becomes_false_and_delay_has_passed_returns_false() {
+        let mut gate = DelayedFalseLogicGate::new(Duration::from_millis(1_000));
+        gate.update(&update_context(Duration::from_millis(0)), true);
+        gate.update(&update_context(Duration::from_millis(500)), false);
+        gate.update(&update_context(Duration::from_millis(1_000)), false);
+
+        assert!(!gate.output());
+    }
+
+    #[test]
+    fn when_the_expression_becomes_true_again_before_delay_has_passed_restarts_the_delay() {
+        let mut gate = DelayedFalseLogicGate::new(Duration::from_millis(1_000));
+        gate.update(&update_context(Duration::from_millis(0)), true);
+        gate.update(&update_context(Duration::from_millis(0)), false);
+        gate.update(&update_context(Duration::from_millis(800)), false);
+        gate.update(&update_context(Duration::from_millis(100)), true);
+        gate.update(&update_context(Duration::from_millis(0)), false);
+        gate.update(&update_context(Duration::from_millis(800)), false);
+
+        assert!(gate.output());
+    }
+
+    fn update_context(delta: Duration) -> UpdateContext {
+        UpdateContext::new(delta)
+    }
+}
+
+#[cfg(test)]
+mod confirmation_node_tests {
+    use super::*;
+
+    #[test]
+    fn starts_with_the_initial_output() {
+        assert!(ConfirmationNode::new(Duration::from_millis(100), true).output());
+        assert!(!ConfirmationNode::new(Duration::from_millis(100), false).output());
+    }
+
+    #[test]
+    fn confirms_rising_edge_once_time_passed() {
+        let mut node = ConfirmationNode::new(Duration::from_millis(1_000), false);
+        node.update(&update_context(Duration::from_millis(0)), true);
+        node.update(&update_context(Duration::from_millis(900)), true);
+        assert!(!node.output());
+
+        node.update(&update_context(Duration::from_millis(100)), true);
+        assert!(node.output());
+    }
+
+    #[test]
+    fn confirms_falling_edge_once_time_passed() {
+        let mut node = ConfirmationNode::new(Duration::from_millis(1_000), true);
+        node.update(&update_context(Duration::from_millis(0)), false);
+        node.update(&update_context(Duration::from_millis(900)), false);
+        assert!(node.output());
+
+        node.update(&update_context(Duration::from_millis(100)), false);
+        assert!(!node.output());
+    }
+
+    #[test]
+    fn input_toggling_faster_than_the_confirmation_time_does_not_change_the_output() {
+        let mut node = ConfirmationNode::new(Duration::from_millis(1_000), false);
+        for _ in 0..10 {
+            node.update(&update_context(Duration::from_millis(600)), true);
+            node.update(&update_context(Duration::from_millis(600)), false);
+        }
+
+        assert!(!node.output());
+    }
+
+    #[test]
+    fn does_not_include_delta_at_the_moment_of_input_changing() {
+        let mut node = ConfirmationNode::new(Duration::from_millis(1_000), false);
+        node.update(&update_context(Duration::from_millis(900)), true);
+        node.update(&update_context(Duration::from_millis(200)), true);
+        assert!(!node.output());
+
+        node.update(&update_context(Duration::from_millis(800)), true);
+        assert!(node.output());
+
+        let mut node = ConfirmationNode::new(Duration::from_millis(1_000), false);
+        node.update(&update_context(Duration::from_millis(1_500)), true);
+
+        assert!(!node.output());
+    }
+
+    fn update_context(delta: Duration) -> UpdateContext {
+        UpdateContext::new(delta)
+    }
+}
+
+#[cfg(test)]
+mod latch_tests {
+    use super::*;
+
+    #[test]
+    fn starts_reset() {
+        assert!(!Latch::new(LatchPriority::Set).output());
+    }
+
+    #[test]
+    fn remains_set_until_reset() {
+        let mut latch = Latch::new(LatchPriority::Set);
+        latch.update(&update_context(), true, false);
+        latch.update(&update_context(), false, false);
+        assert!(latch.output());
+
+        latch.update(&update_context(), false, true);
+        latch.update(&update_context(), false, false);
+        assert!(!latch.output());
+    }
+
+    #[test]
+    fn set_priority_latch_sets_when_both_inputs_are_true() {
+        let mut latch = Latch::new(LatchPriority::Set);
+        latch.update(&update_context(), true, true);
+
+        assert!(latch.output());
+    }
+
+    #[test]
+    fn reset_priority_latch_resets_when_both_inputs_are_true() {
+        let mut latch = Latch::new(LatchPriority::Reset);
+        latch.update(&update_context(), true, false);
+        latch.update(&update_context(), true, true);
+
+        assert!(!latch.output());
+    }
+
+    fn update_context() -> UpdateContext {
+        UpdateContext::new(Duration::from_millis(100))
+    }
+}
+
+#[cfg(test)]
+mod edge_detector_tests {
+    use super::*;
+
+    #[test]
+    fn rising_edge_outputs_a_single_update_pulse() {
+        let mut detector = EdgeDetector::new(Edge::Rising);
+        detector.update(&update_context(), true);
+        assert!(detector.output());
+
+        detector.update(&update_context(), true);
+        assert!(!detector.output());
+    }
+
+    #[test]
+    fn rising_edge_ignores_falling_edge() {
+        let mut detector = EdgeDetector::new(Edge::Rising);
+        detector.update(&update_context(), true);
+        detector.update(&update_context(), false);
+
+        assert!(!detector.output());
+    }
+
+    #[test]
+    fn falling_edge_outputs_a_single_update_pulse() {
+        let mut detector = EdgeDetector::new(Edge::Falling);
+        detector.update(&update_context(), false);
+        assert!(!detector.output());
+
+        detector.update(&update_context(), true);
+        assert!(!detector.output());
+
+        detector.update(&update_context(), false);
+        assert!(detector.output());
+
+        detector.update(&update_context(), false);
+        assert!(!detector.output());
+    }
+
+    fn update_context() -> UpdateContext {
+        UpdateContext::new(Duration::from_millis(100))
+    }
+}
+
+#[cfg(test)]
+mod monostable_gate_tests {
+    use super::*;
+
+    #[test]
+    fn outputs_a_pulse_of_the_given_duration_after_the_edge() {
+        let mut gate = MonostableGate::new(Edge::Rising, Duration::from_millis(1_000));
+        gate.update(&update_context(Duration::from_millis(500)), true);
+        assert!(gate.output());
+
+        gate.update(&update_context(Duration::from_millis(900)), true);
+        assert!(gate.output());
+
+        gate.update(&update_context(Duration::from_millis(100)), true);
+        assert!(!gate.output());
+    }
+
+    #[test]
+    fn pulse_continues_when_input_changes_back() {
+        let mut gate = MonostableGate::new(Edge::Falling, Duration::from_millis(1_000));
+        gate.update(&update_context(Duration::from_millis(0)), true);
+        gate.update(&update_context(Duration::from_millis(0)), false);
+        gate.update(&update_context(Duration::from_millis(300)), true);
+
+        assert!(gate.output());
+    }
+
+    #[test]
+    fn edge_during_pulse_restarts_the_pulse() {
+        let mut gate = MonostableGate::new(Edge::Rising, Duration::from_millis(1_000));
+        gate.update(&update_context(Duration::from_millis(0)), true);
+        gate.update(&update_context(Duration::from_millis(600)), false);
+        gate.update(&update_context(Duration::from_millis(300)), true);
+        gate.update(&update_context(Duration::from_millis(900)), true);
+
+        assert!(gate.output());
+    }
+
+    fn update_context(delta: Duration) -> UpdateContext {
+        UpdateContext::new(delta)
+    }
+}
+
+#[cfg(test)]
+mod hysteresis_comparator_tests {
+    use super::*;
+
+    #[test]
+    fn becomes_true_at_the_upper_threshold() {
+        let mut comparator = HysteresisComparator::new(50., 60.);
+        comparator.update(&update_context(), 59.9);
+        assert!(!comparator.output());
+
+        comparator.update(&update_context(), 60.);
+        assert!(comparator.output());
+    }
+
+    #[test]
+    fn remains_true_until_the_lower_threshold() {
+        let mut comparator = HysteresisComparator::new(50., 60.);
+        comparator.update(&update_context(), 70.);
+        comparator.update(&update_context(), 50.1);
+        assert!(comparator.output());
+
+        comparator.update(&update_context(), 50.);
+        assert!(!comparator.output());
+    }
+
+    #[test]
+    fn input_hovering_between_the_thresholds_does_not_toggle_the_output() {
+        let mut comparator = HysteresisComparator::new(50., 60.);
+        for input in [55., 58., 52., 59.] {
+            comparator.update(&update_context(), input);
+            assert!(!comparator.output());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn lower_threshold_above_upper_threshold_panics() {
+        HysteresisComparator::new(60., 50.);
+    }
+
+    fn update_context() -> UpdateContext {
+        UpdateContext::new(Duration::from_millis(100))
+    }
+}
+
+#[cfg(test)]
+mod simulation_tests {
+    use crate::{simulator::InMemorySimulatorReaderWriter, snapshot::Snapshot};
+
+    use super::*;
+
+    struct DeltaRecordingAircraft {
+        deltas: Vec<Duration>,
+        failures: Failures
+    }
+
+    impl DeltaRecordingAircraft {
+        fn new() -> DeltaRecordingAircraft {
+            DeltaRecordingAircraft {
+                deltas: Vec::new(),
+                failures: Failures::new()
+            }
+        }
+    }
+
+    impl Aircraft for DeltaRecordingAircraft {
+        fn tick(&mut self, context: &UpdateContext) {
+            self.deltas.push(context.delta());
+        }
+
+        fn failures_mut(&mut self) -> &mut Failures {
+            &mut self.failures
+        }
+    }
+
+    impl SimulatorElement for DeltaRecordingAircraft {}
+
+    impl Stateful for DeltaRecordingAircraft {
+        fn visit_state(&mut self, _: &mut dyn StateVisitor) {}
+    }
+
+    #[test]
+    fn delta_of_max_step_takes_one_step() {
+        let mut simulation = Simulation::with_max_step(DeltaRecordingAircraft::new(), Duration::from_millis(300));
+        simulation.tick(Duration::from_millis(300), &mut InMemorySimulatorReaderWriter::new());
+
+        assert_eq!(simulation.aircraft().deltas, vec![Duration::from_millis(300)]);
+    }
+
+    #[test]
+    fn delta_above_max_step_takes_whole_steps_and_carries_the_remainder() {
+        let mut simulation = Simulation::with_max_step(DeltaRecordingAircraft::new(), Duration::from_millis(300));
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        simulation.tick(Duration::from_secs(1), &mut reader_writer);
+
+        assert_eq!(simulation.aircraft().deltas, vec![Duration::from_millis(300); 3]);
+        assert_eq!(simulation.simulation_time(), Duration::from_secs(1));
+
+        simulation.tick(Duration::from_millis(200), &mut reader_writer);
+
+        assert_eq!(simulation.aircraft().deltas, vec![Duration::from_millis(300); 4]);
+    }
+
+    #[test]
+    fn deltas_below_max_step_accumulate_into_a_step() {
+        let mut simulation = Simulation::with_max_step(DeltaRecordingAircraft::new(), Duration::from_millis(300));
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        simulation.tick(Duration::from_millis(100), &mut reader_writer);
+        simulation.tick(Duration::from_millis(100), &mut reader_writer);
+
+        assert!(simulation.aircraft().deltas.is_empty());
+
+        simulation.tick(Duration::from_millis(150), &mut reader_writer);
+
+        assert_eq!(simulation.aircraft().deltas, vec![Duration::from_millis(300)]);
+        assert_eq!(simulation.simulation_time(), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn zero_delta_does_not_tick_the_aircraft() {
+        let mut simulation = Simulation::new(DeltaRecordingAircraft::new());
+        simulation.tick(Duration::from_secs(0), &mut InMemorySimulatorReaderWriter::new());
+
+        assert!(simulation.aircraft().deltas.is_empty());
+    }
+
+    #[test]
+    fn snapshot_restores_the_unspent_time() {
+        let mut simulation = Simulation::with_max_step(DeltaRecordingAircraft::new(), Duration::from_millis(300));
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        simulation.tick(Duration::from_millis(200), &mut reader_writer);
+
+        let mut restored = Simulation::with_max_step(DeltaRecordingAircraft::new(), Duration::from_millis(300));
+        assert_eq!(Snapshot::save(&mut simulation).restore(&mut restored), Ok(()));
+        restored.tick(Duration::from_millis(100), &mut reader_writer);
+
+        assert_eq!(restored.aircraft().deltas, vec![Duration::from_millis(300)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_max_step_panics() {
+        Simulation::with_max_step(DeltaRecordingAircraft::new(), Duration::from_secs(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn setting_zero_max_step_panics() {
+        Simulation::new(DeltaRecordingAircraft::new()).set_max_step(Duration::from_secs(0));
+    }
+}
+
+#[cfg(test)]
+mod failures_tests {
+    use super::*;
+
+    #[test]
+    fn unknown_failure_is_not_active() {
+        assert!(!Failures::new().is_active("TR 1"));
+    }
+
+    #[test]
+    fn activated_failure_is_active() {
+        let mut failures = Failures::new();
+        failures.activate("TR 1");
+
+        assert!(failures.is_active("TR 1"));
+        assert!(!failures.is_active("TR 2"));
+    }
+
+    #[test]
+    fn deactivated_failure_is_not_active() {
+        let mut failures = Failures::new();
+        failures.activate("TR 1");
+        failures.deactivate("TR 1");
+
+        assert!(!failures.is_active("TR 1"));
+    }
+
+    #[test]
+    fn scheduled_failure_is_not_active_before_its_time() {
+        let mut failures = Failures::new();
+        failures.activate_at("AC BUS 1", Duration::from_secs(10));
+        failures.update(&update_context(Duration::from_millis(9_999)));
+
+        assert!(!failures.is_active("AC BUS 1"));
+    }
+
+    #[test]
+    fn scheduled_failure_is_active_once_its_time_is_reached() {
+        let mut failures = Failures::new();
+        failures.activate_at("AC BUS 1", Duration::from_secs(10));
+        failures.update(&update_context(Duration::from_secs(5)));
+        failures.update(&update_context(Duration::from_secs(5)));
+
+        assert!(failures.is_active("AC BUS 1"));
+    }
+
+    #[test]
+    fn failure_scheduled_in_the_past_is_active_immediately() {
+        let mut failures = Failures::new();
+        failures.update(&update_context(Duration::from_secs(20)));
+        failures.activate_at("AC BUS 1", Duration::from_secs(10));
+
+        assert!(failures.is_active("AC BUS 1"));
+    }
+
+    #[test]
+    fn random_failure_eventually_becomes_active() {
+        let mut failures = Failures::new();
+        failures.activate_randomly("GEN 1", Duration::from_secs(60));
+        for _ in 0..3_600 {
+            failures.update(&update_context(Duration::from_secs(1)));
+        }
+
+        assert!(failures.is_active("GEN 1"));
+    }
+
+    #[test]
+    fn random_failure_with_long_mean_time_between_failures_is_unlikely_to_become_active() {
+        let mut failures = Failures::new();
+        failures.activate_randomly("GEN 1", Duration::from_secs(1_000_000_000));
+        failures.update(&update_context(Duration::from_secs(1)));
+
+        assert!(!failures.is_active("GEN 1"));
+    }
+
+    #[test]
+    fn random_failures_with_same_seed_activate_at_the_same_moment() {
+        assert_eq!(random_failure_activation_tick(42), random_failure_activation_tick(42));
+    }
+
+    #[test]
+    fn active_returns_active_failures_in_alphabetical_order() {
+        let mut failures = Failures::new();
+        failures.activate("TR 1");
+        failures.activate("AC BUS 2");
+        failures.activate_at("GEN 1", Duration::from_secs(10));
+
+        assert_eq!(failures.active(), vec!["AC BUS 2", "TR 1"]);
+    }
+
+    fn random_failure_activation_tick(seed: u64) -> Option<usize> {
+        let mut failures = Failures::with_seed(seed);
+        failures.activate_randomly("GEN 1", Duration::from_secs(60));
+
+        (0..3_600).find(|_| {
+            failures.update(&update_context(Duration::from_secs(1)));
+            failures.is_active("GEN 1")
+        })
+    }
+
+    fn update_context(delta: Duration) -> UpdateContext {
+        UpdateContext::new(delta)
+    }
+}
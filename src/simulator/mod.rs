@@ -0,0 +1,314 @@
+use std::{collections::BTreeMap, ffi::CString, os::raw::c_char};
+
+/// Reads and writes the simulation variables (simvars) of the host simulator by name.
+/// Booleans are represented as 0 (false) and 1 (true).
+pub trait SimulatorReaderWriter {
+    /// Returns `None` when the simulator does not have a variable with the given name.
+    fn read(&mut self, name: &str) -> Option<f64>;
+    fn write(&mut self, name: &str, value: f64);
+}
+
+/// An element of the simulated systems which reads its inputs from and writes its outputs
+/// to the host simulator. Elements composed of other elements accept the visitor on
+/// each of them, such that visiting the top-level element visits all systems.
+pub trait SimulatorElement {
+    fn accept<T: SimulatorElementVisitor>(&mut self, visitor: &mut T) where Self: Sized {
+        visitor.visit(self);
+    }
+
+    fn read(&mut self, _reader: &mut SimulatorReader) {}
+    fn write(&self, _writer: &mut SimulatorWriter) {}
+}
+
+pub trait SimulatorElementVisitor {
+    fn visit<T: SimulatorElement>(&mut self, visited: &mut T);
+}
+
+pub struct SimulatorReader<'a> {
+    reader_writer: &'a mut dyn SimulatorReaderWriter
+}
+
+impl<'a> SimulatorReader<'a> {
+    pub fn new(reader_writer: &'a mut dyn SimulatorReaderWriter) -> SimulatorReader<'a> {
+        SimulatorReader {
+            reader_writer
+        }
+    }
+
+    /// Returns `None` when the simulator does not have the variable, in which case
+    /// elements keep their current state.
+    pub fn read_f64(&mut self, name: &str) -> Option<f64> {
+        self.reader_writer.read(name)
+    }
+
+    pub fn read_bool(&mut self, name: &str) -> Option<bool> {
+        self.reader_writer.read(name).map(|value| value != 0.)
+    }
+}
+
+pub struct SimulatorWriter<'a> {
+    reader_writer: &'a mut dyn SimulatorReaderWriter
+}
+
+impl<'a> SimulatorWriter<'a> {
+    pub fn new(reader_writer: &'a mut dyn SimulatorReaderWriter) -> SimulatorWriter<'a> {
+        SimulatorWriter {
+            reader_writer
+        }
+    }
+
+    pub fn write_f64(&mut self, name: &str, value: f64) {
+        self.reader_writer.write(name, value);
+    }
+
+    pub fn write_bool(&mut self, name: &str, value: bool) {
+        self.reader_writer.write(name, if value { 1. } else { 0. });
+    }
+}
+
+struct SimulatorToModelVisitor<'a> {
+    reader: SimulatorReader<'a>
+}
+
+impl<'a> SimulatorElementVisitor for SimulatorToModelVisitor<'a> {
+    fn visit<T: SimulatorElement>(&mut self, visited: &mut T) {
+        visited.read(&mut self.reader);
+    }
+}
+
+struct ModelToSimulatorVisitor<'a> {
+    writer: SimulatorWriter<'a>
+}
+
+impl<'a> SimulatorElementVisitor for ModelToSimulatorVisitor<'a> {
+    fn visit<T: SimulatorElement>(&mut self, visited: &mut T) {
+        visited.write(&mut self.writer);
+    }
+}
+
+/// Reads the inputs of the given element and all elements it is composed of from the simulator.
+pub fn read_from_simulator<T: SimulatorElement>(element: &mut T, reader_writer: &mut dyn SimulatorReaderWriter) {
+    element.accept(&mut SimulatorToModelVisitor { reader: SimulatorReader::new(reader_writer) });
+}
+
+/// Writes the outputs of the given element and all elements it is composed of to the simulator.
+pub fn write_to_simulator<T: SimulatorElement>(element: &mut T, reader_writer: &mut dyn SimulatorReaderWriter) {
+    element.accept(&mut ModelToSimulatorVisitor { writer: SimulatorWriter::new(reader_writer) });
+}
+
+/// Returns the names of the variables the given element and all elements it is composed of read
+/// from the simulator. As none of the variables exist while collecting them, the elements keep their state.
+pub fn input_names<T: SimulatorElement>(element: &mut T) -> Vec<String> {
+    let mut collector = InputNameCollector { names: vec![] };
+    read_from_simulator(element, &mut collector);
+
+    collector.names
+}
+
+struct InputNameCollector {
+    names: Vec<String>
+}
+
+impl SimulatorReaderWriter for InputNameCollector {
+    fn read(&mut self, name: &str) -> Option<f64> {
+        self.names.push(name.to_string());
+        None
+    }
+
+    fn write(&mut self, _name: &str, _value: f64) {}
+}
+
+/// Keeps the simulation variables in memory, for running the systems without a host simulator.
+/// A variable which was never written does not exist.
+pub struct InMemorySimulatorReaderWriter {
+    variables: BTreeMap<String, f64>
+}
+
+impl InMemorySimulatorReaderWriter {
+    pub fn new() -> InMemorySimulatorReaderWriter {
+        InMemorySimulatorReaderWriter {
+            variables: BTreeMap::new()
+        }
+    }
+
+    /// The names of all variables written so far, in alphabetical order.
+    pub fn names(&self) -> Vec<&str> {
+        self.variables.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+impl SimulatorReaderWriter for InMemorySimulatorReaderWriter {
+    fn read(&mut self, name: &str) -> Option<f64> {
+        self.variables.get(name).copied()
+    }
+
+    fn write(&mut self, name: &str, value: f64) {
+        self.variables.insert(name.to_string(), value);
+    }
+}
+
+/// Reads and writes the simulation variables through the functions provided by a host simulator
+/// over the C ABI, such as the named variable functions of a gauge module. The host's read
+/// function returns NaN for a variable which does not exist.
+pub struct HostSimulatorReaderWriter {
+    read: extern "C" fn(name: *const c_char) -> f64,
+    write: extern "C" fn(name: *const c_char, value: f64)
+}
+
+impl HostSimulatorReaderWriter {
+    pub fn new(read: extern "C" fn(name: *const c_char) -> f64, write: extern "C" fn(name: *const c_char, value: f64)) -> HostSimulatorReaderWriter {
+        HostSimulatorReaderWriter {
+            read,
+            write
+        }
+    }
+
+    fn c_name(name: &str) -> CString {
+        CString::new(name).expect("Simulation variable names cannot contain NUL characters.")
+    }
+}
+
+impl SimulatorReaderWriter for HostSimulatorReaderWriter {
+    fn read(&mut self, name: &str) -> Option<f64> {
+        let name = HostSimulatorReaderWriter::c_name(name);
+        Some((self.read)(name.as_ptr())).filter(|value| !value.is_nan())
+    }
+
+    fn write(&mut self, name: &str, value: f64) {
+        let name = HostSimulatorReaderWriter::c_name(name);
+        (self.write)(name.as_ptr(), value);
+    }
+}
+
+#[cfg(test)]
+mod simulator_tests {
+    use std::{cell::RefCell, ffi::CStr};
+    use super::*;
+
+    struct Lamp {
+        input: bool,
+        illuminated: bool
+    }
+
+    impl SimulatorElement for Lamp {
+        fn read(&mut self, reader: &mut SimulatorReader) {
+            if let Some(input) = reader.read_bool("LAMP_SWITCH") {
+                self.input = input;
+            }
+        }
+
+        fn write(&self, writer: &mut SimulatorWriter) {
+            writer.write_bool("LAMP_IS_ILLUMINATED", self.illuminated);
+        }
+    }
+
+    struct Panel {
+        lamp: Lamp,
+        brightness: f64
+    }
+
+    impl SimulatorElement for Panel {
+        fn accept<T: SimulatorElementVisitor>(&mut self, visitor: &mut T) {
+            self.lamp.accept(visitor);
+            visitor.visit(self);
+        }
+
+        fn read(&mut self, reader: &mut SimulatorReader) {
+            if let Some(brightness) = reader.read_f64("PANEL_BRIGHTNESS") {
+                self.brightness = brightness;
+            }
+        }
+    }
+
+    fn panel() -> Panel {
+        Panel {
+            lamp: Lamp { input: false, illuminated: true },
+            brightness: 0.
+        }
+    }
+
+    #[test]
+    fn in_memory_reads_written_value() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        reader_writer.write("PANEL_BRIGHTNESS", 0.5);
+
+        assert_eq!(reader_writer.read("PANEL_BRIGHTNESS"), Some(0.5));
+    }
+
+    #[test]
+    fn in_memory_reads_unwritten_value_as_missing() {
+        assert_eq!(InMemorySimulatorReaderWriter::new().read("PANEL_BRIGHTNESS"), None);
+    }
+
+    #[test]
+    fn reading_missing_variable_keeps_current_state() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        reader_writer.write("PANEL_BRIGHTNESS", 0.5);
+
+        let mut panel = panel();
+        panel.lamp.input = true;
+        read_from_simulator(&mut panel, &mut reader_writer);
+
+        assert!(panel.lamp.input);
+        assert_eq!(panel.brightness, 0.5);
+    }
+
+    #[test]
+    fn reading_visits_composed_elements() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        reader_writer.write("LAMP_SWITCH", 1.);
+        reader_writer.write("PANEL_BRIGHTNESS", 0.5);
+
+        let mut panel = panel();
+        read_from_simulator(&mut panel, &mut reader_writer);
+
+        assert!(panel.lamp.input);
+        assert_eq!(panel.brightness, 0.5);
+    }
+
+    #[test]
+    fn input_names_include_those_of_composed_elements() {
+        let mut panel = panel();
+        panel.lamp.input = true;
+
+        assert_eq!(input_names(&mut panel), vec!["LAMP_SWITCH", "PANEL_BRIGHTNESS"]);
+        assert!(panel.lamp.input);
+    }
+
+    #[test]
+    fn writing_visits_composed_elements() {
+        let mut reader_writer = InMemorySimulatorReaderWriter::new();
+        write_to_simulator(&mut panel(), &mut reader_writer);
+
+        assert_eq!(reader_writer.read("LAMP_IS_ILLUMINATED"), Some(1.));
+        assert_eq!(reader_writer.names(), vec!["LAMP_IS_ILLUMINATED"]);
+    }
+
+    thread_local! {
+        static HOST_VARIABLES: RefCell<BTreeMap<String, f64>> = const { RefCell::new(BTreeMap::new()) };
+    }
+
+    extern "C" fn host_read(name: *const c_char) -> f64 {
+        let name = unsafe { CStr::from_ptr(name) }.to_str().unwrap().to_string();
+        HOST_VARIABLES.with(|variables| *variables.borrow().get(&name).unwrap_or(&f64::NAN))
+    }
+
+    extern "C" fn host_write(name: *const c_char, value: f64) {
+        let name = unsafe { CStr::from_ptr(name) }.to_str().unwrap().to_string();
+        HOST_VARIABLES.with(|variables| variables.borrow_mut().insert(name, value));
+    }
+
+    #[test]
+    fn host_adapter_reads_and_writes_through_host_functions() {
+        let mut reader_writer = HostSimulatorReaderWriter::new(host_read, host_write);
+        reader_writer.write("PANEL_BRIGHTNESS", 0.25);
+
+        assert_eq!(reader_writer.read("PANEL_BRIGHTNESS"), Some(0.25));
+        assert_eq!(HOST_VARIABLES.with(|variables| variables.borrow().get("PANEL_BRIGHTNESS").copied()), Some(0.25));
+    }
+
+    #[test]
+    fn host_adapter_reads_nan_as_missing() {
+        assert_eq!(HostSimulatorReaderWriter::new(host_read, host_write).read("UNKNOWN_VARIABLE"), None);
+    }
+}
@@ -0,0 +1,506 @@
+use std::{error::Error, fmt, time::Duration};
+
+/// An element whose state can be saved to and restored from a snapshot.
+/// Elements composed of other elements visit the state of each of them.
+pub trait Stateful {
+    fn visit_state(&mut self, state: &mut dyn StateVisitor);
+}
+
+/// Visits the state of an element one value at a time. Saving a snapshot records the visited
+/// values, restoring a snapshot overwrites the same values, visited in the same order.
+/// Elements therefore visit their state with the same code for saving and restoring.
+pub trait StateVisitor {
+    /// Enters a scope, which qualifies the names of the values visited until the scope is left.
+    fn enter(&mut self, scope: &str);
+    fn leave(&mut self);
+
+    fn visit_bool(&mut self, name: &str, value: &mut bool);
+    fn visit_u64(&mut self, name: &str, value: &mut u64);
+    fn visit_f32(&mut self, name: &str, value: &mut f32);
+    fn visit_text(&mut self, name: &str, value: &mut String);
+
+    /// Visits the number of items in a collection. The items are visited afterwards.
+    fn visit_len(&mut self, name: &str, len: &mut usize);
+
+    /// Rejects the value with the given name, as the element cannot represent it.
+    fn reject(&mut self, name: &str);
+}
+
+/// Visits the state of the given element within the given scope.
+pub fn visit_element<T: Stateful + ?Sized>(state: &mut dyn StateVisitor, scope: &str, element: &mut T) {
+    state.enter(scope);
+    element.visit_state(state);
+    state.leave();
+}
+
+pub fn visit_duration(state: &mut dyn StateVisitor, name: &str, value: &mut Duration) {
+    let mut nanos = value.as_nanos() as u64;
+    state.visit_u64(name, &mut nanos);
+    *value = Duration::from_nanos(nanos);
+}
+
+/// Visits a value which is one of the given variants, by its index in the variants.
+pub fn visit_variant<T: Copy + PartialEq>(state: &mut dyn StateVisitor, name: &str, value: &mut T, variants: &[T]) {
+    let mut index = variants.iter().position(|variant| variant == value)
+        .expect("The variants must include every possible value.") as u64;
+    state.visit_u64(name, &mut index);
+
+    match variants.get(index as usize) {
+        Some(variant) => *value = *variant,
+        None => state.reject(name)
+    }
+}
+
+/// Visits the number of items in the given collection, creating or removing items to match
+/// the restored number. The caller visits the items afterwards.
+pub fn visit_collection<T>(state: &mut dyn StateVisitor, name: &str, items: &mut Vec<T>, new_item: impl FnMut() -> T) {
+    let mut len = items.len();
+    state.visit_len(name, &mut len);
+    items.resize_with(len, new_item);
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Value {
+    Bool(bool),
+    U64(u64),
+    /// The bits of the value, such that restoring it is exact.
+    F32(u32),
+    Text(String)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Entry {
+    name: String,
+    value: Value
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SnapshotError {
+    /// The text does not start with the snapshot header.
+    NotASnapshot,
+    UnsupportedVersion(u32),
+    /// The line with the given number cannot be parsed.
+    Malformed(usize),
+    /// The snapshot holds a different value than the element expects, e.g. because
+    /// the snapshot was saved from a different type of aircraft.
+    Mismatch { expected: String, found: String },
+    /// The snapshot ended before the value with the given name.
+    Missing(String),
+    /// The element's state ended before the value with the given name.
+    Unexpected(String),
+    /// The value with the given name cannot be represented by the element.
+    Invalid(String)
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::NotASnapshot => write!(f, "The text is not a snapshot."),
+            SnapshotError::UnsupportedVersion(version) => write!(f, "Snapshot version {} is not supported.", version),
+            SnapshotError::Malformed(line) => write!(f, "Line {} of the snapshot is malformed.", line),
+            SnapshotError::Mismatch { expected, found } => write!(f, "Expected value {} but found {}.", expected, found),
+            SnapshotError::Missing(name) => write!(f, "The snapshot is missing value {}.", name),
+            SnapshotError::Unexpected(name) => write!(f, "The snapshot has unexpected value {}.", name),
+            SnapshotError::Invalid(name) => write!(f, "The snapshot has invalid value {}.", name)
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+/// The state of an element at a moment in time, such as a whole simulation. Restoring the snapshot
+/// into an element of the same type makes it continue exactly as the saved element would have.
+/// Snapshots are stored as versioned text, with each value on a line of its own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    entries: Vec<Entry>
+}
+
+impl Snapshot {
+    pub const VERSION: u32 = 6;
+    const HEADER: &'static str = "AIRBUS SYSTEMS SNAPSHOT";
+
+    pub fn save<T: Stateful + ?Sized>(element: &mut T) -> Snapshot {
+        let mut saver = SnapshotSaver { scopes: vec![], entries: vec![] };
+        element.visit_state(&mut saver);
+
+        Snapshot {
+            entries: saver.entries
+        }
+    }
+
+    /// Restores the snapshot into the given element. When restoring fails, the element
+    /// is left partially restored and should be discarded.
+    pub fn restore<T: Stateful + ?Sized>(&self, element: &mut T) -> Result<(), SnapshotError> {
+        let mut restorer = SnapshotRestorer { entries: &self.entries, next: 0, scopes: vec![], error: None };
+        element.visit_state(&mut restorer);
+
+        match (restorer.error, self.entries.get(restorer.next)) {
+            (Some(error), _) => Err(error),
+            (None, Some(entry)) => Err(SnapshotError::Unexpected(entry.name.clone())),
+            (None, None) => Ok(())
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{} {}\n", Snapshot::HEADER, Snapshot::VERSION);
+        for entry in &self.entries {
+            let value = match &entry.value {
+                Value::Bool(value) => format!("b\t{}", *value as u8),
+                Value::U64(value) => format!("u\t{}", value),
+                Value::F32(bits) => format!("f\t{:08x}", bits),
+                Value::Text(value) => format!("s\t{}", escape(value))
+            };
+            text.push_str(&format!("{}\t{}\n", escape(&entry.name), value));
+        }
+
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Snapshot, SnapshotError> {
+        let mut lines = text.lines();
+        let version = lines.next().and_then(|header| header.strip_prefix(Snapshot::HEADER))
+            .and_then(|version| version.trim().parse::<u32>().ok())
+            .ok_or(SnapshotError::NotASnapshot)?;
+        if version != Snapshot::VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let entries = lines.enumerate().map(|(index, line)| {
+            // The header is the first line.
+            let line_number = index + 2;
+            Snapshot::parse_entry(line).ok_or(SnapshotError::Malformed(line_number))
+        }).collect::<Result<Vec<Entry>, SnapshotError>>()?;
+
+        Ok(Snapshot {
+            entries
+        })
+    }
+
+    fn parse_entry(line: &str) -> Option<Entry> {
+        let mut fields = line.splitn(3, '\t');
+        let name = unescape(fields.next()?)?;
+        let value = match (fields.next()?, fields.next()?) {
+            ("b", "0") => Value::Bool(false),
+            ("b", "1") => Value::Bool(true),
+            ("u", value) => Value::U64(value.parse().ok()?),
+            ("f", bits) => Value::F32(u32::from_str_radix(bits, 16).ok()?),
+            ("s", value) => Value::Text(unescape(value)?),
+            _ => return None
+        };
+
+        Some(Entry {
+            name,
+            value
+        })
+    }
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+pub(crate) fn unescape(text: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut characters = text.chars();
+    while let Some(character) = characters.next() {
+        if character == '\\' {
+            unescaped.push(match characters.next()? {
+                '\\' => '\\',
+                't' => '\t',
+                'n' => '\n',
+                _ => return None
+            });
+        } else {
+            unescaped.push(character);
+        }
+    }
+
+    Some(unescaped)
+}
+
+fn qualified_name(scopes: &[String], name: &str) -> String {
+    scopes.iter().map(|scope| scope.as_str()).chain(std::iter::once(name)).collect::<Vec<&str>>().join("/")
+}
+
+struct SnapshotSaver {
+    scopes: Vec<String>,
+    entries: Vec<Entry>
+}
+
+impl SnapshotSaver {
+    fn save(&mut self, name: &str, value: Value) {
+        self.entries.push(Entry { name: qualified_name(&self.scopes, name), value });
+    }
+}
+
+impl StateVisitor for SnapshotSaver {
+    fn enter(&mut self, scope: &str) {
+        self.scopes.push(String::from(scope));
+    }
+
+    fn leave(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn visit_bool(&mut self, name: &str, value: &mut bool) {
+        self.save(name, Value::Bool(*value));
+    }
+
+    fn visit_u64(&mut self, name: &str, value: &mut u64) {
+        self.save(name, Value::U64(*value));
+    }
+
+    fn visit_f32(&mut self, name: &str, value: &mut f32) {
+        self.save(name, Value::F32(value.to_bits()));
+    }
+
+    fn visit_text(&mut self, name: &str, value: &mut String) {
+        self.save(name, Value::Text(value.clone()));
+    }
+
+    fn visit_len(&mut self, name: &str, len: &mut usize) {
+        self.save(name, Value::U64(*len as u64));
+    }
+
+    fn reject(&mut self, name: &str) {
+        panic!("The value {} of the element's own state cannot be saved.", qualified_name(&self.scopes, name));
+    }
+}
+
+/// Restores the entries in order. After the first error, no further values are restored.
+struct SnapshotRestorer<'a> {
+    entries: &'a [Entry],
+    next: usize,
+    scopes: Vec<String>,
+    error: Option<SnapshotError>
+}
+
+impl<'a> SnapshotRestorer<'a> {
+    fn next_value(&mut self, name: &str) -> Option<&'a Value> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        let entries = self.entries;
+        let name = qualified_name(&self.scopes, name);
+        match entries.get(self.next) {
+            Some(entry) if entry.name == name => {
+                self.next += 1;
+                Some(&entry.value)
+            },
+            Some(entry) => {
+                self.error = Some(SnapshotError::Mismatch { expected: name, found: entry.name.clone() });
+                None
+            },
+            None => {
+                self.error = Some(SnapshotError::Missing(name));
+                None
+            }
+        }
+    }
+}
+
+impl<'a> StateVisitor for SnapshotRestorer<'a> {
+    fn enter(&mut self, scope: &str) {
+        self.scopes.push(String::from(scope));
+    }
+
+    fn leave(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn visit_bool(&mut self, name: &str, value: &mut bool) {
+        match self.next_value(name) {
+            Some(Value::Bool(restored)) => *value = *restored,
+            Some(_) => self.reject(name),
+            None => {}
+        }
+    }
+
+    fn visit_u64(&mut self, name: &str, value: &mut u64) {
+        match self.next_value(name) {
+            Some(Value::U64(restored)) => *value = *restored,
+            Some(_) => self.reject(name),
+            None => {}
+        }
+    }
+
+    fn visit_f32(&mut self, name: &str, value: &mut f32) {
+        match self.next_value(name) {
+            Some(Value::F32(bits)) => *value = f32::from_bits(*bits),
+            Some(_) => self.reject(name),
+            None => {}
+        }
+    }
+
+    fn visit_text(&mut self, name: &str, value: &mut String) {
+        match self.next_value(name) {
+            Some(Value::Text(restored)) => *value = restored.clone(),
+            Some(_) => self.reject(name),
+            None => {}
+        }
+    }
+
+    fn visit_len(&mut self, name: &str, len: &mut usize) {
+        // Each item has at least one value, which guards against allocating
+        // huge collections when restoring a corrupt snapshot.
+        let remaining = self.entries.len().saturating_sub(self.next + 1);
+        match self.next_value(name) {
+            Some(Value::U64(restored)) if *restored as usize <= remaining => *len = *restored as usize,
+            Some(_) => self.reject(name),
+            None => {}
+        }
+    }
+
+    fn reject(&mut self, name: &str) {
+        if self.error.is_none() {
+            self.error = Some(SnapshotError::Invalid(qualified_name(&self.scopes, name)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Mode {
+        Off,
+        On
+    }
+
+    struct Lamp {
+        id: String,
+        mode: Mode,
+        brightness: f32,
+        on_duration: Duration
+    }
+
+    impl Lamp {
+        fn new(id: &str) -> Lamp {
+            Lamp {
+                id: String::from(id),
+                mode: Mode::Off,
+                brightness: 0.,
+                on_duration: Duration::from_secs(0)
+            }
+        }
+    }
+
+    impl Stateful for Lamp {
+        fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+            state.visit_text("ID", &mut self.id);
+            visit_variant(state, "MODE", &mut self.mode, &[Mode::Off, Mode::On]);
+            state.visit_f32("BRIGHTNESS", &mut self.brightness);
+            visit_duration(state, "ON DURATION", &mut self.on_duration);
+        }
+    }
+
+    struct Panel {
+        lamps: Vec<Lamp>
+    }
+
+    impl Stateful for Panel {
+        fn visit_state(&mut self, state: &mut dyn StateVisitor) {
+            visit_collection(state, "LAMPS", &mut self.lamps, || Lamp::new(""));
+            for (index, lamp) in self.lamps.iter_mut().enumerate() {
+                visit_element(state, &format!("LAMP {}", index), lamp);
+            }
+        }
+    }
+
+    fn panel() -> Panel {
+        let mut lamp = Lamp::new("DOME\tLIGHT");
+        lamp.mode = Mode::On;
+        lamp.brightness = 1. / 3.;
+        lamp.on_duration = Duration::from_nanos(1_500_000_001);
+
+        Panel {
+            lamps: vec![lamp, Lamp::new("STORM")]
+        }
+    }
+
+    fn header() -> String {
+        format!("AIRBUS SYSTEMS SNAPSHOT {}\n", Snapshot::VERSION)
+    }
+
+    #[test]
+    fn restores_saved_state_exactly() {
+        let snapshot = Snapshot::save(&mut panel());
+        let mut restored = Panel { lamps: vec![] };
+        assert_eq!(snapshot.restore(&mut restored), Ok(()));
+
+        assert_eq!(restored.lamps.len(), 2);
+        assert_eq!(restored.lamps[0].id, "DOME\tLIGHT");
+        assert_eq!(restored.lamps[0].mode, Mode::On);
+        assert_eq!(restored.lamps[0].brightness.to_bits(), (1f32 / 3.).to_bits());
+        assert_eq!(restored.lamps[0].on_duration, Duration::from_nanos(1_500_000_001));
+        assert_eq!(restored.lamps[1].id, "STORM");
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let snapshot = Snapshot::save(&mut panel());
+
+        assert_eq!(Snapshot::from_text(&snapshot.to_text()), Ok(snapshot));
+    }
+
+    #[test]
+    fn text_starts_with_versioned_header() {
+        assert!(Snapshot::save(&mut panel()).to_text().starts_with(&header()));
+    }
+
+    #[test]
+    fn text_without_header_is_not_a_snapshot() {
+        assert_eq!(Snapshot::from_text("LAMPS\tu\t0\n"), Err(SnapshotError::NotASnapshot));
+    }
+
+    #[test]
+    fn unsupported_version_is_rejected() {
+        let version = Snapshot::VERSION + 1;
+
+        assert_eq!(Snapshot::from_text(&format!("AIRBUS SYSTEMS SNAPSHOT {}\n", version)), Err(SnapshotError::UnsupportedVersion(version)));
+    }
+
+    #[test]
+    fn malformed_line_is_reported() {
+        assert_eq!(Snapshot::from_text(&format!("{}LAMPS\tu\t0\nLAMP 0/ID\tx\t1\n", header())), Err(SnapshotError::Malformed(3)));
+    }
+
+    #[test]
+    fn restoring_into_different_element_is_a_mismatch() {
+        let snapshot = Snapshot::save(&mut Lamp::new("DOME"));
+
+        assert_eq!(snapshot.restore(&mut panel()), Err(SnapshotError::Mismatch { expected: String::from("LAMPS"), found: String::from("ID") }));
+    }
+
+    #[test]
+    fn restoring_incomplete_snapshot_reports_missing_value() {
+        let snapshot = Snapshot::from_text(&format!("{}ID\ts\tDOME\n", header())).unwrap();
+
+        assert_eq!(snapshot.restore(&mut Lamp::new("")), Err(SnapshotError::Missing(String::from("MODE"))));
+    }
+
+    #[test]
+    fn restoring_snapshot_with_extra_values_reports_unexpected_value() {
+        let mut text = Snapshot::save(&mut Lamp::new("DOME")).to_text();
+        text.push_str("COLOUR\tu\t1\n");
+
+        assert_eq!(Snapshot::from_text(&text).unwrap().restore(&mut Lamp::new("")), Err(SnapshotError::Unexpected(String::from("COLOUR"))));
+    }
+
+    #[test]
+    fn restoring_unknown_variant_is_invalid() {
+        let text = Snapshot::save(&mut Lamp::new("DOME")).to_text().replace("MODE\tu\t0", "MODE\tu\t7");
+
+        assert_eq!(Snapshot::from_text(&text).unwrap().restore(&mut Lamp::new("")), Err(SnapshotError::Invalid(String::from("MODE"))));
+    }
+
+    #[test]
+    fn restoring_collection_larger_than_snapshot_is_invalid() {
+        let text = Snapshot::save(&mut Panel { lamps: vec![] }).to_text().replace("LAMPS\tu\t0", "LAMPS\tu\t1000000000");
+
+        assert_eq!(Snapshot::from_text(&text).unwrap().restore(&mut Panel { lamps: vec![] }), Err(SnapshotError::Invalid(String::from("LAMPS"))));
+    }
+}
@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SampleValue {
+    Number(f64),
+    Bool(bool),
+    Text(String)
+}
+
+/// The named values of an aircraft's outputs at a moment in time.
+pub type Sample = Vec<(String, SampleValue)>;
+
+/// A series of samples over time, holding the selected columns. Time series are written as CSV
+/// or JSON, with the simulation time in seconds as the first column.
+pub struct TimeSeries {
+    columns: Vec<String>,
+    rows: Vec<(Duration, Vec<SampleValue>)>
+}
+
+impl TimeSeries {
+    pub fn new(columns: Vec<String>) -> TimeSeries {
+        TimeSeries {
+            columns,
+            rows: vec![]
+        }
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Records the selected columns of the sample. Columns missing from the sample are recorded as empty text.
+    pub fn record(&mut self, time: Duration, sample: Sample) {
+        let values = self.columns.iter().map(|column| {
+            sample.iter().find(|(name, _)| name == column).map(|(_, value)| value.clone()).unwrap_or_else(|| SampleValue::Text(String::new()))
+        }).collect();
+
+        self.rows.push((time, values));
+    }
+
+    /// Booleans are written as 1 and 0, such that they can be plotted.
+    pub fn to_csv(&self) -> String {
+        let header = std::iter::once("time").chain(self.columns.iter().map(|column| column.as_str()))
+            .map(TimeSeries::csv_field).collect::<Vec<String>>().join(",");
+        let mut csv = format!("{}\n", header);
+        for (time, values) in self.rows.iter() {
+            let fields = std::iter::once(time.as_secs_f64().to_string()).chain(values.iter().map(|value| match value {
+                SampleValue::Number(number) => number.to_string(),
+                SampleValue::Bool(value) => String::from(if *value { "1" } else { "0" }),
+                SampleValue::Text(text) => TimeSeries::csv_field(text)
+            })).collect::<Vec<String>>();
+            csv.push_str(&format!("{}\n", fields.join(",")));
+        }
+
+        csv
+    }
+
+    fn csv_field(text: &str) -> String {
+        if text.contains([',', '"', '\n']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            String::from(text)
+        }
+    }
+
+    /// Writes an array holding an object per sample. Numbers which JSON cannot represent are written as null.
+    pub fn to_json(&self) -> String {
+        let objects = self.rows.iter().map(|(time, values)| {
+            let members = std::iter::once(format!("\"time\":{}", time.as_secs_f64()))
+                .chain(self.columns.iter().zip(values.iter()).map(|(column, value)| {
+                    let value = match value {
+                        SampleValue::Number(number) if number.is_finite() => number.to_string(),
+                        SampleValue::Number(_) => String::from("null"),
+                        SampleValue::Bool(value) => value.to_string(),
+                        SampleValue::Text(text) => TimeSeries::json_string(text)
+                    };
+                    format!("{}:{}", TimeSeries::json_string(column), value)
+                })).collect::<Vec<String>>();
+
+            format!("{{{}}}", members.join(","))
+        }).collect::<Vec<String>>();
+
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    }
+
+    fn json_string(text: &str) -> String {
+        let mut json = String::from("\"");
+        for character in text.chars() {
+            match character {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                '\n' => json.push_str("\\n"),
+                character if character.is_control() => json.push_str(&format!("\\u{:04x}", character as u32)),
+                character => json.push(character)
+            }
+        }
+        json.push('"');
+
+        json
+    }
+}
+
+#[cfg(test)]
+mod time_series_tests {
+    use super::*;
+
+    fn time_series() -> TimeSeries {
+        let mut time_series = TimeSeries::new(vec![String::from("AC BUS 1 SOURCE"), String::from("9XU1 CLOSED"), String::from("BAT 1 CHARGE")]);
+        time_series.record(Duration::from_millis(500), vec![
+            (String::from("BAT 1 CHARGE"), SampleValue::Number(23.)),
+            (String::from("AC BUS 1 SOURCE"), SampleValue::Text(String::from("GEN 1"))),
+            (String::from("9XU1 CLOSED"), SampleValue::Bool(true)),
+            (String::from("AC BUS 2 SOURCE"), SampleValue::Text(String::from("GEN 2")))
+        ]);
+
+        time_series
+    }
+
+    #[test]
+    fn csv_has_header_and_selected_columns_in_order() {
+        assert_eq!(time_series().to_csv(), "time,AC BUS 1 SOURCE,9XU1 CLOSED,BAT 1 CHARGE\n0.5,GEN 1,1,23\n");
+    }
+
+    #[test]
+    fn json_has_object_per_sample() {
+        assert_eq!(time_series().to_json(), "[\n{\"time\":0.5,\"AC BUS 1 SOURCE\":\"GEN 1\",\"9XU1 CLOSED\":true,\"BAT 1 CHARGE\":23}\n]\n");
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_commas_and_quotes() {
+        let mut time_series = TimeSeries::new(vec![String::from("NOTE")]);
+        time_series.record(Duration::from_secs(1), vec![(String::from("NOTE"), SampleValue::Text(String::from("A, \"B\"")))]);
+
+        assert_eq!(time_series.to_csv(), "time,NOTE\n1,\"A, \"\"B\"\"\"\n");
+    }
+
+    #[test]
+    fn json_writes_non_finite_numbers_as_null() {
+        let mut time_series = TimeSeries::new(vec![String::from("LOAD")]);
+        time_series.record(Duration::from_secs(1), vec![(String::from("LOAD"), SampleValue::Number(f64::NAN))]);
+
+        assert_eq!(time_series.to_json(), "[\n{\"time\":1,\"LOAD\":null}\n]\n");
+    }
+
+    #[test]
+    fn missing_column_is_recorded_empty() {
+        let mut time_series = TimeSeries::new(vec![String::from("LOAD")]);
+        time_series.record(Duration::from_secs(1), vec![]);
+
+        assert_eq!(time_series.to_csv(), "time,LOAD\n1,\n");
+    }
+}